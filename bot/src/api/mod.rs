@@ -1,23 +1,104 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use futures::future::join_all;
-use octocrab::models::{
-    activity::Notification, issues::Comment, pulls::PullRequest, CommentId, NotificationId,
-    RateLimit,
+use octocrab::{
+    models::{
+        activity::Notification, issues::Comment, pulls::PullRequest, AuthorAssociation, CommentId,
+        NotificationId, RateLimit, Repository,
+    },
+    Page,
 };
+use serde::Deserialize;
+use shared::{reason_hash, Base58CryptoHash, Score};
 use tracing::{error, info, instrument};
 
-use crate::events::{actions::Action, commands::Command, Event, EventType};
+use crate::events::{
+    actions::Action,
+    commands::{BotScored, Command, LABEL_PREFIX},
+    Event, EventType,
+};
 
 pub use shared::github::*;
 
 pub mod prometheus;
+pub mod server;
 pub mod telegram;
+pub mod tracker;
+
+use tracker::{content_hash, CommentChange, CommentTracker};
+
+// Permissions are rechecked after this time, so the reactions don't cost a request per reviewer
+const PERMISSION_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const LABEL_SEARCH_OVERLAP: chrono::Duration = chrono::Duration::minutes(5);
+// GitHub rejects longer search queries, so the organizations are searched in chunks
+const MAX_SEARCH_QUERY_LENGTH: usize = 256;
+
+#[derive(Debug, Deserialize)]
+struct Actor {
+    login: String,
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reaction {
+    id: u64,
+    user: Option<Actor>,
+    content: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueEventLabel {
+    name: String,
+}
+
+// Only the fields of the labeled events
+#[derive(Debug, Deserialize)]
+struct IssueEvent {
+    id: u64,
+    actor: Option<Actor>,
+    event: String,
+    label: Option<IssueEventLabel>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitActor {
+    date: chrono::DateTime<chrono::Utc>,
+}
+
+// Only the fields of the events that hand the PR over between the author and the reviewers
+#[derive(Debug, Deserialize)]
+struct TimelineEvent {
+    event: Option<String>,
+    actor: Option<Actor>,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    // Reviews and commits don't have the creation time
+    submitted_at: Option<chrono::DateTime<chrono::Utc>>,
+    committer: Option<GitActor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollaboratorPermission {
+    permission: String,
+}
 
 #[derive(Clone)]
 pub struct GithubClient {
     octocrab: octocrab::Octocrab,
     prometheus: Arc<prometheus::PrometheusClient>,
+    tracker: Arc<CommentTracker>,
+    // Reactions are tracked by the reacted user, as only the latest one counts
+    reactions: Arc<CommentTracker<String>>,
+    // Ids of our status comments by the PR, as they don't change
+    status_comments: Arc<Mutex<HashMap<String, CommentId>>>,
+    // Maintainer permissions by the repo and the user with the time they were checked
+    permissions: Arc<Mutex<HashMap<(String, String), (bool, Instant)>>>,
+    labels_checked_at: Arc<Mutex<Option<chrono::DateTime<chrono::Utc>>>>,
     pub user_handle: String,
 }
 
@@ -35,6 +116,11 @@ impl GithubClient {
             octocrab,
             user_handle,
             prometheus,
+            tracker: Default::default(),
+            reactions: Default::default(),
+            status_comments: Default::default(),
+            permissions: Default::default(),
+            labels_checked_at: Default::default(),
         })
     }
 
@@ -79,7 +165,7 @@ impl GithubClient {
                 }
             };
 
-            let pr_metadata = match PrMetadata::try_from(pr) {
+            let mut pr_metadata = match PrMetadata::try_from(pr) {
                 Ok(pr) => pr,
                 Err(e) => {
                     error!("Failed to convert PR: {:?}", e);
@@ -87,66 +173,26 @@ impl GithubClient {
                 }
             };
 
-            let comments = self
-                .octocrab
-                .issues(&pr_metadata.owner, &pr_metadata.repo)
-                .list_comments(pr_metadata.number)
-                .per_page(100)
-                .send()
-                .await;
-
-            let comments = match comments {
+            let (first_bot_comment, comments) = match self.get_comments(&mut pr_metadata).await {
                 Ok(comments) => comments,
                 Err(e) => {
                     error!("Failed to get comments: {:?}", e);
                     return None;
                 }
             };
+            let found_us = first_bot_comment.is_some();
 
-            let comments = match self.octocrab.all_pages(comments).await {
-                Ok(comments) => comments,
-                Err(e) => {
-                    error!("Failed to get all comments: {:?}", e);
-                    return None;
-                }
-            };
-            let first_bot_comment = comments
-                .iter()
-                .find(|c| c.user.login == self.user_handle)
-                .cloned();
-
-            let mut results = Vec::new();
-            let mut found_us = false;
+            let mut results =
+                self.comment_events(&pr_metadata, &first_bot_comment, &comments, Some(event.id));
 
-            for comment in comments.into_iter().rev() {
-                if comment.user.login == self.user_handle {
-                    found_us = true;
-                    break;
-                }
-
-                if let Some(command) =
-                    Command::parse_command(&self.user_handle, &pr_metadata, &comment)
-                {
-                    results.push(Event {
-                        event: EventType::Command {
-                            command,
-                            notification_id: event.id,
-                            sender: User::new(comment.user.login, comment.author_association),
-                        },
-                        pr: pr_metadata.clone(),
-                        comment: first_bot_comment.clone(),
-                        event_time: comment.updated_at.unwrap_or(comment.created_at),
-                    });
-                }
-            }
-
-            // We haven
+            // We haven't replied yet, so the body might contain the include command
             if results.is_empty() && !found_us {
                 if let Some(command) = Command::parse_body(&self.user_handle, &pr_metadata) {
                     results.push(Event {
                         event: EventType::Command {
                             command,
-                            notification_id: event.id,
+                            notification_id: Some(event.id),
+                            label: None,
                             sender: pr_metadata.author.clone(),
                         },
                         pr: pr_metadata.clone(),
@@ -163,8 +209,40 @@ impl GithubClient {
                 }
             }
 
-            // To keep the chronological order, we reverse the results
-            results.reverse();
+            // Closed PR was removed from the race, so it's restored before the new commands
+            if event.reason == "state_change" && !pr_metadata.closed && pr_metadata.merged.is_none()
+            {
+                results.insert(
+                    0,
+                    Event {
+                        event: EventType::Action(Action::reopen()),
+                        pr: pr_metadata.clone(),
+                        comment: first_bot_comment.clone(),
+                        event_time: pr_metadata.updated_at,
+                    },
+                );
+            }
+
+            // Maintainers confirm the rating reversal with the `revert` command
+            let revert_confirmed = results.iter().any(|result| {
+                matches!(
+                    result.event,
+                    EventType::Command {
+                        command: Command::Revert(_),
+                        ..
+                    }
+                )
+            });
+            if let (false, false, Some(reverted)) =
+                (found_us, revert_confirmed, pr_metadata.reverted_pr())
+            {
+                results.push(Event {
+                    event: EventType::Action(Action::revert_detected(reverted)),
+                    pr: pr_metadata.clone(),
+                    comment: first_bot_comment.clone(),
+                    event_time: pr_metadata.updated_at,
+                });
+            }
 
             if pr_metadata.merged.is_some() {
                 results.push(Event {
@@ -187,6 +265,590 @@ impl GithubClient {
         Ok(results)
     }
 
+    // Edits and deletions don't trigger notifications, so we check the tracked PRs by ourselves
+    #[instrument(skip(self))]
+    pub async fn get_edited_events(&self) -> anyhow::Result<Vec<Event>> {
+        let mut results = vec![];
+        for full_id in self.tracker.tracked_prs() {
+            let mut parts = full_id.splitn(3, '/');
+            let (Some(owner), Some(repo), Some(Ok(number))) =
+                (parts.next(), parts.next(), parts.next().map(str::parse))
+            else {
+                error!("Failed to parse tracked PR id: {full_id}");
+                self.tracker.forget(&full_id);
+                continue;
+            };
+
+            let pr = match self.get_pull_request(owner, repo, number).await {
+                Ok(pr) => pr,
+                Err(e) => {
+                    error!("Failed to get PR {full_id}: {e}");
+                    continue;
+                }
+            };
+            let mut pr_metadata = match PrMetadata::try_from(pr) {
+                Ok(pr) => pr,
+                Err(e) => {
+                    error!("Failed to convert PR {full_id}: {e}");
+                    continue;
+                }
+            };
+            let (first_bot_comment, comments) = match self.get_comments(&mut pr_metadata).await {
+                Ok(comments) => comments,
+                Err(e) => {
+                    error!("Failed to get comments for {full_id}: {e}");
+                    continue;
+                }
+            };
+
+            results.extend(self.comment_events(&pr_metadata, &first_bot_comment, &comments, None));
+        }
+        Ok(results)
+    }
+
+    // Returns the command events in chronological order:
+    // - commands sent after our last reply, if we process a notification
+    // - commands that were edited since we have processed them
+    // - commands that were deleted since we have processed them
+    fn comment_events(
+        &self,
+        pr_metadata: &PrMetadata,
+        first_bot_comment: &Option<Comment>,
+        comments: &[CommandComment],
+        notification_id: Option<NotificationId>,
+    ) -> Vec<Event> {
+        let last_bot_comment = comments
+            .iter()
+            .rposition(|c| c.user.login == self.user_handle);
+
+        let mut results: Vec<Event> = self
+            .tracker
+            .take_deleted(
+                &pr_metadata.full_id,
+                &comments.iter().map(|c| c.id).collect::<Vec<_>>(),
+            )
+            .into_iter()
+            .flat_map(|deleted| {
+                let sender = deleted.sender;
+                deleted.commands.into_iter().map(move |command| Event {
+                    event: EventType::Deleted {
+                        command,
+                        sender: sender.clone(),
+                    },
+                    pr: pr_metadata.clone(),
+                    comment: first_bot_comment.clone(),
+                    event_time: chrono::Utc::now(),
+                })
+            })
+            .collect();
+
+        for (index, comment) in comments.iter().enumerate() {
+            if comment.user.login == self.user_handle {
+                continue;
+            }
+            let commands = Command::parse_command(&self.user_handle, pr_metadata, comment);
+            if commands.is_empty() {
+                continue;
+            }
+
+            let sender = comment.user.clone();
+            let change = self.tracker.track(
+                &pr_metadata.full_id,
+                comment.id,
+                content_hash(&comment.body),
+                commands.clone(),
+                sender.clone(),
+            );
+
+            let not_replied = last_bot_comment.map_or(true, |last| index > last);
+            let should_execute = if not_replied {
+                // New commands are executed on the notification, so we don't execute them twice
+                notification_id.is_some()
+            } else {
+                // If we were restarted, we don't know if the older comments were edited
+                matches!(change, CommentChange::Edited)
+            };
+            if should_execute && !not_replied {
+                info!(
+                    "Command comment {} was edited in PR: {}",
+                    comment.id, pr_metadata.full_id
+                );
+            }
+
+            if should_execute {
+                results.extend(commands.into_iter().map(|command| Event {
+                    event: EventType::Command {
+                        command,
+                        notification_id,
+                        label: None,
+                        sender: sender.clone(),
+                    },
+                    pr: pr_metadata.clone(),
+                    comment: first_bot_comment.clone(),
+                    event_time: comment.updated_at,
+                }));
+            }
+        }
+
+        results
+    }
+
+    // Collects the issue comments, reviews and review comments of the PR in chronological order
+    // and sets the review states of the PR. Also returns our status comment
+    async fn get_comments(
+        &self,
+        pr_metadata: &mut PrMetadata,
+    ) -> anyhow::Result<(Option<Comment>, Vec<CommandComment>)> {
+        let (owner, repo, number) = (&pr_metadata.owner, &pr_metadata.repo, pr_metadata.number);
+
+        let page = self
+            .octocrab
+            .issues(owner, repo)
+            .list_comments(number)
+            .per_page(100)
+            .send()
+            .await?;
+        let issue_comments = self.octocrab.all_pages(page).await?;
+
+        let page = self
+            .octocrab
+            .pulls(owner, repo)
+            .list_reviews(number)
+            .per_page(100)
+            .send()
+            .await?;
+        let reviews = self.octocrab.all_pages(page).await?;
+
+        let page = self
+            .octocrab
+            .pulls(owner, repo)
+            .list_comments(Some(number))
+            .per_page(100)
+            .send()
+            .await?;
+        let review_comments = self.octocrab.all_pages(page).await?;
+
+        let first_bot_comment = issue_comments
+            .iter()
+            .find(|c| c.user.login == self.user_handle)
+            .cloned();
+
+        pr_metadata.set_reviews(&reviews);
+
+        let mut comments: Vec<CommandComment> = issue_comments
+            .into_iter()
+            .map(CommandComment::from)
+            .chain(
+                reviews
+                    .into_iter()
+                    .filter_map(|review| CommandComment::try_from(review).ok())
+                    // Approvals without a summary are the most of the reviews
+                    .filter(|review| !review.body.is_empty()),
+            )
+            .chain(
+                review_comments
+                    .into_iter()
+                    .filter_map(|comment| CommandComment::try_from(comment).ok()),
+            )
+            .collect();
+        comments.sort_by_key(|comment| comment.created_at);
+
+        Ok((first_bot_comment, comments))
+    }
+
+    // Texts of the score reasons seen in the PR comments by their hash, as only the hash is on-chain
+    pub fn score_reasons(&self, pr: &PrMetadata) -> HashMap<Base58CryptoHash, String> {
+        self.tracker
+            .commands(&pr.full_id)
+            .into_iter()
+            .filter_map(|command| match command {
+                Command::Score(event) => event.reason,
+                _ => None,
+            })
+            .map(|reason| (reason_hash(&reason), reason))
+            .collect()
+    }
+
+    // The tracker is in-memory, so the PRs of the contract are watched again after a restart.
+    // Their commands are tracked on the first check, only the later edits are executed
+    pub fn watch_comments(&self, full_ids: impl IntoIterator<Item = String>) {
+        for full_id in full_ids {
+            self.tracker.watch(&full_id);
+        }
+    }
+
+    pub fn forget_comments(&self, pr: &PrMetadata) {
+        self.tracker.forget(&pr.full_id);
+        self.reactions.forget(&pr.full_id);
+        self.status_comments.lock().unwrap().remove(&pr.full_id);
+    }
+
+    pub async fn status_comment_id(&self, pr: &PrMetadata) -> anyhow::Result<Option<CommentId>> {
+        if let Some(id) = self.status_comments.lock().unwrap().get(&pr.full_id) {
+            return Ok(Some(*id));
+        }
+
+        let comment = self.get_bot_comment(&pr.owner, &pr.repo, pr.number).await?;
+        if let Some(comment) = &comment {
+            self.status_comments
+                .lock()
+                .unwrap()
+                .insert(pr.full_id.clone(), comment.id);
+        }
+        Ok(comment.map(|comment| comment.id))
+    }
+
+    // Converts the maintainer reactions on our status comment to the score commands.
+    // The events come without the status comment, the caller fetches it only if there are any
+    #[instrument(skip(self, pr_metadata, votes, reaction_scores), fields(pr = pr_metadata.full_id))]
+    pub async fn get_reaction_events(
+        &self,
+        pr_metadata: &PrMetadata,
+        comment_id: CommentId,
+        votes: &[Score],
+        reaction_scores: &BTreeMap<String, u8>,
+    ) -> anyhow::Result<Vec<Event>> {
+        let mut reactions: Vec<Reaction> = match self
+            .octocrab
+            .get(
+                format!(
+                    "/repos/{}/{}/issues/comments/{}/reactions",
+                    pr_metadata.owner, pr_metadata.repo, comment_id.0
+                ),
+                Some(&[("per_page", 100)]),
+            )
+            .await
+        {
+            Ok(reactions) => reactions,
+            Err(e) => {
+                // The status comment might be deleted, so it's looked up again next time
+                self.status_comments
+                    .lock()
+                    .unwrap()
+                    .remove(&pr_metadata.full_id);
+                return Err(e.into());
+            }
+        };
+        reactions.sort_by_key(|reaction| reaction.created_at);
+
+        // The latest reaction of the user is the score
+        let mut scores: HashMap<String, (Reaction, u8)> = HashMap::new();
+        for reaction in reactions {
+            let (Some(user), Some(score)) =
+                (&reaction.user, reaction_scores.get(&reaction.content))
+            else {
+                continue;
+            };
+            if user.login == self.user_handle || user.login == pr_metadata.author.login {
+                continue;
+            }
+            scores.insert(user.login.clone(), (reaction, *score));
+        }
+
+        let mut present = Vec::with_capacity(scores.len());
+        let mut maintainers = Vec::with_capacity(scores.len());
+        for (login, (reaction, score)) in scores {
+            match self
+                .is_maintainer(&pr_metadata.owner, &pr_metadata.repo, &login)
+                .await
+            {
+                Ok(true) => {
+                    present.push(login.clone());
+                    maintainers.push((login, reaction, score));
+                }
+                Ok(false) => {}
+                // The reaction is kept, so a failed request doesn't take the score back
+                Err(e) => {
+                    error!("Failed to get permissions of {login}: {e}");
+                    present.push(login);
+                }
+            }
+        }
+        let mut results: Vec<Event> = self
+            .reactions
+            .take_deleted(&pr_metadata.full_id, &present)
+            .into_iter()
+            .flat_map(|deleted| {
+                let sender = deleted.sender;
+                deleted.commands.into_iter().map(move |command| Event {
+                    event: EventType::Deleted {
+                        command,
+                        sender: sender.clone(),
+                    },
+                    pr: pr_metadata.clone(),
+                    comment: None,
+                    event_time: chrono::Utc::now(),
+                })
+            })
+            .collect();
+
+        maintainers.sort_by_key(|(_, reaction, _)| reaction.created_at);
+        for (login, reaction, score) in maintainers {
+            let id = reaction.user.map(|user| user.id).unwrap_or_default();
+            let sender = User::new(login.clone(), AuthorAssociation::Collaborator).with_id(id);
+            let command = BotScored::from_reaction(score, reaction.created_at, reaction.id);
+            let change = self.reactions.track(
+                &pr_metadata.full_id,
+                login.clone(),
+                score as u64,
+                vec![command.clone()],
+                sender.clone(),
+            );
+            // The tracker is empty after a restart, so the scores that are already on-chain are skipped
+            let voted = votes
+                .iter()
+                .any(|vote| vote.user == login && vote.score == score as u32);
+            match change {
+                CommentChange::Unchanged => continue,
+                CommentChange::New if voted => continue,
+                _ => {}
+            }
+
+            results.push(Event {
+                event: EventType::Command {
+                    command,
+                    notification_id: None,
+                    label: None,
+                    sender,
+                },
+                pr: pr_metadata.clone(),
+                comment: None,
+                event_time: reaction.created_at,
+            });
+        }
+
+        Ok(results)
+    }
+
+    async fn is_maintainer(&self, owner: &str, repo: &str, user: &str) -> anyhow::Result<bool> {
+        let key = (format!("{owner}/{repo}"), user.to_string());
+        if let Some((maintainer, checked_at)) = self.permissions.lock().unwrap().get(&key) {
+            if checked_at.elapsed() < PERMISSION_CACHE_TTL {
+                return Ok(*maintainer);
+            }
+        }
+
+        let permission: CollaboratorPermission = self
+            .octocrab
+            .get(
+                format!("/repos/{owner}/{repo}/collaborators/{user}/permission"),
+                None::<&()>,
+            )
+            .await?;
+        let maintainer = matches!(
+            permission.permission.as_str(),
+            "admin" | "maintain" | "write"
+        );
+        self.permissions
+            .lock()
+            .unwrap()
+            .insert(key, (maintainer, Instant::now()));
+        Ok(maintainer)
+    }
+
+    // Converts the command labels applied by maintainers to the commands
+    #[instrument(skip(self, pr_metadata), fields(pr = pr_metadata.full_id))]
+    pub async fn get_label_events(&self, pr_metadata: &PrMetadata) -> anyhow::Result<Vec<Event>> {
+        let labels: Vec<&String> = pr_metadata
+            .labels
+            .iter()
+            .filter(|label| Command::is_command_label(label))
+            .collect();
+        if labels.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let page: Page<IssueEvent> = self
+            .octocrab
+            .get(
+                format!(
+                    "/repos/{}/{}/issues/{}/events",
+                    pr_metadata.owner, pr_metadata.repo, pr_metadata.number
+                ),
+                Some(&[("per_page", 100)]),
+            )
+            .await?;
+        let issue_events = self.octocrab.all_pages(page).await?;
+        let comment = self
+            .get_bot_comment(&pr_metadata.owner, &pr_metadata.repo, pr_metadata.number)
+            .await?;
+
+        let mut results = vec![];
+        for label in labels {
+            // The latest one, as the label could be applied several times
+            let Some((event, actor)) = issue_events
+                .iter()
+                .rev()
+                .filter(|event| {
+                    event.event == "labeled" && event.label.as_ref().map(|l| &l.name) == Some(label)
+                })
+                .find_map(|event| event.actor.as_ref().map(|actor| (event, actor)))
+            else {
+                continue;
+            };
+
+            if !self
+                .is_maintainer(&pr_metadata.owner, &pr_metadata.repo, &actor.login)
+                .await?
+            {
+                info!(
+                    "Label {label} was applied by non-maintainer {} in PR: {}. Skipping",
+                    actor.login, pr_metadata.full_id
+                );
+                continue;
+            }
+
+            let sender =
+                User::new(actor.login.clone(), AuthorAssociation::Collaborator).with_id(actor.id);
+            let label_comment = CommandComment {
+                id: CommentRef {
+                    source: CommentSource::Label,
+                    id: event.id,
+                },
+                body: label.clone(),
+                user: sender.clone(),
+                review_state: None,
+                created_at: event.created_at,
+                updated_at: event.created_at,
+            };
+            let Some(command) = Command::parse_label(label, &label_comment) else {
+                continue;
+            };
+
+            results.push(Event {
+                event: EventType::Command {
+                    command,
+                    notification_id: None,
+                    sender,
+                    label: Some(label.clone()),
+                },
+                pr: pr_metadata.clone(),
+                comment: comment.clone(),
+                event_time: event.created_at,
+            });
+        }
+
+        Ok(results)
+    }
+
+    // Open PRs with the command labels in the organizations that were updated since the last check,
+    // as applying a label updates the PR. The first check after a restart looks at all of them
+    #[instrument(skip(self, labels))]
+    pub async fn get_labeled_prs(
+        &self,
+        organizations: &[String],
+        labels: &[String],
+    ) -> anyhow::Result<Vec<(String, String, u64)>> {
+        if organizations.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let checked_at = chrono::Utc::now();
+        let labels = labels
+            .iter()
+            .map(|label| format!("\"{label}\""))
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut base = format!("is:pr is:open label:{labels}");
+        if let Some(since) = *self.labels_checked_at.lock().unwrap() {
+            base.push_str(&format!(
+                " updated:>={}",
+                since.format("%Y-%m-%dT%H:%M:%SZ")
+            ));
+        }
+
+        let mut queries = vec![];
+        let mut query = base.clone();
+        for organization in organizations {
+            let qualifier = format!(" org:{organization}");
+            if query.len() + qualifier.len() > MAX_SEARCH_QUERY_LENGTH && query != base {
+                queries.push(std::mem::replace(&mut query, base.clone()));
+            }
+            query.push_str(&qualifier);
+        }
+        queries.push(query);
+
+        let mut issues = vec![];
+        for query in queries {
+            let page = self
+                .octocrab
+                .search()
+                .issues_and_pull_requests(&query)
+                .per_page(100)
+                .send()
+                .await?;
+            issues.extend(self.octocrab.all_pages(page).await?);
+        }
+        // The search index lags a bit behind, so the checks overlap
+        *self.labels_checked_at.lock().unwrap() = Some(checked_at - LABEL_SEARCH_OVERLAP);
+
+        Ok(issues
+            .into_iter()
+            .filter_map(|issue| {
+                // https://api.github.com/repos/{owner}/{repo}
+                let mut segments = issue.repository_url.path_segments()?.rev();
+                let repo = segments.next()?.to_string();
+                let owner = segments.next()?.to_string();
+                Some((owner, repo, issue.number))
+            })
+            .collect())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn remove_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        label: &str,
+    ) -> anyhow::Result<()> {
+        self.prometheus.add_write_request();
+        self.octocrab
+            .issues(owner, repo)
+            .remove_label(number, label)
+            .await?;
+        Ok(())
+    }
+
+    // Keeps the only status label on the PR
+    #[instrument(skip(self))]
+    pub async fn sync_status_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        status_label: &str,
+    ) -> anyhow::Result<()> {
+        let page = self
+            .octocrab
+            .issues(owner, repo)
+            .list_labels_for_issue(number)
+            .per_page(100)
+            .send()
+            .await?;
+        let labels = self.octocrab.all_pages(page).await?;
+
+        let mut has_status = false;
+        for label in labels {
+            if label.name == status_label {
+                has_status = true;
+            } else if label.name.starts_with(LABEL_PREFIX)
+                && !Command::is_command_label(&label.name)
+            {
+                self.remove_label(owner, repo, number, &label.name).await?;
+            }
+        }
+
+        if !has_status {
+            self.prometheus.add_write_request();
+            self.octocrab
+                .issues(owner, repo)
+                .add_labels(number, &[status_label.to_string()])
+                .await?;
+        }
+        Ok(())
+    }
+
     #[instrument(skip(self), fields(notification = notification.id.0))]
     pub async fn get_pull_request_from_notification(
         &self,
@@ -221,6 +883,20 @@ impl GithubClient {
         Ok(pull_request)
     }
 
+    // GitHub redirects the old name of the renamed or transferred repo to the new one
+    #[instrument(skip(self))]
+    pub async fn get_repo(&self, owner: &str, repo: &str) -> anyhow::Result<Repository> {
+        Ok(self.octocrab.repos(owner, repo).get().await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_repo_by_id(&self, id: u64) -> anyhow::Result<Repository> {
+        Ok(self
+            .octocrab
+            .get(format!("/repositories/{id}"), None::<&()>)
+            .await?)
+    }
+
     #[instrument(skip(self, text))]
     pub async fn reply(
         &self,
@@ -242,16 +918,36 @@ impl GithubClient {
         &self,
         owner: &str,
         repo: &str,
-        comment_id: u64,
+        comment_id: CommentRef,
     ) -> anyhow::Result<()> {
-        self.prometheus.add_write_request();
-        self.octocrab
-            .issues(owner, repo)
-            .create_comment_reaction(
-                comment_id,
-                octocrab::models::reactions::ReactionContent::PlusOne,
-            )
-            .await?;
+        match comment_id.source {
+            CommentSource::Issue => {
+                self.prometheus.add_write_request();
+                self.octocrab
+                    .issues(owner, repo)
+                    .create_comment_reaction(
+                        comment_id.id,
+                        octocrab::models::reactions::ReactionContent::PlusOne,
+                    )
+                    .await?;
+            }
+            CommentSource::ReviewComment => {
+                self.prometheus.add_write_request();
+                let _: octocrab::models::reactions::Reaction = self
+                    .octocrab
+                    .post(
+                        format!(
+                            "/repos/{owner}/{repo}/pulls/comments/{}/reactions",
+                            comment_id.id
+                        ),
+                        Some(&serde_json::json!({ "content": "+1" })),
+                    )
+                    .await?;
+            }
+            // GitHub doesn't support reactions on the review summaries
+            // and there is nothing to like for the reactions and the labels
+            CommentSource::Review | CommentSource::Reaction | CommentSource::Label => {}
+        }
 
         Ok(())
     }
@@ -317,6 +1013,73 @@ impl GithubClient {
         }
     }
 
+    // The latest bot comment that contains the marker, e.g. the stale warning
+    #[instrument(skip(self, marker))]
+    pub async fn get_last_bot_comment_with(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        marker: &str,
+    ) -> anyhow::Result<Option<Comment>> {
+        let page = self
+            .octocrab
+            .issues(owner, repo)
+            .list_comments(pr_number)
+            .per_page(100)
+            .send()
+            .await?;
+        let comments = self.octocrab.all_pages(page).await?;
+
+        Ok(comments.into_iter().rev().find(|comment| {
+            comment.user.login == self.user_handle
+                && comment
+                    .body
+                    .as_deref()
+                    .is_some_and(|body| body.contains(marker))
+        }))
+    }
+
+    // The latest review request came after the last commit, review or comment,
+    // so the PR waits on the reviewers and not on the author
+    #[instrument(skip(self, pr_metadata), fields(pr = pr_metadata.full_id))]
+    pub async fn is_waiting_on_review(&self, pr_metadata: &PrMetadata) -> anyhow::Result<bool> {
+        let page: Page<TimelineEvent> = self
+            .octocrab
+            .get(
+                format!(
+                    "/repos/{}/{}/issues/{}/timeline",
+                    pr_metadata.owner, pr_metadata.repo, pr_metadata.number
+                ),
+                Some(&[("per_page", 100)]),
+            )
+            .await?;
+        let events = self.octocrab.all_pages(page).await?;
+
+        let last = events
+            .into_iter()
+            .filter_map(|event| {
+                let time = match event.event.as_deref()? {
+                    "review_requested" | "reviewed" => event.created_at.or(event.submitted_at),
+                    "committed" => event.committer.as_ref().map(|committer| committer.date),
+                    // Our own comments don't hand the PR over
+                    "commented"
+                        if event
+                            .actor
+                            .as_ref()
+                            .is_some_and(|actor| actor.login != self.user_handle) =>
+                    {
+                        event.created_at
+                    }
+                    _ => None,
+                }?;
+                Some((time, event.event.as_deref() == Some("review_requested")))
+            })
+            .max_by_key(|(time, _)| *time);
+
+        Ok(last.is_some_and(|(_, requested)| requested))
+    }
+
     pub async fn get_rate_limits(&self) -> anyhow::Result<RateLimit> {
         Ok(self.octocrab.ratelimit().get().await?)
     }
@@ -15,10 +15,22 @@ pub enum EventType {
     Pause,
     Unpause,
     Excluded,
+    Team,
+    ReferredBy,
+    LinkAccount,
+    OptOut,
+    Snooze,
+    Revert,
     Unknown,
     Merge,
     Finalize,
     Stale,
+    StaleWarning,
+    ReadyForReview,
+    ConvertedToDraft,
+    Reopen,
+    RevertDetected,
+    Deleted,
 }
 
 impl From<&crate::events::EventType> for EventType {
@@ -30,12 +42,24 @@ impl From<&crate::events::EventType> for EventType {
                 crate::events::commands::Command::Pause(_) => EventType::Pause,
                 crate::events::commands::Command::Unpause(_) => EventType::Unpause,
                 crate::events::commands::Command::Excluded(_) => EventType::Excluded,
+                crate::events::commands::Command::Team(_) => EventType::Team,
+                crate::events::commands::Command::ReferredBy(_) => EventType::ReferredBy,
+                crate::events::commands::Command::LinkAccount(_) => EventType::LinkAccount,
+                crate::events::commands::Command::OptOut(_) => EventType::OptOut,
+                crate::events::commands::Command::Snooze(_) => EventType::Snooze,
+                crate::events::commands::Command::Revert(_) => EventType::Revert,
                 crate::events::commands::Command::Unknown(_) => EventType::Unknown,
             },
+            crate::events::EventType::Deleted { .. } => EventType::Deleted,
             crate::events::EventType::Action(action) => match action {
                 crate::events::actions::Action::Merge(_) => EventType::Merge,
                 crate::events::actions::Action::Finalize(_) => EventType::Finalize,
                 crate::events::actions::Action::Stale(_) => EventType::Stale,
+                crate::events::actions::Action::StaleWarning(_) => EventType::StaleWarning,
+                crate::events::actions::Action::ReadyForReview(_) => EventType::ReadyForReview,
+                crate::events::actions::Action::ConvertedToDraft(_) => EventType::ConvertedToDraft,
+                crate::events::actions::Action::Reopen(_) => EventType::Reopen,
+                crate::events::actions::Action::RevertDetected(_) => EventType::RevertDetected,
             },
         }
     }
@@ -0,0 +1,53 @@
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde_json::json;
+use tracing::instrument;
+
+use shared::github::PrMetadata;
+
+// Only the hashes of the score reasons are stored on-chain, so the texts are sent to the server.
+// The server checks them against the contract, so there is no need in the authorization
+#[derive(Clone)]
+pub struct ServerClient {
+    client: Client,
+    url: Option<String>,
+}
+
+impl ServerClient {
+    pub fn new(url: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.map(|url| url.trim_end_matches('/').to_string()),
+        }
+    }
+
+    #[instrument(skip(self, pr, reason), fields(pr = pr.full_id))]
+    pub async fn send_reason(
+        &self,
+        pr: &PrMetadata,
+        reviewer: &str,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let Some(url) = &self.url else {
+            return Ok(());
+        };
+
+        let body = json!({
+            "organization": pr.owner,
+            "repo": pr.repo,
+            "number": pr.number,
+            "reviewer": reviewer,
+            "reason": reason,
+        });
+        let response = self
+            .client
+            .post(format!("{url}/reasons"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Server rejected the reason: HTTP {}", response.status());
+        }
+        Ok(())
+    }
+}
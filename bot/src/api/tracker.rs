@@ -0,0 +1,184 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+use shared::github::{CommentRef, User};
+
+use crate::events::commands::Command;
+
+#[derive(Debug, Clone)]
+pub struct TrackedComment {
+    pub hash: u64,
+    // A comment may contain several commands
+    pub commands: Vec<Command>,
+    pub sender: User,
+}
+
+pub enum CommentChange {
+    New,
+    Edited,
+    Unchanged,
+}
+
+// Keeps the commands of the active PRs, so we can react on edits and deletions.
+// Comments are tracked by their id and reactions by the reacted user
+pub struct CommentTracker<K = CommentRef> {
+    prs: Mutex<HashMap<String, HashMap<K, TrackedComment>>>,
+}
+
+impl<K> Default for CommentTracker<K> {
+    fn default() -> Self {
+        Self {
+            prs: Default::default(),
+        }
+    }
+}
+
+pub fn content_hash(content: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<K: Hash + Eq + Clone> CommentTracker<K> {
+    pub fn track(
+        &self,
+        pr: &str,
+        key: K,
+        hash: u64,
+        commands: Vec<Command>,
+        sender: User,
+    ) -> CommentChange {
+        let mut prs = self.prs.lock().unwrap();
+        let previous = prs.entry(pr.to_string()).or_default().insert(
+            key,
+            TrackedComment {
+                hash,
+                commands,
+                sender,
+            },
+        );
+
+        match previous {
+            None => CommentChange::New,
+            Some(previous) if previous.hash != hash => CommentChange::Edited,
+            Some(_) => CommentChange::Unchanged,
+        }
+    }
+
+    // Removes the entries that are not present on the PR anymore and returns them
+    pub fn take_deleted(&self, pr: &str, present: &[K]) -> Vec<TrackedComment> {
+        let mut prs = self.prs.lock().unwrap();
+        let Some(tracked) = prs.get_mut(pr) else {
+            return vec![];
+        };
+
+        let deleted: Vec<K> = tracked
+            .keys()
+            .filter(|key| !present.contains(key))
+            .cloned()
+            .collect();
+        deleted
+            .into_iter()
+            .filter_map(|key| tracked.remove(&key))
+            .collect()
+    }
+
+    pub fn commands(&self, pr: &str) -> Vec<Command> {
+        self.prs
+            .lock()
+            .unwrap()
+            .get(pr)
+            .map(|tracked| {
+                tracked
+                    .values()
+                    .flat_map(|comment| comment.commands.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Starts watching the PR without any comments, so the commands are tracked on the next check
+    pub fn watch(&self, pr: &str) {
+        self.prs.lock().unwrap().entry(pr.to_string()).or_default();
+    }
+
+    pub fn tracked_prs(&self) -> Vec<String> {
+        self.prs.lock().unwrap().keys().cloned().collect()
+    }
+
+    pub fn forget(&self, pr: &str) {
+        self.prs.lock().unwrap().remove(pr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use octocrab::models::AuthorAssociation;
+    use shared::github::User;
+
+    use super::{content_hash, CommentChange, CommentTracker};
+    use crate::events::commands::{tests::generate_comment, Command, UnknownCommand};
+
+    const PR: &str = "a/b/1";
+
+    fn track(tracker: &CommentTracker, text: &str) -> CommentChange {
+        let comment = generate_comment(text);
+        let command = UnknownCommand::construct(&comment, &Default::default());
+        let sender = User::new("username".to_string(), AuthorAssociation::Contributor);
+        tracker.track(
+            PR,
+            comment.id,
+            content_hash(&comment.body),
+            vec![command],
+            sender,
+        )
+    }
+
+    #[test]
+    pub fn tracks_edited_comments() {
+        let tracker = CommentTracker::default();
+
+        assert!(matches!(track(&tracker, "score 3"), CommentChange::New));
+        assert!(matches!(
+            track(&tracker, "score 3"),
+            CommentChange::Unchanged
+        ));
+        assert!(matches!(track(&tracker, "score 8"), CommentChange::Edited));
+        assert!(matches!(
+            track(&tracker, "score 8"),
+            CommentChange::Unchanged
+        ));
+    }
+
+    #[test]
+    pub fn tracks_deleted_comments() {
+        let tracker = CommentTracker::default();
+        track(&tracker, "score 3");
+
+        let comments = vec![generate_comment("score 3").id];
+        assert!(tracker.take_deleted(PR, &comments).is_empty());
+
+        let deleted = tracker.take_deleted(PR, &[]);
+        assert_eq!(deleted.len(), 1);
+        assert!(matches!(deleted[0].commands[..], [Command::Unknown(_)]));
+        assert!(tracker.take_deleted(PR, &[]).is_empty());
+    }
+
+    #[test]
+    pub fn watched_prs_track_comments_as_new() {
+        let tracker = CommentTracker::default();
+        tracker.watch(PR);
+        assert_eq!(tracker.tracked_prs(), vec![PR.to_string()]);
+        assert!(tracker.take_deleted(PR, &[]).is_empty());
+
+        assert!(matches!(track(&tracker, "score 3"), CommentChange::New));
+        tracker.watch(PR);
+        assert!(matches!(
+            track(&tracker, "score 3"),
+            CommentChange::Unchanged
+        ));
+    }
+}
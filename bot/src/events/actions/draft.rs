@@ -0,0 +1,47 @@
+use tracing::{debug, instrument};
+
+use shared::{github::PrMetadata, PRInfo};
+
+use crate::events::Context;
+
+#[derive(Debug, Clone)]
+pub struct PullRequestReadyForReview {}
+
+impl PullRequestReadyForReview {
+    #[instrument(skip(self, context, check_info), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: PRInfo,
+    ) -> anyhow::Result<bool> {
+        if !check_info.pending {
+            return Ok(false);
+        }
+
+        debug!("PR {} is ready for review", pr.full_id);
+        context.near.send_ready(pr).await?;
+        Ok(true)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PullRequestConvertedToDraft {}
+
+impl PullRequestConvertedToDraft {
+    #[instrument(skip(self, context, check_info), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: PRInfo,
+    ) -> anyhow::Result<bool> {
+        if check_info.pending || check_info.merged {
+            return Ok(false);
+        }
+
+        debug!("PR {} is converted to draft", pr.full_id);
+        context.near.send_draft(pr).await?;
+        Ok(true)
+    }
+}
@@ -1,11 +1,14 @@
 use tracing::{instrument, warn};
 
-use shared::{github::PrMetadata, Event, PRInfo};
+use shared::{github::PrMetadata, ContractError, Event, PRInfo};
 
 use crate::events::Context;
 
 #[derive(Debug, Clone)]
-pub struct PullRequestFinalize {}
+pub struct PullRequestFinalize {
+    // Events of the PR if it was already finalized in a batch
+    pub events: Option<Vec<Event>>,
+}
 
 impl PullRequestFinalize {
     #[instrument(skip(self, pr, context, info), fields(pr = pr.full_id))]
@@ -15,12 +18,25 @@ impl PullRequestFinalize {
         context: Context,
         info: PRInfo,
     ) -> anyhow::Result<bool> {
-        if info.executed {
-            warn!("PR {} is already finalized. Skipping", pr.full_id);
-            return Ok(false);
-        }
-
-        let events = context.near.send_finalize(&pr.full_id).await?;
+        let events = match &self.events {
+            Some(events) => events.clone(),
+            None if info.executed => {
+                warn!("PR {} is already finalized. Skipping", pr.full_id);
+                return Ok(false);
+            }
+            None => match context.near.send_finalize(&pr.full_id).await {
+                Ok(events) => events,
+                Err(e)
+                    if e.downcast_ref::<ContractError>()
+                        == Some(&ContractError::NotReadyToFinalize) =>
+                {
+                    warn!("PR {} is not ready to be finalized. Skipping", pr.full_id);
+                    return Ok(false);
+                }
+                Err(e) => return Err(e),
+            },
+        };
+        context.github.forget_comments(pr);
 
         if !info.allowed_repo {
             return Ok(true);
@@ -45,6 +61,8 @@ impl PullRequestFinalize {
         let mut total_rating = 0;
         let mut total_lifetime_bonus = 0;
         let mut pr_this_week = 0;
+        let mut referral = None;
+        let mut achievements = vec![];
 
         for e in events {
             match e {
@@ -71,11 +89,24 @@ impl PullRequestFinalize {
                     total_lifetime_bonus = applied_multiplier;
                     pr_this_week = pr_number_this_week;
                 }
-                Event::NewSloth { .. } => {}
+                Event::AchievementUnlocked { achievement } => {
+                    achievements.push(achievement);
+                }
+                Event::ReferralRewarded {
+                    referrer,
+                    bonus_rating,
+                } => {
+                    referral = Some((referrer, bonus_rating));
+                }
+                Event::NewSloth { .. }
+                | Event::TeamJoined { .. }
+                | Event::PRArchived { .. }
+                | Event::PRReverted { .. }
+                | Event::BatchOperation { .. } => {}
             }
         }
 
-        let message = context.messages.final_message(
+        let mut message = context.messages.final_message(
             &pr.author.login,
             total_rating,
             score,
@@ -85,6 +116,20 @@ impl PullRequestFinalize {
             total_lifetime_bonus,
             pr_this_week,
         )?;
+        if !achievements.is_empty() {
+            message.push_str(
+                &context
+                    .messages
+                    .achievements_message(&pr.author.login, &achievements)?,
+            );
+        }
+        if let Some((referrer, bonus_rating)) = referral {
+            message.push_str(&context.messages.referral_message(
+                &pr.author.login,
+                &referrer,
+                bonus_rating,
+            )?);
+        }
 
         context
             .github
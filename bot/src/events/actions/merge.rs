@@ -27,7 +27,12 @@ impl PullRequestMerge {
 
         if info.votes.is_empty() {
             context
-                .reply(pr, None, MsgCategory::MergeWithoutScoreMessage, vec![])
+                .reply(
+                    pr,
+                    None,
+                    MsgCategory::MergeWithoutScoreMessage,
+                    vec![("score_timeout".to_string(), info.score_timeout_text())],
+                )
                 .await?;
         }
         Ok(true)
@@ -1,11 +1,17 @@
+mod draft;
 mod finalize;
 mod merge;
+mod reopen;
+mod revert;
 mod stale;
 
 use super::*;
 
+pub use draft::*;
 pub use finalize::*;
 pub use merge::*;
+pub use reopen::*;
+pub use revert::*;
 pub use stale::*;
 use tracing::error;
 
@@ -14,6 +20,11 @@ pub enum Action {
     Finalize(PullRequestFinalize),
     Merge(PullRequestMerge),
     Stale(PullRequestStale),
+    StaleWarning(PullRequestStaleWarning),
+    ReadyForReview(PullRequestReadyForReview),
+    ConvertedToDraft(PullRequestConvertedToDraft),
+    Reopen(PullRequestReopen),
+    RevertDetected(PullRequestRevertDetected),
 }
 
 impl std::fmt::Display for Action {
@@ -22,13 +33,18 @@ impl std::fmt::Display for Action {
             Action::Finalize(_) => write!(f, "Finalize"),
             Action::Merge(_) => write!(f, "Merge"),
             Action::Stale(_) => write!(f, "Stale"),
+            Action::StaleWarning(_) => write!(f, "StaleWarning"),
+            Action::ReadyForReview(_) => write!(f, "ReadyForReview"),
+            Action::ConvertedToDraft(_) => write!(f, "ConvertedToDraft"),
+            Action::Reopen(_) => write!(f, "Reopen"),
+            Action::RevertDetected(_) => write!(f, "RevertDetected"),
         }
     }
 }
 
 impl Action {
-    pub fn finalize() -> Self {
-        Self::Finalize(PullRequestFinalize {})
+    pub fn finalize(events: Option<Vec<shared::Event>>) -> Self {
+        Self::Finalize(PullRequestFinalize { events })
     }
 
     pub fn merge() -> Self {
@@ -39,6 +55,26 @@ impl Action {
         Self::Stale(PullRequestStale {})
     }
 
+    pub fn stale_warning() -> Self {
+        Self::StaleWarning(PullRequestStaleWarning {})
+    }
+
+    pub fn ready_for_review() -> Self {
+        Self::ReadyForReview(PullRequestReadyForReview {})
+    }
+
+    pub fn converted_to_draft() -> Self {
+        Self::ConvertedToDraft(PullRequestConvertedToDraft {})
+    }
+
+    pub fn reopen() -> Self {
+        Self::Reopen(PullRequestReopen {})
+    }
+
+    pub fn revert_detected(pr_number: u64) -> Self {
+        Self::RevertDetected(PullRequestRevertDetected { pr_number })
+    }
+
     pub async fn execute(
         &self,
         pr: &PrMetadata,
@@ -49,7 +85,8 @@ impl Action {
             error!("Shouldn't happening. PR({}) is excluded, so should be removed, but we tracked action for it...", pr.full_id);
             return Ok(false);
         }
-        if !check_info.exist {
+        // Reopened PR was removed on close, and the revert PR refers to another one
+        if !check_info.exist && !matches!(self, Action::Reopen(_) | Action::RevertDetected(_)) {
             // Parsed notification but we weren't called before to include us
             return Ok(false);
         }
@@ -58,6 +95,11 @@ impl Action {
             Action::Finalize(action) => action.execute(pr, context, check_info).await,
             Action::Merge(action) => action.execute(pr, context, check_info).await,
             Action::Stale(action) => action.execute(pr, context, check_info).await,
+            Action::StaleWarning(action) => action.execute(pr, context, check_info).await,
+            Action::ReadyForReview(action) => action.execute(pr, context, check_info).await,
+            Action::ConvertedToDraft(action) => action.execute(pr, context, check_info).await,
+            Action::Reopen(action) => action.execute(pr, context, check_info).await,
+            Action::RevertDetected(action) => action.execute(pr, context, check_info).await,
         }
     }
 }
@@ -0,0 +1,42 @@
+use tracing::{debug, instrument};
+
+use shared::{github::PrMetadata, ContractError, PRInfo};
+
+use crate::{events::Context, messages::MsgCategory};
+
+#[derive(Debug, Clone)]
+pub struct PullRequestReopen {}
+
+impl PullRequestReopen {
+    #[instrument(skip(self, context, check_info), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: PRInfo,
+    ) -> anyhow::Result<bool> {
+        if !check_info.closed || pr.closed {
+            return Ok(false);
+        }
+
+        debug!("Restoring the reopened PR {}", pr.full_id);
+        if let Err(e) = context.near.send_reopen(pr).await {
+            match e.downcast_ref::<ContractError>() {
+                Some(
+                    ContractError::UserOptedOut
+                    | ContractError::RepoPaused
+                    | ContractError::OrganizationNotAllowed,
+                ) => {
+                    debug!("PR {} can't be restored. Skipping", pr.full_id);
+                    return Ok(false);
+                }
+                _ => return Err(e),
+            }
+        }
+
+        context
+            .reply(pr, None, MsgCategory::ReopenMessage, vec![])
+            .await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,48 @@
+use tracing::{debug, instrument};
+
+use shared::{github::PrMetadata, PRInfo};
+
+use crate::{events::Context, messages::MsgCategory};
+
+// Asks maintainers to confirm the rating reversal of the reverted PR
+#[derive(Debug, Clone)]
+pub struct PullRequestRevertDetected {
+    pub pr_number: u64,
+}
+
+impl PullRequestRevertDetected {
+    #[instrument(skip(self, context, check_info), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: PRInfo,
+    ) -> anyhow::Result<bool> {
+        if !check_info.allowed_repo {
+            return Ok(false);
+        }
+
+        let reverted = context
+            .near
+            .check_info(&pr.owner, &pr.repo, self.pr_number)
+            .await?;
+        if !reverted.merged && !reverted.executed {
+            debug!(
+                "PR #{} reverted in {} wasn't merged in the race. Skipping",
+                self.pr_number, pr.full_id
+            );
+            return Ok(false);
+        }
+
+        context
+            .reply(
+                pr,
+                None,
+                MsgCategory::RevertDetectedMessage,
+                vec![("pr_number".to_string(), self.pr_number.to_string())],
+            )
+            .await?;
+        // The revert PR itself isn't in the race, so there is no status to update
+        Ok(false)
+    }
+}
@@ -1,9 +1,68 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
 use tracing::{instrument, warn};
 
 use shared::{github::PrMetadata, PRInfo};
 
 use crate::{events::Context, messages::MsgCategory};
 
+pub const STALE_AFTER_DAYS: i64 = 14;
+// The warning is posted this many days before the PR is marked as stale
+pub const STALE_WARNING_DAYS: i64 = 3;
+// Hidden marker to find our last warning among the PR comments
+pub const STALE_WARNING_MARKER: &str = "<!-- sloth:stale-warning -->";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Staleness {
+    Active,
+    Warn,
+    Stale,
+}
+
+// Our own warning comment updates the PR, so the activity within a minute after it is ignored
+pub fn staleness(
+    pr: &PrMetadata,
+    snoozed_until: Option<DateTime<Utc>>,
+    waiting_on_review: bool,
+    last_warning: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Staleness {
+    if pr.merged.is_some() {
+        return Staleness::Active;
+    }
+    if pr.closed {
+        return Staleness::Stale;
+    }
+    // Drafts aren't in the race yet
+    if pr.draft {
+        return Staleness::Active;
+    }
+    if snoozed_until.is_some_and(|until| until > now) {
+        return Staleness::Active;
+    }
+    // Waiting on the maintainers shouldn't be held against the author
+    if waiting_on_review {
+        return Staleness::Active;
+    }
+
+    if let Some(warning) = last_warning {
+        if pr.updated_at <= warning + Duration::minutes(1) {
+            return if now - warning >= Duration::days(STALE_WARNING_DAYS) {
+                Staleness::Stale
+            } else {
+                Staleness::Active
+            };
+        }
+    }
+
+    if now - pr.updated_at >= Duration::days(STALE_AFTER_DAYS - STALE_WARNING_DAYS) {
+        Staleness::Warn
+    } else {
+        Staleness::Active
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PullRequestStale {}
 
@@ -21,6 +80,7 @@ impl PullRequestStale {
         }
 
         context.near.send_stale(pr).await?;
+        context.github.forget_comments(pr);
         if !check_info.allowed_repo {
             return Ok(false);
         }
@@ -35,3 +95,111 @@ impl PullRequestStale {
         Ok(true)
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct PullRequestStaleWarning {}
+
+impl PullRequestStaleWarning {
+    #[instrument(skip(self, context, check_info), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: PRInfo,
+    ) -> anyhow::Result<bool> {
+        if check_info.merged || pr.closed || !check_info.allowed_repo {
+            return Ok(false);
+        }
+
+        let text = context
+            .messages
+            .get_message(MsgCategory::StaleWarningMessage)
+            .format(HashMap::from([(
+                "days".to_string(),
+                STALE_WARNING_DAYS.to_string(),
+            )]))?;
+        context
+            .github
+            .reply(
+                &pr.owner,
+                &pr.repo,
+                pr.number,
+                &format!("{text}\n{STALE_WARNING_MARKER}"),
+            )
+            .await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::events::commands::tests::default_pr_metadata;
+
+    use super::{staleness, Staleness};
+
+    #[test]
+    fn inactive_pr_is_warned_before_stale() {
+        let now = Utc::now();
+        let mut pr = default_pr_metadata();
+
+        pr.updated_at = now - Duration::days(5);
+        assert_eq!(staleness(&pr, None, false, None, now), Staleness::Active);
+
+        pr.updated_at = now - Duration::days(12);
+        assert_eq!(staleness(&pr, None, false, None, now), Staleness::Warn);
+
+        // The warning itself updates the PR
+        let warning = now - Duration::days(1);
+        pr.updated_at = warning;
+        assert_eq!(
+            staleness(&pr, None, false, Some(warning), now),
+            Staleness::Active
+        );
+
+        let warning = now - Duration::days(3);
+        pr.updated_at = warning;
+        assert_eq!(
+            staleness(&pr, None, false, Some(warning), now),
+            Staleness::Stale
+        );
+
+        // Activity after the warning resets it
+        pr.updated_at = now - Duration::days(2);
+        assert_eq!(
+            staleness(&pr, None, false, Some(warning), now),
+            Staleness::Active
+        );
+    }
+
+    #[test]
+    fn snoozed_and_waiting_prs_are_kept() {
+        let now = Utc::now();
+        let mut pr = default_pr_metadata();
+        pr.updated_at = now - Duration::days(30);
+
+        let snoozed_until = Some(now + Duration::days(1));
+        assert_eq!(
+            staleness(&pr, snoozed_until, false, None, now),
+            Staleness::Active
+        );
+
+        let snoozed_until = Some(now - Duration::days(1));
+        assert_eq!(
+            staleness(&pr, snoozed_until, false, None, now),
+            Staleness::Warn
+        );
+
+        assert_eq!(staleness(&pr, None, true, None, now), Staleness::Active);
+
+        pr.draft = true;
+        assert_eq!(staleness(&pr, None, false, None, now), Staleness::Active);
+
+        pr.closed = true;
+        assert_eq!(
+            staleness(&pr, snoozed_until, false, None, now),
+            Staleness::Stale
+        );
+    }
+}
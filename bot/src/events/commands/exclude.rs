@@ -9,7 +9,7 @@ use super::*;
 #[derive(Debug, Clone)]
 pub struct BotExcluded {
     pub author: User,
-    pub comment_id: u64,
+    pub comment_id: CommentRef,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
@@ -40,6 +40,7 @@ impl BotExcluded {
         debug!("Excluding PR {}", pr.full_id);
 
         context.near.send_exclude(pr).await?;
+        context.github.forget_comments(pr);
         context
             .reply(
                 pr,
@@ -51,16 +52,13 @@ impl BotExcluded {
         Ok(true)
     }
 
-    pub fn construct(comment: &Comment) -> Command {
-        let author = User::new(
-            comment.user.login.clone(),
-            comment.author_association.clone(),
-        );
+    pub fn construct(comment: &CommandComment) -> Command {
+        let author = comment.user.clone();
         let timestamp = comment.created_at;
 
         Command::Excluded(BotExcluded {
             author,
-            comment_id: comment.id.0,
+            comment_id: comment.id,
             timestamp,
         })
     }
@@ -0,0 +1,85 @@
+use tracing::{debug, instrument, warn};
+
+use crate::messages::MsgCategory;
+
+use shared::github::User;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotLinkAccount {
+    pub account_id: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: CommentRef,
+}
+
+impl BotLinkAccount {
+    #[instrument(skip(self, pr, context, _check_info, sender), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        _check_info: PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        let account_id = match &self.account_id {
+            Some(account_id) => account_id,
+            None => {
+                context
+                    .reply_with_error(
+                        pr,
+                        Some(self.comment_id),
+                        MsgCategory::ErrorUnknownCommandMessage,
+                        vec![],
+                    )
+                    .await?;
+                return Ok(false);
+            }
+        };
+
+        debug!("Linking account {} to {}", account_id, sender.login);
+        // The account should request the link on-chain first, so we explain it in case of failure
+        if let Err(e) = context
+            .near
+            .send_link_account(&sender.login, account_id)
+            .await
+        {
+            warn!("Failed to link account for {}: {e}", sender.login);
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorLinkAccountMessage,
+                    vec![
+                        ("user".to_string(), sender.login.clone()),
+                        ("account_id".to_string(), account_id.clone()),
+                    ],
+                )
+                .await?;
+            return Ok(false);
+        }
+
+        context
+            .reply(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::LinkAccountMessage,
+                vec![
+                    ("user".to_string(), sender.login.clone()),
+                    ("account_id".to_string(), account_id.clone()),
+                ],
+            )
+            .await?;
+        Ok(false)
+    }
+
+    pub fn construct(comment: &CommandComment, command: &ParsedCommand) -> Command {
+        let account_id = command.arg(0).map(|account_id| account_id.to_lowercase());
+
+        Command::LinkAccount(BotLinkAccount {
+            account_id,
+            timestamp: comment.created_at,
+            comment_id: comment.id,
+        })
+    }
+}
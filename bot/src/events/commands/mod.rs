@@ -5,12 +5,24 @@ use crate::messages::MsgCategory;
 use super::*;
 
 pub mod exclude;
+pub mod link;
+pub mod opt_out;
+pub mod parser;
 pub mod pause;
+pub mod referral;
+pub mod revert;
 pub mod score;
+pub mod snooze;
 pub mod start;
+pub mod team;
 pub mod unknown;
 
-pub use self::{exclude::*, pause::*, score::*, start::*, unknown::*};
+pub use self::{
+    exclude::*, link::*, opt_out::*, parser::ParsedCommand, pause::*, referral::*, revert::*,
+    score::*, snooze::*, start::*, team::*, unknown::*,
+};
+
+pub const LABEL_PREFIX: &str = "sloth:";
 
 #[derive(Debug, Clone)]
 pub enum Command {
@@ -19,6 +31,12 @@ pub enum Command {
     Pause(BotPaused),
     Unpause(BotUnpaused),
     Excluded(BotExcluded),
+    Team(BotTeam),
+    ReferredBy(BotReferredBy),
+    LinkAccount(BotLinkAccount),
+    OptOut(BotOptOut),
+    Snooze(BotSnooze),
+    Revert(BotRevert),
     Unknown(UnknownCommand),
 }
 
@@ -26,25 +44,62 @@ impl Command {
     pub fn parse_command(
         bot_name: &str,
         pr_metadata: &PrMetadata,
-        comment: &Comment,
-    ) -> Option<Command> {
-        let (command, args) = common::extract_command_with_args(bot_name, comment)?;
-
-        Some(match command.as_str() {
-            "score" | "rate" | "value" => BotScored::construct(comment, args),
-            "pause" | "block" => BotPaused::construct(comment),
-            "unpause" | "unblock" => BotUnpaused::construct(comment),
-            "exclude" | "leave" => BotExcluded::construct(comment),
-            "include" | "in" | "start" | "join" => BotIncluded::construct(comment),
-
-            _ => {
-                info!(
-                    "Unknown command: {} for PR: {}",
-                    command, pr_metadata.full_id
-                );
-                UnknownCommand::construct(comment, command, args)
+        comment: &CommandComment,
+    ) -> Vec<Command> {
+        parser::parse_commands(bot_name, &comment.body)
+            .into_iter()
+            .map(|command| match command.name.as_str() {
+                "score" | "rate" | "value" => BotScored::construct(comment, &command),
+                "pause" | "block" => BotPaused::construct(comment),
+                "unpause" | "unblock" => BotUnpaused::construct(comment),
+                "exclude" | "leave" => BotExcluded::construct(comment),
+                "include" | "in" | "start" | "join" => BotIncluded::construct(comment),
+                "team" => BotTeam::construct(comment, &command),
+                "referred-by" | "referral" => BotReferredBy::construct(comment, &command),
+                "link" => BotLinkAccount::construct(comment, &command),
+                "optout" | "opt-out" => BotOptOut::construct(comment),
+                "snooze" => BotSnooze::construct(comment, &command),
+                "revert" => BotRevert::construct(comment),
+
+                _ => {
+                    info!(
+                        "Unknown command: {} for PR: {}",
+                        command.name, pr_metadata.full_id
+                    );
+                    UnknownCommand::construct(comment, &command)
+                }
+            })
+            .collect()
+    }
+
+    // Labels applied by maintainers, e.g. `sloth:include`, `sloth:exclude` or `sloth:score-5`
+    pub fn parse_label(label: &str, comment: &CommandComment) -> Option<Command> {
+        match label.strip_prefix(LABEL_PREFIX)? {
+            "include" => Some(BotIncluded::construct(comment)),
+            "exclude" => Some(BotExcluded::construct(comment)),
+            command => command.strip_prefix("score-").map(|score| {
+                let command = ParsedCommand::new("score", vec![score.to_string()]);
+                BotScored::construct(comment, &command)
+            }),
+        }
+    }
+
+    pub fn is_command_label(label: &str) -> bool {
+        match label.strip_prefix(LABEL_PREFIX) {
+            Some(command) => {
+                command == "include" || command == "exclude" || command.starts_with("score-")
             }
-        })
+            None => false,
+        }
+    }
+
+    // Labels the maintainers can apply, other scores are rounded on execution anyway
+    pub fn command_labels() -> Vec<String> {
+        ["include".to_string(), "exclude".to_string()]
+            .into_iter()
+            .chain([0, 1, 2, 3, 5, 8, 13].map(|score| format!("score-{score}")))
+            .map(|command| format!("{LABEL_PREFIX}{command}"))
+            .collect()
     }
 
     pub fn parse_body(bot_name: &str, pr_metadata: &PrMetadata) -> Option<Command> {
@@ -58,6 +113,12 @@ impl Command {
             Command::Pause(event) => &event.timestamp,
             Command::Unpause(event) => &event.timestamp,
             Command::Excluded(event) => &event.timestamp,
+            Command::Team(event) => &event.timestamp,
+            Command::ReferredBy(event) => &event.timestamp,
+            Command::LinkAccount(event) => &event.timestamp,
+            Command::OptOut(event) => &event.timestamp,
+            Command::Snooze(event) => &event.timestamp,
+            Command::Revert(event) => &event.timestamp,
             Command::Unknown(event) => &event.timestamp,
         }
     }
@@ -76,6 +137,7 @@ impl Command {
                 "Sloth called for a PR from not allowed org: {}. Skipping",
                 pr.full_id
             );
+            context.github.forget_comments(pr);
             context
                 .reply_with_error(
                     pr,
@@ -88,7 +150,13 @@ impl Command {
             return Ok(false);
         }
 
-        if check_info.executed {
+        // Team membership, account links and opt-outs aren't bound to the PR state
+        if check_info.executed
+            && !matches!(
+                self,
+                Command::Team(_) | Command::LinkAccount(_) | Command::OptOut(_)
+            )
+        {
             info!(
                 "Sloth called for a PR that is already executed: {}. Skipping",
                 pr.full_id
@@ -99,7 +167,7 @@ impl Command {
                         pr,
                         Some(event.comment_id),
                         MsgCategory::ErrorLateScoringMessage,
-                        vec![],
+                        vec![("score_timeout".to_string(), check_info.score_timeout_text())],
                     )
                     .await?;
             }
@@ -127,7 +195,15 @@ impl Command {
             return Ok(false);
         }
 
-        if check_info.excluded && !matches!(self, Command::Include(_)) {
+        if check_info.excluded
+            && !matches!(
+                self,
+                Command::Include(_)
+                    | Command::Team(_)
+                    | Command::LinkAccount(_)
+                    | Command::OptOut(_)
+            )
+        {
             info!(
                 "Sloth called for a PR from excluded PR: {}. Skipping",
                 pr.full_id
@@ -142,9 +218,44 @@ impl Command {
             Command::Pause(event) => event.execute(pr, context, check_info, sender).await,
             Command::Unpause(event) => event.execute(pr, context, check_info, sender).await,
             Command::Excluded(event) => event.execute(pr, context, check_info).await,
+            Command::Team(event) => event.execute(pr, context, check_info, sender).await,
+            Command::ReferredBy(event) => event.execute(pr, context, check_info, sender).await,
+            Command::LinkAccount(event) => event.execute(pr, context, check_info, sender).await,
+            Command::OptOut(event) => event.execute(pr, context, check_info, sender).await,
+            Command::Snooze(event) => event.execute(pr, context, check_info, sender).await,
+            Command::Revert(event) => event.execute(pr, context, check_info, sender).await,
             Command::Unknown(event) => event.execute(pr, context, check_info, sender).await,
         }
     }
+
+    // Reverts the effect of the deleted command if possible
+    #[instrument(skip(self, context, check_info, pr), fields(pr = pr.full_id))]
+    pub async fn revoke(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        if !check_info.exist || check_info.executed {
+            info!(
+                "Command was deleted in a PR that is not tracked or already executed: {}. Skipping",
+                pr.full_id
+            );
+            return Ok(false);
+        }
+
+        match self {
+            Command::Score(event) => event.revoke(pr, context, sender).await,
+            command => {
+                info!(
+                    "Deleted command `{command}` can't be revoked in PR: {}. Skipping",
+                    pr.full_id
+                );
+                Ok(false)
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for Command {
@@ -155,6 +266,12 @@ impl std::fmt::Display for Command {
             Command::Pause(_) => write!(f, "Pause"),
             Command::Unpause(_) => write!(f, "Unpause"),
             Command::Excluded(_) => write!(f, "Excluded"),
+            Command::Team(_) => write!(f, "Team"),
+            Command::ReferredBy(_) => write!(f, "ReferredBy"),
+            Command::LinkAccount(_) => write!(f, "LinkAccount"),
+            Command::OptOut(_) => write!(f, "OptOut"),
+            Command::Snooze(_) => write!(f, "Snooze"),
+            Command::Revert(_) => write!(f, "Revert"),
             Command::Unknown(_) => write!(f, "Unknown"),
         }
     }
@@ -162,12 +279,15 @@ impl std::fmt::Display for Command {
 
 #[cfg(test)]
 pub mod tests {
-    use octocrab::models::issues::Comment;
-    use shared::github::{PrMetadata, User};
+    use octocrab::models::{issues::Comment, pulls::ReviewState};
+    use shared::github::{CommandComment, CommentRef, CommentSource, PrMetadata, User};
 
-    use super::Command;
+    use super::{
+        BotLinkAccount, BotOptOut, BotReferredBy, BotScored, BotSnooze, BotTeam, Command,
+        TeamAction,
+    };
 
-    fn generate_comment(text: &str) -> Comment {
+    pub fn generate_comment(text: &str) -> CommandComment {
         let string = format!(
             r#"
             {{
@@ -204,19 +324,20 @@ pub mod tests {
           "#
         );
 
-        serde_json::from_str(&string).unwrap()
+        serde_json::from_str::<Comment>(&string).unwrap().into()
     }
 
-    fn generate_command_comment(command: &str) -> Comment {
+    fn generate_command_comment(command: &str) -> CommandComment {
         generate_comment(&format!("@{NAME} {command}"))
     }
 
     const NAME: &str = "@name";
 
-    fn default_pr_metadata() -> PrMetadata {
+    pub fn default_pr_metadata() -> PrMetadata {
         PrMetadata {
             owner: "a".to_string(),
             repo: "b".to_string(),
+            repo_id: None,
             number: 1,
             author: User::new(
                 "a-u".to_string(),
@@ -226,8 +347,13 @@ pub mod tests {
             merged: None,
             updated_at: chrono::Utc::now(),
             full_id: "a/b/1".to_string(),
+            title: "abc".to_string(),
             body: "abc".to_string(),
             closed: false,
+            reviews: Default::default(),
+            labels: Default::default(),
+            review_requested: false,
+            draft: false,
         }
     }
 
@@ -237,7 +363,7 @@ pub mod tests {
         for alias in aliases {
             let include_comment = generate_command_comment(alias);
             let command =
-                Command::parse_command(NAME, &default_pr_metadata(), &include_comment).unwrap();
+                Command::parse_command(NAME, &default_pr_metadata(), &include_comment).remove(0);
 
             assert!(matches!(command, Command::Include(_)))
         }
@@ -249,7 +375,7 @@ pub mod tests {
         for alias in aliases {
             let score_comment = generate_command_comment(alias);
             let command =
-                Command::parse_command(NAME, &default_pr_metadata(), &score_comment).unwrap();
+                Command::parse_command(NAME, &default_pr_metadata(), &score_comment).remove(0);
 
             assert!(matches!(command, Command::Score(_)))
         }
@@ -261,7 +387,7 @@ pub mod tests {
         for alias in aliases {
             let pause_comment = generate_command_comment(alias);
             let command =
-                Command::parse_command(NAME, &default_pr_metadata(), &pause_comment).unwrap();
+                Command::parse_command(NAME, &default_pr_metadata(), &pause_comment).remove(0);
 
             assert!(matches!(command, Command::Pause(_)))
         }
@@ -273,7 +399,7 @@ pub mod tests {
         for alias in aliases {
             let unpause_comment = generate_command_comment(alias);
             let command =
-                Command::parse_command(NAME, &default_pr_metadata(), &unpause_comment).unwrap();
+                Command::parse_command(NAME, &default_pr_metadata(), &unpause_comment).remove(0);
 
             assert!(matches!(command, Command::Unpause(_)))
         }
@@ -285,26 +411,194 @@ pub mod tests {
         for alias in aliases {
             let exclude_comment = generate_command_comment(alias);
             let command =
-                Command::parse_command(NAME, &default_pr_metadata(), &exclude_comment).unwrap();
+                Command::parse_command(NAME, &default_pr_metadata(), &exclude_comment).remove(0);
 
             assert!(matches!(command, Command::Excluded(_)))
         }
     }
 
+    #[test]
+    pub fn correct_team() {
+        let team_comment = generate_command_comment("team join sloths");
+        let command = Command::parse_command(NAME, &default_pr_metadata(), &team_comment).remove(0);
+        assert!(matches!(
+            command,
+            Command::Team(BotTeam {
+                action: Some(TeamAction::Join(ref team)),
+                ..
+            }) if team == "sloths"
+        ));
+
+        let team_comment = generate_command_comment("team approve @username");
+        let command = Command::parse_command(NAME, &default_pr_metadata(), &team_comment).remove(0);
+        assert!(matches!(
+            command,
+            Command::Team(BotTeam {
+                action: Some(TeamAction::Approve(ref user)),
+                ..
+            }) if user == "username"
+        ));
+
+        let team_comment = generate_command_comment("team");
+        let command = Command::parse_command(NAME, &default_pr_metadata(), &team_comment).remove(0);
+        assert!(matches!(
+            command,
+            Command::Team(BotTeam { action: None, .. })
+        ));
+    }
+
+    #[test]
+    pub fn correct_referred_by() {
+        let aliases = vec!["referred-by @username", "referral username"];
+        for alias in aliases {
+            let referral_comment = generate_command_comment(alias);
+            let command =
+                Command::parse_command(NAME, &default_pr_metadata(), &referral_comment).remove(0);
+
+            assert!(matches!(
+                command,
+                Command::ReferredBy(BotReferredBy {
+                    referrer: Some(ref referrer),
+                    ..
+                }) if referrer == "username"
+            ))
+        }
+    }
+
+    #[test]
+    pub fn correct_link_account() {
+        let link_comment = generate_command_comment("link Sloth.near");
+        let command = Command::parse_command(NAME, &default_pr_metadata(), &link_comment).remove(0);
+
+        assert!(matches!(
+            command,
+            Command::LinkAccount(BotLinkAccount {
+                account_id: Some(ref account_id),
+                ..
+            }) if account_id == "sloth.near"
+        ));
+    }
+
+    #[test]
+    pub fn score_from_review() {
+        let mut review = generate_command_comment("score 5");
+        review.id = CommentRef {
+            source: CommentSource::Review,
+            id: 444,
+        };
+        review.review_state = Some(ReviewState::Approved);
+
+        let command = Command::parse_command(NAME, &default_pr_metadata(), &review).remove(0);
+        assert!(matches!(
+            command,
+            Command::Score(BotScored { comment_id, .. }) if comment_id == review.id
+        ));
+    }
+
+    #[test]
+    pub fn multiple_commands() {
+        let comment = generate_comment(&format!(
+            "@{NAME} include\\n@{NAME} score 8 --reason 'Great tests'"
+        ));
+        let commands = Command::parse_command(NAME, &default_pr_metadata(), &comment);
+
+        assert_eq!(commands.len(), 2);
+        assert!(matches!(commands[0], Command::Include(_)));
+        assert!(matches!(
+            commands[1],
+            Command::Score(ref event) if event.score() == (8, false)
+                && event.reason.as_deref() == Some("Great tests")
+        ));
+    }
+
+    #[test]
+    pub fn correct_labels() {
+        let comment = generate_comment("");
+        let parse = |label| Command::parse_label(label, &comment);
+
+        assert!(matches!(parse("sloth:include"), Some(Command::Include(_))));
+        assert!(matches!(parse("sloth:exclude"), Some(Command::Excluded(_))));
+        assert!(matches!(
+            parse("sloth:score-5"),
+            Some(Command::Score(ref event)) if event.score() == (5, false)
+        ));
+        assert!(parse("sloth:waiting-for-scoring").is_none());
+        assert!(parse("bug").is_none());
+
+        assert!(Command::is_command_label("sloth:score-13"));
+        assert!(!Command::is_command_label("sloth:executed"));
+    }
+
+    #[test]
+    pub fn correct_opt_out() {
+        let aliases = vec!["optout", "opt-out"];
+        for alias in aliases {
+            let opt_out_comment = generate_command_comment(alias);
+            let command =
+                Command::parse_command(NAME, &default_pr_metadata(), &opt_out_comment).remove(0);
+
+            assert!(matches!(command, Command::OptOut(BotOptOut { .. })));
+        }
+    }
+
+    #[test]
+    pub fn correct_snooze() {
+        let snooze_comment = generate_command_comment("snooze 10");
+        let command =
+            Command::parse_command(NAME, &default_pr_metadata(), &snooze_comment).remove(0);
+        assert!(matches!(
+            command,
+            Command::Snooze(BotSnooze { days: Some(10), .. })
+        ));
+
+        let snooze_comment = generate_command_comment("snooze forever");
+        let command =
+            Command::parse_command(NAME, &default_pr_metadata(), &snooze_comment).remove(0);
+        assert!(matches!(
+            command,
+            Command::Snooze(BotSnooze { days: None, .. })
+        ));
+    }
+
+    #[test]
+    pub fn revert_pr_detection() {
+        let mut pr = default_pr_metadata();
+        assert_eq!(pr.reverted_pr(), None);
+
+        pr.body = "Reverts a/b#42\n\nBroke the build".to_string();
+        assert_eq!(pr.reverted_pr(), Some(42));
+
+        pr.body = "Reverts #43".to_string();
+        assert_eq!(pr.reverted_pr(), Some(43));
+
+        pr.body = "This reverts commit abc.".to_string();
+        pr.title = "Revert \"Add the feature (#44)\"".to_string();
+        assert_eq!(pr.reverted_pr(), Some(44));
+
+        // Another repository isn't ours to revert
+        pr.title = "abc".to_string();
+        pr.body = "Reverts c/d#45".to_string();
+        assert_eq!(pr.reverted_pr(), None);
+
+        let comment = generate_command_comment("revert");
+        let command = Command::parse_command(NAME, &pr, &comment).remove(0);
+        assert!(matches!(command, Command::Revert(_)));
+    }
+
     #[test]
     pub fn correct_unknown() {
         let aliases = vec!["", "asdasdasdas", "hello workld"];
         for alias in aliases {
             let unknown_command = generate_command_comment(alias);
             let command =
-                Command::parse_command(NAME, &default_pr_metadata(), &unknown_command).unwrap();
+                Command::parse_command(NAME, &default_pr_metadata(), &unknown_command).remove(0);
 
             assert!(matches!(command, Command::Unknown(_)))
         }
 
         let unknown_command = generate_comment(&format!("@{NAME}"));
         let command =
-            Command::parse_command(NAME, &default_pr_metadata(), &unknown_command).unwrap();
+            Command::parse_command(NAME, &default_pr_metadata(), &unknown_command).remove(0);
 
         assert!(matches!(command, Command::Unknown(_)))
     }
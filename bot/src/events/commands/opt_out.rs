@@ -0,0 +1,43 @@
+use tracing::{debug, instrument};
+
+use crate::messages::MsgCategory;
+
+use shared::github::User;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotOptOut {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: CommentRef,
+}
+
+impl BotOptOut {
+    #[instrument(skip(self, pr, context, _check_info, sender), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        _check_info: PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        debug!("User {} opted out of the race", sender.login);
+        context.near.send_opt_out(&sender.login).await?;
+        context
+            .reply(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::OptOutMessage,
+                vec![("user".to_string(), sender.login.clone())],
+            )
+            .await?;
+        Ok(false)
+    }
+
+    pub fn construct(comment: &CommandComment) -> Command {
+        Command::OptOut(BotOptOut {
+            timestamp: comment.created_at,
+            comment_id: comment.id,
+        })
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use tracing::trace;
+
+// A single `@bot <name> [args...] [--flag [value]...]` invocation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedCommand {
+    pub name: String,
+    pub args: Vec<String>,
+    pub flags: HashMap<String, Option<String>>,
+}
+
+impl ParsedCommand {
+    pub fn new(name: &str, args: Vec<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            args,
+            flags: Default::default(),
+        }
+    }
+
+    pub fn arg(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(String::as_str)
+    }
+
+    pub fn flag(&self, name: &str) -> Option<&str> {
+        self.flags.get(name)?.as_deref()
+    }
+}
+
+// Finds all commands addressed to the bot. Every mention starts a new command that lasts until
+// the end of the line or the next mention. Code blocks, inline code and quotes are skipped,
+// so the examples and replies don't trigger the commands.
+pub fn parse_commands(bot_name: &str, body: &str) -> Vec<ParsedCommand> {
+    let mention = format!("@{}", bot_name.to_ascii_lowercase());
+    let mut commands = vec![];
+    let mut in_code_block = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || trimmed.starts_with('>') {
+            continue;
+        }
+
+        let line = strip_inline_code(line);
+        let mentions = find_mentions(&line, &mention);
+        for (i, start) in mentions.iter().enumerate() {
+            let end = mentions.get(i + 1).copied().unwrap_or(line.len());
+            let command = parse_command(&line[start + mention.len()..end]);
+            trace!("Parsed command: {command:?}");
+            commands.push(command);
+        }
+    }
+
+    commands
+}
+
+fn strip_inline_code(line: &str) -> String {
+    // Unpaired backtick is just a character in markdown
+    if line.matches('`').count() < 2 {
+        return line.to_string();
+    }
+
+    let mut result = String::with_capacity(line.len());
+    let mut parts = line.split('`').peekable();
+    let mut in_code = false;
+    while let Some(part) = parts.next() {
+        // The last part after the unpaired backtick is a plain text
+        if !in_code || parts.peek().is_none() {
+            result.push_str(part);
+        }
+        in_code = !in_code;
+    }
+    result
+}
+
+fn find_mentions(line: &str, mention: &str) -> Vec<usize> {
+    let lowercase = line.to_ascii_lowercase();
+    lowercase
+        .match_indices(mention)
+        .map(|(position, _)| position)
+        // `@bot` shouldn't match `@bot-staging`
+        .filter(|position| {
+            !lowercase[position + mention.len()..]
+                .starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+        .collect()
+}
+
+fn parse_command(input: &str) -> ParsedCommand {
+    let mut tokens = tokenize(input).into_iter().peekable();
+    let mut command = ParsedCommand {
+        name: tokens.next().unwrap_or_default().to_lowercase(),
+        ..Default::default()
+    };
+
+    while let Some(token) = tokens.next() {
+        match token.strip_prefix("--").filter(|flag| !flag.is_empty()) {
+            Some(flag) => {
+                let (flag, value) = match flag.split_once('=') {
+                    Some((flag, value)) => (flag, Some(value.to_string())),
+                    None => (flag, tokens.next_if(|next| !next.starts_with("--"))),
+                };
+                command.flags.insert(flag.to_lowercase(), value);
+            }
+            // Handles and names are case-insensitive
+            None => command.args.push(token.to_lowercase()),
+        }
+    }
+
+    command
+}
+
+// Splits by whitespaces, keeping the quoted text as a single token
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quote = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if !in_token && (c == '"' || c == '\'') => {
+                quote = Some(c);
+                in_token = true;
+            }
+            None if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if in_token {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_commands, tokenize, ParsedCommand};
+
+    const BOT: &str = "sloth";
+
+    fn command(name: &str, args: &[&str]) -> ParsedCommand {
+        ParsedCommand::new(name, args.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn single_command() {
+        assert_eq!(
+            parse_commands(BOT, "@sloth score 5"),
+            vec![command("score", &["5"])]
+        );
+        assert_eq!(
+            parse_commands(BOT, "Nice work! @Sloth Score 5"),
+            vec![command("score", &["5"])]
+        );
+        assert_eq!(parse_commands(BOT, "@sloth"), vec![command("", &[])]);
+        assert!(parse_commands(BOT, "no mentions here").is_empty());
+    }
+
+    #[test]
+    fn multiple_commands() {
+        let body = "@sloth include\nThanks!\n@sloth score 8\n@sloth referral @user @sloth team";
+        assert_eq!(
+            parse_commands(BOT, body),
+            vec![
+                command("include", &[]),
+                command("score", &["8"]),
+                command("referral", &["@user"]),
+                command("team", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_args() {
+        assert_eq!(
+            tokenize(r#"join "big sloths" 'small one' don't"#),
+            vec!["join", "big sloths", "small one", "don't"]
+        );
+        assert_eq!(
+            tokenize(r#"join "unterminated quote"#),
+            vec!["join", "unterminated quote"]
+        );
+        assert_eq!(
+            parse_commands(BOT, r#"@sloth team join "Big Sloths""#),
+            vec![command("team", &["join", "big sloths"])]
+        );
+    }
+
+    #[test]
+    fn flags() {
+        let commands = parse_commands(BOT, r#"@sloth score 5 --reason "Great tests" --silent"#);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].args, vec!["5"]);
+        assert_eq!(commands[0].flag("reason"), Some("Great tests"));
+        assert!(commands[0].flags.contains_key("silent"));
+        assert_eq!(commands[0].flag("silent"), None);
+
+        let commands = parse_commands(BOT, "@sloth score --reason=tests 3");
+        assert_eq!(commands[0].args, vec!["3"]);
+        assert_eq!(commands[0].flag("reason"), Some("tests"));
+    }
+
+    #[test]
+    fn code_blocks_are_ignored() {
+        let body =
+            "Use `@sloth score 5` to score\n```\n@sloth exclude\n```\n~~~\n@sloth pause\n~~~";
+        assert!(parse_commands(BOT, body).is_empty());
+
+        assert_eq!(
+            parse_commands(BOT, "`example` @sloth score 3 `another`"),
+            vec![command("score", &["3"])]
+        );
+        assert_eq!(
+            parse_commands(BOT, "unpaired ` @sloth score 2"),
+            vec![command("score", &["2"])]
+        );
+    }
+
+    #[test]
+    fn quotes_are_ignored() {
+        let body = "> @sloth score 13\nI disagree";
+        assert!(parse_commands(BOT, body).is_empty());
+    }
+
+    #[test]
+    fn mention_boundary() {
+        assert!(parse_commands(BOT, "@sloth-staging score 5").is_empty());
+        assert!(parse_commands(BOT, "@sloths score 5").is_empty());
+    }
+}
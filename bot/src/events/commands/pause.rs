@@ -9,7 +9,7 @@ use super::*;
 #[derive(Clone, Debug)]
 pub struct BotPaused {
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub comment_id: u64,
+    pub comment_id: CommentRef,
 }
 
 impl BotPaused {
@@ -61,10 +61,10 @@ impl BotPaused {
         Ok(true)
     }
 
-    pub fn construct(comment: &Comment) -> Command {
+    pub fn construct(comment: &CommandComment) -> Command {
         Command::Pause(BotPaused {
             timestamp: comment.created_at,
-            comment_id: comment.id.0,
+            comment_id: comment.id,
         })
     }
 }
@@ -72,7 +72,7 @@ impl BotPaused {
 #[derive(Clone, Debug)]
 pub struct BotUnpaused {
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub comment_id: u64,
+    pub comment_id: CommentRef,
 }
 
 impl BotUnpaused {
@@ -117,10 +117,10 @@ impl BotUnpaused {
         }
     }
 
-    pub fn construct(comment: &Comment) -> Command {
+    pub fn construct(comment: &CommandComment) -> Command {
         Command::Unpause(BotUnpaused {
             timestamp: comment.created_at,
-            comment_id: comment.id.0,
+            comment_id: comment.id,
         })
     }
 }
@@ -0,0 +1,107 @@
+use tracing::{debug, info, instrument, warn};
+
+use crate::messages::MsgCategory;
+
+use shared::{github::User, ContractError};
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotReferredBy {
+    pub referrer: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: CommentRef,
+}
+
+impl BotReferredBy {
+    #[instrument(skip(self, pr, context, _check_info, sender), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        _check_info: PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        let referrer = match &self.referrer {
+            Some(referrer) => referrer,
+            None => {
+                context
+                    .reply_with_error(
+                        pr,
+                        Some(self.comment_id),
+                        MsgCategory::ErrorUnknownCommandMessage,
+                        vec![],
+                    )
+                    .await?;
+                return Ok(false);
+            }
+        };
+
+        if pr.author.login != sender.login {
+            info!(
+                "Tried to set a referrer for a PR from not author: {}. Skipping",
+                pr.full_id
+            );
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorRightsViolationMessage,
+                    vec![],
+                )
+                .await?;
+            return Ok(false);
+        }
+
+        debug!("Setting referrer {} for {}", referrer, sender.login);
+        // The contract validates the referral rules, so we explain them in case of failure
+        if let Err(e) = context.near.send_referred_by(&sender.login, referrer).await {
+            if !matches!(
+                e.downcast_ref::<ContractError>(),
+                Some(
+                    ContractError::SelfReferral
+                        | ContractError::ReferrerWithoutExecutedPrs
+                        | ContractError::ReferrerAlreadySet
+                        | ContractError::ReferralNotOnFirstPr
+                )
+            ) {
+                return Err(e);
+            }
+            warn!("Failed to set referrer for {}: {e}", pr.full_id);
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorReferralMessage,
+                    vec![("referrer".to_string(), referrer.clone())],
+                )
+                .await?;
+            return Ok(false);
+        }
+
+        context
+            .reply(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::ReferralMessage,
+                vec![
+                    ("pr_author_username".to_string(), sender.login.clone()),
+                    ("referrer".to_string(), referrer.clone()),
+                ],
+            )
+            .await?;
+        Ok(false)
+    }
+
+    pub fn construct(comment: &CommandComment, command: &ParsedCommand) -> Command {
+        let referrer = command
+            .arg(0)
+            .map(|referrer| referrer.trim_start_matches('@').to_string());
+
+        Command::ReferredBy(BotReferredBy {
+            referrer,
+            timestamp: comment.created_at,
+            comment_id: comment.id,
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use tracing::{debug, info, instrument};
+
+use crate::messages::MsgCategory;
+
+use shared::{github::User, ContractError};
+
+use super::*;
+
+// Confirms the rating reversal of the PR reverted by this one
+#[derive(Clone, Debug)]
+pub struct BotRevert {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: CommentRef,
+}
+
+impl BotRevert {
+    #[instrument(skip(self, pr, context, _check_info, sender), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        _check_info: PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        if !sender.is_maintainer() {
+            info!(
+                "Tried to revert a PR from not maintainer: {}. Skipping",
+                pr.full_id
+            );
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorRightsViolationMessage,
+                    vec![],
+                )
+                .await?;
+            return Ok(false);
+        }
+
+        let Some(reverted) = pr.reverted_pr() else {
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorRevertMessage,
+                    vec![],
+                )
+                .await?;
+            return Ok(false);
+        };
+
+        debug!("Reverting the rating of #{reverted} in {}", pr.full_id);
+        if let Err(e) = context
+            .near
+            .send_revert(&pr.owner, &pr.repo, reverted)
+            .await
+        {
+            match e.downcast_ref::<ContractError>() {
+                Some(ContractError::PrNotFound | ContractError::PrNotMerged) => {
+                    context
+                        .reply_with_error(
+                            pr,
+                            Some(self.comment_id),
+                            MsgCategory::ErrorRevertMessage,
+                            vec![],
+                        )
+                        .await?;
+                    return Ok(false);
+                }
+                _ => return Err(e),
+            }
+        }
+
+        context
+            .reply(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::RevertMessage,
+                vec![("pr_number".to_string(), reverted.to_string())],
+            )
+            .await?;
+        // The revert PR itself isn't in the race, so there is no status to update
+        Ok(false)
+    }
+
+    pub fn construct(comment: &CommandComment) -> Command {
+        Command::Revert(BotRevert {
+            timestamp: comment.created_at,
+            comment_id: comment.id,
+        })
+    }
+}
@@ -1,22 +1,33 @@
-use tracing::{debug, instrument};
+use octocrab::models::pulls::ReviewState;
+use tracing::{debug, error, instrument};
 
 use crate::messages::MsgCategory;
 
-use shared::{github::User, PRInfo};
+use shared::{
+    github::{CommentSource, User},
+    ContractError, PRInfo,
+};
 
 use super::*;
 
 #[derive(Debug, Clone)]
 pub struct BotScored {
     score: String,
+    // Optional `--reason` of the reviewer, that is kept in the logs
+    pub reason: Option<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub comment_id: u64,
+    pub comment_id: CommentRef,
 }
 
 impl BotScored {
-    pub fn new(score: String, timestamp: chrono::DateTime<chrono::Utc>, comment_id: u64) -> Self {
+    pub fn new(
+        score: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        comment_id: CommentRef,
+    ) -> Self {
         Self {
             score,
+            reason: None,
             timestamp,
             comment_id,
         }
@@ -45,7 +56,7 @@ impl BotScored {
 }
 
 impl BotScored {
-    #[instrument(skip(self, pr, context, info, sender), fields(pr = pr.full_id, score = self.score))]
+    #[instrument(skip(self, pr, context, info, sender), fields(pr = pr.full_id, score = self.score, reason = self.reason))]
     pub async fn execute(
         &self,
         pr: &PrMetadata,
@@ -68,34 +79,71 @@ impl BotScored {
                 "Author tried to score their own PR {}. Skipping.",
                 pr.full_id,
             );
-            context
-                .reply_with_error(
-                    pr,
-                    Some(self.comment_id),
-                    MsgCategory::ErrorSelfScore,
-                    vec![],
-                )
+            self.reply(pr, &context, MsgCategory::ErrorSelfScore, vec![])
                 .await?;
             return Ok(false);
         }
 
         if !sender.is_maintainer() {
             debug!("Non-maintainer tried to score PR {}. Skipping.", pr.full_id,);
-            context
-                .reply_with_error(
+            self.reply(
+                pr,
+                &context,
+                MsgCategory::ErrorRightsViolationMessage,
+                vec![],
+            )
+            .await?;
+            return Ok(false);
+        }
+
+        if pr.has_requested_changes() {
+            debug!(
+                "Tried to score PR {} with requested changes. Skipping.",
+                pr.full_id,
+            );
+            let reviewers = pr
+                .reviews
+                .iter()
+                .filter(|(_, state)| **state == ReviewState::ChangesRequested)
+                .map(|(reviewer, _)| format!("@{reviewer}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.reply(
+                pr,
+                &context,
+                MsgCategory::ErrorChangesRequestedMessage,
+                vec![("reviewers".to_string(), reviewers)],
+            )
+            .await?;
+            return Ok(false);
+        }
+
+        if let Err(e) = context
+            .near
+            .send_scored(pr, &sender.login, number as u64, self.reason.as_deref())
+            .await
+        {
+            // The PR could be finalized after we checked it
+            if e.downcast_ref::<ContractError>() == Some(&ContractError::PrNotFound) {
+                debug!("PR {} was executed before scoring. Skipping", pr.full_id);
+                self.reply(
                     pr,
-                    Some(self.comment_id),
-                    MsgCategory::ErrorRightsViolationMessage,
-                    vec![],
+                    &context,
+                    MsgCategory::ErrorLateScoringMessage,
+                    vec![("score_timeout".to_string(), info.score_timeout_text())],
                 )
                 .await?;
-            return Ok(false);
+                return Ok(false);
+            }
+            return Err(e);
         }
 
-        context
-            .near
-            .send_scored(pr, &sender.login, number as u64)
-            .await?;
+        if let Some(reason) = &self.reason {
+            // The score is already accepted, the reason just won't be shown on the website
+            if let Err(e) = context.server.send_reason(pr, &sender.login, reason).await {
+                error!("Failed to send the score reason for {}: {e}", pr.full_id);
+            }
+        }
 
         let (category, args) = match (number, edited) {
             (num, true) => (
@@ -116,61 +164,121 @@ impl BotScored {
             ),
         };
 
-        context
-            .reply(pr, Some(self.comment_id), category, args)
-            .await?;
+        self.reply(pr, &context, category, args).await?;
         Ok(true)
     }
 
-    pub fn construct(comment: &Comment, input: String) -> Command {
-        Command::Score(BotScored::new(input, comment.created_at, comment.id.0))
+    #[instrument(skip(self, pr, context, sender), fields(pr = pr.full_id))]
+    pub async fn revoke(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        // The score wasn't accepted in the first place
+        if pr.author.login == sender.login || !sender.is_maintainer() {
+            return Ok(false);
+        }
+
+        debug!("Removing the score of {} from {}", sender.login, pr.full_id);
+        context.near.send_unscored(pr, &sender.login).await?;
+        Ok(true)
+    }
+
+    // Reactions are acknowledged only by the status comment update
+    async fn reply(
+        &self,
+        pr: &PrMetadata,
+        context: &Context,
+        msg: MsgCategory,
+        args: Vec<(String, String)>,
+    ) -> anyhow::Result<()> {
+        if self.comment_id.source == CommentSource::Reaction {
+            return Ok(());
+        }
+
+        context
+            .reply_with_error(pr, Some(self.comment_id), msg, args)
+            .await
+    }
+
+    pub fn from_reaction(
+        score: u8,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        reaction_id: u64,
+    ) -> Command {
+        Command::Score(BotScored::new(
+            score.to_string(),
+            timestamp,
+            CommentRef {
+                source: CommentSource::Reaction,
+                id: reaction_id,
+            },
+        ))
+    }
+
+    pub fn construct(comment: &CommandComment, command: &ParsedCommand) -> Command {
+        let mut event = BotScored::new(
+            command.arg(0).unwrap_or_default().to_string(),
+            comment.created_at,
+            comment.id,
+        );
+        event.reason = command.flag("reason").map(str::to_string);
+        Command::Score(event)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use shared::github::CommentRef;
+
     use super::commands::BotScored;
 
     #[test]
     pub fn score_parsing() {
         assert_eq!(
             (5, false),
-            BotScored::new("5".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new("5".to_string(), chrono::Utc::now(), CommentRef::issue(1)).score()
         );
 
         assert_eq!(
             (5, false),
-            BotScored::new("5 ".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new("5 ".to_string(), chrono::Utc::now(), CommentRef::issue(1)).score()
         );
 
         assert_eq!(
             (5, false),
-            BotScored::new("5 asdasdas".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new(
+                "5 asdasdas".to_string(),
+                chrono::Utc::now(),
+                CommentRef::issue(1)
+            )
+            .score()
         );
 
         assert_eq!(
             (0, true),
-            BotScored::new("as".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new("as".to_string(), chrono::Utc::now(), CommentRef::issue(1)).score()
         );
 
         assert_eq!(
             (0, false),
-            BotScored::new("0".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new("0".to_string(), chrono::Utc::now(), CommentRef::issue(1)).score()
         );
 
         assert_eq!(
             (8, true),
-            BotScored::new("9".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new("9".to_string(), chrono::Utc::now(), CommentRef::issue(1)).score()
         );
 
         assert_eq!(
             (8, true),
-            BotScored::new("7".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new("7".to_string(), chrono::Utc::now(), CommentRef::issue(1)).score()
         );
 
         assert_eq!(
             (0, true),
-            BotScored::new("".to_string(), chrono::Utc::now(), 1).score()
+            BotScored::new("".to_string(), chrono::Utc::now(), CommentRef::issue(1)).score()
         );
     }
 }
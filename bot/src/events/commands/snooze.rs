@@ -0,0 +1,91 @@
+use tracing::{debug, info, instrument};
+
+use crate::messages::MsgCategory;
+
+use shared::{github::User, MAX_SNOOZE_DAYS};
+
+use super::*;
+
+#[derive(Clone, Debug)]
+pub struct BotSnooze {
+    pub days: Option<u64>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: CommentRef,
+}
+
+impl BotSnooze {
+    #[instrument(skip(self, pr, context, check_info, sender), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        if !check_info.exist || check_info.merged {
+            info!(
+                "Tried to snooze a PR that is not tracked or already merged: {}. Skipping",
+                pr.full_id
+            );
+            return Ok(false);
+        }
+
+        if !sender.is_maintainer() && pr.author.login != sender.login {
+            info!(
+                "Tried to snooze a PR from not maintainer or author: {}. Skipping",
+                pr.full_id
+            );
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorRightsViolationMessage,
+                    vec![],
+                )
+                .await?;
+            return Ok(false);
+        }
+
+        let Some(days) = self
+            .days
+            .filter(|days| (1..=MAX_SNOOZE_DAYS).contains(days))
+        else {
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorSnoozeDaysMessage,
+                    vec![("max_days".to_string(), MAX_SNOOZE_DAYS.to_string())],
+                )
+                .await?;
+            return Ok(false);
+        };
+
+        let until = self.timestamp + chrono::Duration::days(days as i64);
+        debug!("Snoozing the PR {} until {until}", pr.full_id);
+        context
+            .near
+            .send_snooze(pr, until.timestamp_nanos_opt().unwrap_or_default() as u64)
+            .await?;
+        context
+            .reply(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::SnoozeMessage,
+                vec![
+                    ("days".to_string(), days.to_string()),
+                    ("date".to_string(), until.format("%Y-%m-%d").to_string()),
+                ],
+            )
+            .await?;
+        Ok(true)
+    }
+
+    pub fn construct(comment: &CommandComment, command: &ParsedCommand) -> Command {
+        Command::Snooze(BotSnooze {
+            days: command.arg(0).and_then(|days| days.parse().ok()),
+            timestamp: comment.created_at,
+            comment_id: comment.id,
+        })
+    }
+}
@@ -2,18 +2,18 @@ use tracing::{debug, instrument};
 
 use crate::messages::MsgCategory;
 
-use shared::github::User;
+use shared::{github::User, ContractError};
 
 use super::*;
 
 #[derive(Debug, Clone)]
 pub struct BotIncluded {
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub comment_id: Option<u64>,
+    pub comment_id: Option<CommentRef>,
 }
 
 impl BotIncluded {
-    pub fn new(timestamp: chrono::DateTime<chrono::Utc>, comment_id: Option<u64>) -> Self {
+    pub fn new(timestamp: chrono::DateTime<chrono::Utc>, comment_id: Option<CommentRef>) -> Self {
         Self {
             timestamp,
             comment_id,
@@ -37,6 +37,7 @@ impl BotIncluded {
 
         if pr.merged.is_some() {
             debug!("PR {} is already merged. Skipping", pr.full_id,);
+            context.github.forget_comments(pr);
             context
                 .reply_with_error(
                     pr,
@@ -49,7 +50,36 @@ impl BotIncluded {
         }
 
         debug!("Starting PR {}", pr.full_id);
-        context.near.send_start(pr, sender.is_maintainer()).await?;
+        if let Err(e) = context.near.send_start(pr, sender.is_maintainer()).await {
+            match e.downcast_ref::<ContractError>() {
+                Some(ContractError::PrAlreadyExists) => {
+                    debug!("PR {} was already included. Skipping", pr.full_id);
+                    return Ok(false);
+                }
+                Some(ContractError::UserOptedOut) => {
+                    debug!("Author of {} opted out of the race. Skipping", pr.full_id);
+                    context.github.forget_comments(pr);
+                    return Ok(false);
+                }
+                Some(ContractError::RepoPaused) => {
+                    debug!("Repository of {} was paused. Skipping", pr.full_id);
+                    context.github.forget_comments(pr);
+                    context
+                        .reply_with_error(
+                            pr,
+                            self.comment_id,
+                            MsgCategory::ErrorPausedMessage,
+                            vec![("user".to_string(), sender.login.clone())],
+                        )
+                        .await?;
+                    return Ok(false);
+                }
+                _ => {
+                    context.github.forget_comments(pr);
+                    return Err(e);
+                }
+            }
+        }
 
         if let Some(comment_id) = self.comment_id {
             context
@@ -60,14 +90,12 @@ impl BotIncluded {
         Ok(true)
     }
 
-    pub fn construct(comment: &Comment) -> Command {
-        Command::Include(BotIncluded::new(comment.created_at, Some(comment.id.0)))
+    pub fn construct(comment: &CommandComment) -> Command {
+        Command::Include(BotIncluded::new(comment.created_at, Some(comment.id)))
     }
 
     pub fn parse_body(bot_name: &str, pr_metadata: &PrMetadata) -> Option<Command> {
-        let body = pr_metadata.body.as_str();
-        let bot_name = format!("@{}", bot_name);
-        if !body.contains(&bot_name) {
+        if parser::parse_commands(bot_name, &pr_metadata.body).is_empty() {
             return None;
         }
 
@@ -0,0 +1,199 @@
+use tracing::{debug, info, instrument};
+
+use crate::messages::MsgCategory;
+
+use shared::github::User;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub enum TeamAction {
+    Join(String),
+    Approve(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BotTeam {
+    pub action: Option<TeamAction>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: CommentRef,
+}
+
+impl BotTeam {
+    #[instrument(skip(self, pr, context, _check_info, sender), fields(pr = pr.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        _check_info: PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<bool> {
+        match &self.action {
+            Some(TeamAction::Join(team)) => self.join(pr, context, sender, team).await,
+            Some(TeamAction::Approve(user)) => self.approve(pr, context, sender, user).await,
+            None => {
+                context
+                    .reply_with_error(
+                        pr,
+                        Some(self.comment_id),
+                        MsgCategory::ErrorUnknownCommandMessage,
+                        vec![],
+                    )
+                    .await?;
+                Ok(false)
+            }
+        }
+    }
+
+    async fn join(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        sender: &User,
+        team: &str,
+    ) -> anyhow::Result<bool> {
+        // Only maintainers can create new teams, so nobody can spam them in the organization
+        let existing = context.near.team(team).await?;
+        match existing {
+            Some(existing) if existing.organization != pr.owner => {
+                info!(
+                    "Team {} belongs to the organization {}. Skipping",
+                    team, existing.organization
+                );
+                context
+                    .reply_with_error(
+                        pr,
+                        Some(self.comment_id),
+                        MsgCategory::ErrorTeamTakenMessage,
+                        vec![("team".to_string(), team.to_string())],
+                    )
+                    .await?;
+                return Ok(false);
+            }
+            Some(_) => {}
+            None if sender.is_maintainer() => {
+                debug!("Maintainer {} created team {}", sender.login, team);
+                context.near.send_team_create(team, &pr.owner).await?;
+            }
+            None => return self.team_not_found(pr, context, team).await,
+        }
+
+        debug!("User {} requested to join team {}", sender.login, team);
+        context
+            .near
+            .send_team_request(team, &pr.owner, &sender.login)
+            .await?;
+        context
+            .reply(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::TeamRequestMessage,
+                vec![
+                    ("user".to_string(), sender.login.clone()),
+                    ("team".to_string(), team.to_string()),
+                ],
+            )
+            .await?;
+        Ok(false)
+    }
+
+    async fn team_not_found(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        team: &str,
+    ) -> anyhow::Result<bool> {
+        info!(
+            "Team {} doesn't exist in the organization {}. Skipping",
+            team, pr.owner
+        );
+        context
+            .reply_with_error(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::ErrorTeamNotFoundMessage,
+                vec![("team".to_string(), team.to_string())],
+            )
+            .await?;
+        Ok(false)
+    }
+
+    async fn approve(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        sender: &User,
+        user: &str,
+    ) -> anyhow::Result<bool> {
+        if !sender.is_maintainer() {
+            info!(
+                "Tried to approve a team request from not maintainer: {}. Skipping",
+                pr.full_id
+            );
+            context
+                .reply_with_error(
+                    pr,
+                    Some(self.comment_id),
+                    MsgCategory::ErrorRightsViolationMessage,
+                    vec![],
+                )
+                .await?;
+            return Ok(false);
+        }
+
+        // Maintainers can approve only requests to the teams of their organization
+        let team = match context.near.team_request(user).await? {
+            Some(team) => context.near.team(&team).await?,
+            None => None,
+        };
+        let team = match team {
+            Some(team) if team.organization == pr.owner => team,
+            _ => {
+                info!(
+                    "No team request from {} for the organization {}. Skipping",
+                    user, pr.owner
+                );
+                context
+                    .reply_with_error(
+                        pr,
+                        Some(self.comment_id),
+                        MsgCategory::ErrorTeamNoRequestMessage,
+                        vec![("user".to_string(), user.to_string())],
+                    )
+                    .await?;
+                return Ok(false);
+            }
+        };
+
+        debug!("Approving {} to join team {}", user, team.name);
+        context.near.send_team_approve(&pr.owner, user).await?;
+        context
+            .reply(
+                pr,
+                Some(self.comment_id),
+                MsgCategory::TeamJoinedMessage,
+                vec![
+                    ("user".to_string(), user.to_string()),
+                    ("team".to_string(), team.name),
+                ],
+            )
+            .await?;
+        Ok(false)
+    }
+
+    pub fn construct(comment: &CommandComment, command: &ParsedCommand) -> Command {
+        let action = match (command.arg(0), command.arg(1)) {
+            (Some("join"), Some(team)) => Some(TeamAction::Join(team.to_string())),
+            (Some("approve"), Some(user)) => Some(TeamAction::Approve(
+                user.trim_start_matches('@').to_string(),
+            )),
+            _ => None,
+        };
+
+        Command::Team(BotTeam {
+            action,
+            timestamp: comment.created_at,
+            comment_id: comment.id,
+        })
+    }
+}
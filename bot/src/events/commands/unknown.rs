@@ -10,7 +10,7 @@ pub struct UnknownCommand {
     pub command: String,
     pub args: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub comment_id: u64,
+    pub comment_id: CommentRef,
 }
 
 impl UnknownCommand {
@@ -18,7 +18,7 @@ impl UnknownCommand {
         user: User,
         command: String,
         args: String,
-        comment_id: u64,
+        comment_id: CommentRef,
         timestamp: chrono::DateTime<chrono::Utc>,
     ) -> Self {
         Self {
@@ -55,15 +55,12 @@ impl UnknownCommand {
         Ok(false)
     }
 
-    pub fn construct(comment: &Comment, command: String, args: String) -> Command {
+    pub fn construct(comment: &CommandComment, command: &ParsedCommand) -> Command {
         Command::Unknown(Self::new(
-            User::new(
-                comment.user.login.clone(),
-                comment.author_association.clone(),
-            ),
-            command,
-            args,
-            comment.id.0,
+            comment.user.clone(),
+            command.name.clone(),
+            command.args.join(" "),
+            comment.id,
             comment.created_at,
         ))
     }
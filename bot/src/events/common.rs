@@ -1,6 +1,5 @@
 use shared::PRInfo;
 use std::collections::HashMap;
-use tracing::trace;
 
 use crate::messages::MsgCategory;
 
@@ -16,7 +15,7 @@ impl Context {
     pub async fn reply(
         &self,
         pr_metadata: &PrMetadata,
-        comment_id: Option<u64>,
+        comment_id: Option<CommentRef>,
         msg: MsgCategory,
         args: Vec<(String, String)>,
     ) -> anyhow::Result<Comment> {
@@ -45,7 +44,7 @@ impl Context {
     pub async fn reply_with_error(
         &self,
         pr_metadata: &PrMetadata,
-        comment_id: Option<u64>,
+        comment_id: Option<CommentRef>,
         error: MsgCategory,
         args: Vec<(String, String)>,
     ) -> anyhow::Result<()> {
@@ -53,30 +52,3 @@ impl Context {
         Ok(())
     }
 }
-
-pub fn extract_command_with_args(bot_name: &str, comment: &Comment) -> Option<(String, String)> {
-    let body = comment
-        .body
-        .as_ref()
-        .or(comment.body_html.as_ref())
-        .or(comment.body_text.as_ref())?
-        .to_lowercase();
-
-    let bot_name = format!("@{}", bot_name);
-    let position = body.find(&bot_name)?;
-
-    let commands = body[position + bot_name.len()..]
-        .split_whitespace()
-        .collect::<Vec<&str>>();
-
-    if commands.is_empty() {
-        return Some((String::new(), String::new()));
-    }
-
-    let command = commands[0].to_string();
-    let args = commands[1..].join(" ");
-
-    trace!("Extracted command: {command}, args: {args}");
-
-    Some((command, args))
-}
@@ -7,7 +7,7 @@ use tracing::{info, instrument};
 use crate::{api, messages::MessageLoader};
 
 use shared::{
-    github::{PrMetadata, User},
+    github::{CommandComment, CommentRef, PrMetadata, User},
     near::NearClient,
     PRInfo,
 };
@@ -25,6 +25,7 @@ pub struct Context {
     pub messages: Arc<MessageLoader>,
     pub prometheus: Arc<api::prometheus::PrometheusClient>,
     pub telegram: Arc<api::telegram::TelegramSubscriber>,
+    pub server: Arc<api::server::ServerClient>,
 }
 
 pub struct Event {
@@ -43,6 +44,7 @@ impl Event {
                 command,
                 sender,
                 notification_id,
+                label,
             } => {
                 let should_update = command
                     .execute(
@@ -53,14 +55,26 @@ impl Event {
                         self.comment.is_none(),
                     )
                     .await;
-                if should_update.is_ok() {
+                if let (Ok(_), Some(notification_id)) = (&should_update, notification_id) {
                     context
                         .github
                         .mark_notification_as_read(notification_id.0)
                         .await?;
                 }
+                // The label is removed even if the command failed, so it isn't executed again
+                if let Some(label) = label {
+                    context
+                        .github
+                        .remove_label(&self.pr.owner, &self.pr.repo, self.pr.number, label)
+                        .await?;
+                }
                 should_update
             }
+            EventType::Deleted { command, sender } => {
+                command
+                    .revoke(&self.pr, context.clone(), check_info, sender)
+                    .await
+            }
             EventType::Action(action) => {
                 action.execute(&self.pr, context.clone(), check_info).await
             }
@@ -79,7 +93,14 @@ pub enum EventType {
     Command {
         command: Command,
         sender: User,
-        notification_id: NotificationId,
+        // Edited commands are found without a notification
+        notification_id: Option<NotificationId>,
+        // Command labels are removed once processed
+        label: Option<String>,
+    },
+    Deleted {
+        command: Command,
+        sender: User,
     },
     Action(Action),
 }
@@ -95,6 +116,12 @@ impl std::fmt::Display for EventType {
                 command,
                 name = sender.login
             ),
+            EventType::Deleted { command, sender } => write!(
+                f,
+                "Deleted command `{}` send by [{name}](https://github.com/{name})",
+                command,
+                name = sender.login
+            ),
             EventType::Action(action) => write!(f, "Action `{action}`",),
         }
     }
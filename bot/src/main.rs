@@ -1,10 +1,18 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::Arc,
+};
 
 use futures::future::join_all;
 use race_of_sloths_bot::{
-    api::{prometheus::PrometheusClient, telegram, GithubClient},
-    events::{actions::Action, Context, Event, EventType},
-    messages::MessageLoader,
+    api::{prometheus::PrometheusClient, server::ServerClient, telegram, GithubClient},
+    events::{
+        actions::{staleness, Action, Staleness, STALE_WARNING_DAYS, STALE_WARNING_MARKER},
+        commands::Command,
+        Context, Event, EventType,
+    },
+    messages::{status_label, MessageLoader},
 };
 use rocket::routes;
 use serde::Deserialize;
@@ -13,7 +21,10 @@ use tracing::{debug, error, info, instrument, trace};
 use tracing_subscriber::{layer::SubscriberExt, EnvFilter};
 
 use shared::near::NearClient;
-use shared::{github::PrMetadata, TimePeriod};
+use shared::{github::PrMetadata, Operation, PRId, TimePeriod, SCORE_TIMEOUT_IN_NANOSECONDS};
+
+// Batches of the moved repo per check, so a stuck move doesn't burn the gas
+const MAX_MOVE_REPO_BATCHES: usize = 20;
 
 #[derive(Deserialize)]
 struct Env {
@@ -24,6 +35,8 @@ struct Env {
     message_file: PathBuf,
     telegram_token: String,
     telegram_chat_id: String,
+    // Receives the texts of the score reasons
+    server_url: Option<String>,
 }
 
 #[rocket::get("/metrics")]
@@ -70,6 +83,7 @@ async fn main() -> anyhow::Result<()> {
         messages: messages.into(),
         prometheus,
         telegram: telegram.into(),
+        server: ServerClient::new(env.server_url).into(),
     };
 
     tokio::select! {
@@ -97,9 +111,46 @@ async fn run(context: Context) {
     let mut interval: tokio::time::Interval = tokio::time::interval(minute);
     let mut merge_time = std::time::SystemTime::now();
     let merge_interval = 60 * minute;
+    // Repos are moved rarely, and every tracked repo is requested from GitHub
+    let mut move_check_time = std::time::SystemTime::now();
+    let move_check_interval = 24 * 60 * minute;
+    let mut paused = false;
+
+    if let Err(e) = index_prs(&context).await {
+        error!("Failed to index PRs: {}", e);
+    }
+    if let Err(e) = watch_comments(&context).await {
+        error!("Failed to watch comments: {}", e);
+    }
 
     loop {
+        // Notifications stay unread, so they are processed once the contract is unpaused
+        let is_paused = is_contract_paused(&context).await;
+        if is_paused != paused {
+            if is_paused {
+                tracing::warn!("Contract is paused. Stopping the events processing");
+            } else {
+                tracing::warn!("Contract is unpaused. Resuming the events processing");
+            }
+            paused = is_paused;
+        }
+        if paused {
+            interval.tick().await;
+            continue;
+        }
+        if let Err(e) = context.near.refresh_paused_methods().await {
+            error!("Failed to refresh paused methods: {e}");
+        }
+
         let current_time = std::time::SystemTime::now();
+        // PRs of the moved repos have to be re-keyed before we look them up by the new name
+        if current_time >= move_check_time {
+            if let Err(e) = moved_repos(&context).await {
+                error!("Failed to check moved repos: {}", e);
+            }
+            move_check_time = current_time + move_check_interval;
+        }
+
         (_, _, merge_time) = tokio::join!(
             interval.tick(),
             event_task(context.clone()),
@@ -108,6 +159,16 @@ async fn run(context: Context) {
     }
 }
 
+async fn is_contract_paused(context: &Context) -> bool {
+    match context.near.is_paused().await {
+        Ok(paused) => paused,
+        Err(e) => {
+            error!("Failed to check if the contract is paused: {e}");
+            false
+        }
+    }
+}
+
 async fn event_task(context: Context) {
     let events = match context.github.get_events().await {
         Ok(events) => events,
@@ -118,7 +179,10 @@ async fn event_task(context: Context) {
     };
 
     info!("Received {} events.", events.len());
+    execute_per_pr(context, events).await;
+}
 
+async fn execute_per_pr(context: Context, events: Vec<Event>) {
     let events_per_pr = events.into_iter().fold(
         std::collections::HashMap::new(),
         |mut map: HashMap<String, Vec<Event>>, event| {
@@ -146,6 +210,22 @@ async fn merge_and_execute_task(
         return merge_time;
     }
 
+    match context.github.get_edited_events().await {
+        Ok(events) => execute_per_pr(context.clone(), events).await,
+        Err(e) => error!("Failed to get edited events: {}", e),
+    }
+
+    // Scores have to be applied before the finalization
+    match reaction_events(&context).await {
+        Ok(events) => execute_per_pr(context.clone(), events).await,
+        Err(e) => error!("Failed to get reaction events: {}", e),
+    }
+
+    match label_events(&context).await {
+        Ok(events) => execute_per_pr(context.clone(), events).await,
+        Err(e) => error!("Failed to get label events: {}", e),
+    }
+
     let events = match merge_events(&context).await {
         Ok(events) => events,
         Err(e) => {
@@ -222,9 +302,12 @@ async fn execute(context: Context, events: Vec<Event>) {
                 .or(comment.body_text.as_ref())
                 .cloned()
                 .unwrap_or_default();
-            let status = context
-                .messages
-                .status_message(&context.github.user_handle, &info, pr);
+            let status = context.messages.status_message(
+                &context.github.user_handle,
+                &info,
+                pr,
+                &context.github.score_reasons(pr),
+            );
 
             let message = context.messages.update_pr_status_message(text, status);
 
@@ -259,6 +342,7 @@ async fn execute(context: Context, events: Vec<Event>) {
                 &info,
                 pr,
                 &user,
+                &context.github.score_reasons(pr),
             );
 
             context
@@ -272,12 +356,114 @@ async fn execute(context: Context, events: Vec<Event>) {
     if let Err(e) = result {
         error!("Failed to update status comment for {}: {e}", pr.full_id);
     }
+
+    // The bot might not have permissions to manage labels in the repository
+    if let Err(e) = context
+        .github
+        .sync_status_label(&pr.owner, &pr.repo, pr.number, &status_label(&info))
+        .await
+    {
+        debug!("Failed to update status label for {}: {e}", pr.full_id);
+    }
+}
+
+// Backfills the contract indexes for the PRs stored before they were introduced.
+// Merged PRs are sent in the merge order, so they are finalized in the same order.
+#[instrument(skip(context))]
+async fn index_prs(context: &Context) -> anyhow::Result<()> {
+    let mut prs = context.near.unindexed_prs().await?;
+    if prs.is_empty() {
+        return Ok(());
+    }
+
+    prs.sort_by_key(|(_, merged_at)| *merged_at);
+    let pr_ids: Vec<String> = prs.into_iter().map(|(pr_id, _)| pr_id).collect();
+    let mut indexed = 0;
+    for chunk in pr_ids.chunks(100) {
+        indexed += context.near.send_index_prs(chunk).await?;
+    }
+    info!("Indexed {indexed} PRs");
+    Ok(())
+}
+
+// Comment edits of the PRs in the race are tracked again after a restart
+#[instrument(skip(context))]
+async fn watch_comments(context: &Context) -> anyhow::Result<()> {
+    let mut prs = context.near.unmerged_prs_all().await?;
+    prs.extend(context.near.unfinalized_prs_all().await?);
+    context
+        .github
+        .watch_comments(prs.into_iter().map(|pr| pr.pr_id()));
+    Ok(())
+}
+
+// Repos are checked by the tracked GitHub id, or by the name for the repos we don't know the id of
+#[instrument(skip(context))]
+async fn moved_repos(context: &Context) -> anyhow::Result<()> {
+    let mut repos: BTreeMap<(String, String), Option<u64>> = context
+        .near
+        .repo_ids()
+        .await?
+        .into_iter()
+        .map(|(id, organization, repo)| ((organization, repo), Some(id)))
+        .collect();
+    for pr in context.near.unmerged_prs_all().await? {
+        repos.entry((pr.organization, pr.repo)).or_default();
+    }
+
+    for ((owner, repo), id) in repos {
+        let result = match id {
+            Some(id) => context.github.get_repo_by_id(id).await,
+            None => context.github.get_repo(&owner, &repo).await,
+        };
+        let new_repo = match result {
+            Ok(new_repo) => new_repo,
+            Err(e) => {
+                error!("Failed to get repo {owner}/{repo}: {e}");
+                continue;
+            }
+        };
+        let Some(new_owner) = new_repo.owner.map(|owner| owner.login) else {
+            continue;
+        };
+        if new_owner == owner && new_repo.name == repo {
+            continue;
+        }
+
+        info!(
+            "Repo {owner}/{repo} is moved to {new_owner}/{}",
+            new_repo.name
+        );
+        // The rest of the big repo is moved on the next check
+        let mut left = u32::MAX;
+        for _ in 0..MAX_MOVE_REPO_BATCHES {
+            match context
+                .near
+                .send_move_repo(&owner, &repo, &new_owner, &new_repo.name, new_repo.id.0)
+                .await
+            {
+                Ok(0) => break,
+                Ok(now_left) if now_left >= left => {
+                    error!("Moving repo {owner}/{repo} makes no progress, {now_left} PRs left");
+                    break;
+                }
+                Ok(now_left) => left = now_left,
+                Err(e) => {
+                    error!("Failed to move repo {owner}/{repo}: {e}");
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 #[instrument(skip(context))]
 async fn merge_events(context: &Context) -> anyhow::Result<Vec<Event>> {
     let prs = context.near.unmerged_prs_all().await?;
     info!("Received {} PRs for merge request check", prs.len());
+    let pr_ids: Vec<PRId> = prs.iter().map(|pr| pr.pr_id()).collect();
+    let pending = context.near.pending_prs(&pr_ids).await?;
     let mut results = vec![];
 
     for pr in prs {
@@ -312,15 +498,43 @@ async fn merge_events(context: &Context) -> anyhow::Result<Vec<Event>> {
                 "PR {} is not merged. Checking for stale",
                 pr_metadata.full_id
             );
-            if check_for_stale_pr(&pr_metadata) {
-                info!("PR {} is stale. Creating an event", pr_metadata.full_id);
+            // GitHub doesn't notify about the draft transitions, so we compare with the contract
+            if pending.contains(&pr_metadata.full_id) != pr_metadata.draft && !pr_metadata.closed {
+                let action = if pr_metadata.draft {
+                    Action::converted_to_draft()
+                } else {
+                    Action::ready_for_review()
+                };
                 results.push(Event {
-                    event: EventType::Action(Action::stale()),
+                    event: EventType::Action(action),
                     pr: pr_metadata,
                     comment: comment.clone(),
                     event_time: chrono::Utc::now(),
                 });
+                continue;
             }
+
+            let action = match check_for_stale_pr(context, &pr_metadata).await {
+                Ok(Staleness::Active) => continue,
+                Ok(Staleness::Warn) => {
+                    info!("PR {} is inactive. Creating a warning", pr_metadata.full_id);
+                    Action::stale_warning()
+                }
+                Ok(Staleness::Stale) => {
+                    info!("PR {} is stale. Creating an event", pr_metadata.full_id);
+                    Action::stale()
+                }
+                Err(e) => {
+                    error!("Failed to check PR {} for stale: {e}", pr_metadata.full_id);
+                    continue;
+                }
+            };
+            results.push(Event {
+                event: EventType::Action(action),
+                pr: pr_metadata,
+                comment: comment.clone(),
+                event_time: chrono::Utc::now(),
+            });
             continue;
         }
         trace!("PR {} is merged. Creating an event", pr_metadata.full_id);
@@ -335,6 +549,108 @@ async fn merge_events(context: &Context) -> anyhow::Result<Vec<Event>> {
     Ok(results)
 }
 
+#[instrument(skip(context))]
+async fn reaction_events(context: &Context) -> anyhow::Result<Vec<Event>> {
+    if context.messages.reaction_scores.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut prs = context.near.unmerged_prs_all().await?;
+    prs.extend(context.near.unfinalized_prs_all().await?);
+    info!("Received {} PRs for reaction check", prs.len());
+
+    let mut results = vec![];
+    for pr in prs {
+        let votes = pr.score.clone();
+        let pr: PrMetadata = pr.into();
+        let comment_id = match context.github.status_comment_id(&pr).await {
+            Ok(Some(comment_id)) => comment_id,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("Failed to get bot comment for {}: {e}", pr.full_id);
+                continue;
+            }
+        };
+
+        let mut events = match context
+            .github
+            .get_reaction_events(&pr, comment_id, &votes, &context.messages.reaction_scores)
+            .await
+        {
+            Ok(events) if events.is_empty() => continue,
+            Ok(events) => events,
+            Err(e) => {
+                error!("Failed to get reactions for {}: {e}", pr.full_id);
+                continue;
+            }
+        };
+
+        // The status comment is updated with the text, so it's fetched only for the changes
+        let comment = match context
+            .github
+            .get_bot_comment(&pr.owner, &pr.repo, pr.number)
+            .await
+        {
+            Ok(comment) => comment,
+            Err(e) => {
+                error!("Failed to get bot comment for {}: {e}", pr.full_id);
+                continue;
+            }
+        };
+        for event in &mut events {
+            event.comment = comment.clone();
+        }
+        results.extend(events);
+    }
+    info!("Finished reaction task with {} events", results.len());
+    Ok(results)
+}
+
+#[instrument(skip(context))]
+async fn label_events(context: &Context) -> anyhow::Result<Vec<Event>> {
+    let organizations: Vec<String> = context
+        .near
+        .repos()
+        .await?
+        .into_iter()
+        .map(|repos| repos.organization)
+        .collect();
+    // PRs can be included with the label, so we look for them outside of the contract too
+    let prs = context
+        .github
+        .get_labeled_prs(&organizations, &Command::command_labels())
+        .await?;
+    info!("Received {} PRs for label check", prs.len());
+
+    let mut results = vec![];
+    for (owner, repo, number) in prs {
+        let pr = match context.github.get_pull_request(&owner, &repo, number).await {
+            Ok(pr) => pr,
+            Err(e) => {
+                error!("Failed to get PR: {e}");
+                continue;
+            }
+        };
+        let pr_metadata = match PrMetadata::try_from(pr) {
+            Ok(pr) => pr,
+            Err(e) => {
+                error!("Failed to convert PR: {e}");
+                continue;
+            }
+        };
+
+        match context.github.get_label_events(&pr_metadata).await {
+            Ok(events) => results.extend(events),
+            Err(e) => error!(
+                "Failed to get label events for {}: {e}",
+                pr_metadata.full_id
+            ),
+        }
+    }
+    info!("Finished label task with {} events", results.len());
+    Ok(results)
+}
+
 #[instrument(skip(context))]
 async fn finalized_events(context: &Context) -> anyhow::Result<Vec<Event>> {
     let prs = context.near.unfinalized_prs_all().await?;
@@ -347,16 +663,43 @@ async fn finalized_events(context: &Context) -> anyhow::Result<Vec<Event>> {
             .await
             .ok()
             .flatten();
-        (pr, comment)
+        let score_timeout = context
+            .near
+            .score_timeout(&pr.organization)
+            .await
+            .unwrap_or(SCORE_TIMEOUT_IN_NANOSECONDS);
+        (pr, comment, score_timeout)
     });
 
-    Ok(join_all(comment_id_futures)
+    let prs = join_all(comment_id_futures).await;
+
+    // PRs that failed to be finalized in a batch are finalized one by one on execution
+    for (pr, _, _) in &prs {
+        context.near.queue(Operation::Finalize {
+            pr_id: pr.pr_id(),
+            timestamp: None,
+        });
+    }
+    let mut finalized: HashMap<PRId, Vec<shared::Event>> = context
+        .near
+        .flush()
         .await
         .into_iter()
-        .map(|(pr, comment)| Event {
-            event: EventType::Action(Action::finalize()),
+        .filter_map(|(operation, result)| match result {
+            Ok(events) => Some((operation.pr_id(), events)),
+            Err(e) => {
+                tracing::warn!("Failed to finalize {} in a batch: {e}", operation.pr_id());
+                None
+            }
+        })
+        .collect();
+
+    Ok(prs
+        .into_iter()
+        .map(|(pr, comment, score_timeout)| Event {
+            event: EventType::Action(Action::finalize(finalized.remove(&pr.pr_id()))),
             event_time: pr
-                .ready_to_move_timestamp()
+                .ready_to_move_timestamp(score_timeout)
                 .map(|t| chrono::DateTime::from_timestamp_nanos(t as i64))
                 .unwrap_or_else(chrono::Utc::now),
             pr: pr.into(),
@@ -365,12 +708,33 @@ async fn finalized_events(context: &Context) -> anyhow::Result<Vec<Event>> {
         .collect())
 }
 
-fn check_for_stale_pr(pr: &PrMetadata) -> bool {
-    if pr.merged.is_some() {
-        return false;
+// Our last warning is fetched only for the PRs that are inactive for a while
+async fn check_for_stale_pr(context: &Context, pr: &PrMetadata) -> anyhow::Result<Staleness> {
+    let now = chrono::Utc::now();
+    if pr.merged.is_some()
+        || (!pr.closed && now - pr.updated_at < chrono::Duration::days(STALE_WARNING_DAYS))
+    {
+        return Ok(Staleness::Active);
     }
 
-    let now = chrono::Utc::now();
-    let stale = now - pr.updated_at;
-    stale.num_days() > 14 || pr.closed
+    let check_info = context.check_info(pr).await?;
+    let snoozed_until = check_info
+        .snoozed_until
+        .map(|t| chrono::DateTime::from_timestamp_nanos(t as i64));
+    let last_warning = context
+        .github
+        .get_last_bot_comment_with(&pr.owner, &pr.repo, pr.number, STALE_WARNING_MARKER)
+        .await?
+        .map(|comment| comment.created_at);
+
+    // Pending reviewers might be requested long ago or automatically, so the timeline decides
+    let waiting_on_review = pr.review_requested && context.github.is_waiting_on_review(pr).await?;
+
+    Ok(staleness(
+        pr,
+        snoozed_until,
+        waiting_on_review,
+        last_warning,
+        now,
+    ))
 }
@@ -2,13 +2,18 @@ use rand::seq::SliceRandom;
 use rand::thread_rng;
 use serde::{Deserialize, Serialize};
 use shared::github::PrMetadata;
-use shared::{PRInfo, TimePeriod, User};
-use std::collections::{HashMap, HashSet};
+use shared::{Achievement, Base58CryptoHash, PRInfo, TimePeriod, User};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::ops::Add;
 use std::path::PathBuf;
 use tracing::error;
 
+use crate::events::commands::LABEL_PREFIX;
+
+// GitHub reactions that can be mapped to scores, 👍 and 👎 are left for the plain approvals
+const SCORE_REACTIONS: [&str; 6] = ["laugh", "confused", "heart", "hooray", "rocket", "eyes"];
+
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum MsgCategory {
     IncludeBasicMessage,
@@ -26,7 +31,19 @@ pub enum MsgCategory {
     FinalMessagesFirstLifetimeBonus,
     FinalMessagesLifetimeBonus,
     FinalMessagesFeedbackForm,
+    FinalMessagesReferral,
+    FinalMessagesAchievements,
     StaleMessage,
+    StaleWarningMessage,
+    SnoozeMessage,
+    ReopenMessage,
+    RevertDetectedMessage,
+    RevertMessage,
+    TeamRequestMessage,
+    TeamJoinedMessage,
+    ReferralMessage,
+    LinkAccountMessage,
+    OptOutMessage,
     ErrorUnknownCommandMessage,
     ErrorRightsViolationMessage,
     ErrorLateIncludeMessage,
@@ -35,7 +52,15 @@ pub enum MsgCategory {
     ErrorPausedMessage,
     ErrorLateScoringMessage,
     ErrorSelfScore,
+    ErrorChangesRequestedMessage,
     ErrorOrgNotInAllowedListMessage,
+    ErrorTeamNoRequestMessage,
+    ErrorTeamNotFoundMessage,
+    ErrorTeamTakenMessage,
+    ErrorReferralMessage,
+    ErrorLinkAccountMessage,
+    ErrorSnoozeDaysMessage,
+    ErrorRevertMessage,
 
     FirstTimeContribution,
     FirstWeekContribution,
@@ -100,6 +125,9 @@ pub struct MessageLoader {
     pub leaderboard_link: String,
     pub form: String,
     pub picture_api_link: String,
+    // Reactions on the status comment that maintainers can use to score the PR
+    #[serde(default)]
+    pub reaction_scores: BTreeMap<String, u8>,
 
     // Messages
     pub include_basic_messages: Messages,
@@ -117,7 +145,19 @@ pub struct MessageLoader {
     pub final_messages_first_lifetime_bonus: Messages,
     pub final_messages_lifetime_bonus: Messages,
     pub final_messages_feedback_form: Messages,
+    pub final_messages_referral: Messages,
+    pub final_messages_achievements: Messages,
     pub stale_messages: Messages,
+    pub stale_warning_messages: Messages,
+    pub snooze_messages: Messages,
+    pub reopen_messages: Messages,
+    pub revert_detected_messages: Messages,
+    pub revert_messages: Messages,
+    pub team_request_messages: Messages,
+    pub team_joined_messages: Messages,
+    pub referral_messages: Messages,
+    pub link_account_messages: Messages,
+    pub opt_out_messages: Messages,
 
     // Errors
     pub error_unknown_command_messages: Messages,
@@ -128,7 +168,15 @@ pub struct MessageLoader {
     pub error_unpause_unpaused_messages: Messages,
     pub error_paused_messages: Messages,
     pub error_selfscore_messages: Messages,
+    pub error_changes_requested_messages: Messages,
     pub error_org_not_in_allowed_list_messages: Messages,
+    pub error_team_no_request_messages: Messages,
+    pub error_team_not_found_messages: Messages,
+    pub error_team_taken_messages: Messages,
+    pub error_referral_messages: Messages,
+    pub error_link_account_messages: Messages,
+    pub error_snooze_days_messages: Messages,
+    pub error_revert_messages: Messages,
 
     // Message by amount of contributions
     pub first_time_contribution: Messages,
@@ -146,6 +194,13 @@ impl MessageLoader {
     pub fn load_from_file(file_path: &PathBuf, bot_name: &str) -> anyhow::Result<Self> {
         let file_content = fs::read_to_string(file_path)?;
         let mut result: Self = toml::from_str(&file_content)?;
+        if let Some(reaction) = result
+            .reaction_scores
+            .keys()
+            .find(|reaction| !SCORE_REACTIONS.contains(&reaction.as_str()))
+        {
+            anyhow::bail!("Reaction {reaction} can't be used for scoring");
+        }
         result.postprocess_messages_with_link(bot_name);
         tracing::trace!("Loaded messages: {:#?}", result);
         Ok(result)
@@ -184,7 +239,19 @@ impl MessageLoader {
             &mut self.final_messages_monthly_streak,
             &mut self.final_messages_weekly_streak,
             &mut self.final_messages_feedback_form,
+            &mut self.final_messages_referral,
+            &mut self.final_messages_achievements,
             &mut self.stale_messages,
+            &mut self.stale_warning_messages,
+            &mut self.snooze_messages,
+            &mut self.reopen_messages,
+            &mut self.revert_detected_messages,
+            &mut self.revert_messages,
+            &mut self.team_request_messages,
+            &mut self.team_joined_messages,
+            &mut self.referral_messages,
+            &mut self.link_account_messages,
+            &mut self.opt_out_messages,
             &mut self.error_unknown_command_messages,
             &mut self.error_rights_violation_messages,
             &mut self.error_late_include_messages,
@@ -193,7 +260,15 @@ impl MessageLoader {
             &mut self.error_unpause_unpaused_messages,
             &mut self.error_paused_messages,
             &mut self.error_selfscore_messages,
+            &mut self.error_changes_requested_messages,
             &mut self.error_org_not_in_allowed_list_messages,
+            &mut self.error_team_no_request_messages,
+            &mut self.error_team_not_found_messages,
+            &mut self.error_team_taken_messages,
+            &mut self.error_referral_messages,
+            &mut self.error_link_account_messages,
+            &mut self.error_snooze_days_messages,
+            &mut self.error_revert_messages,
             &mut self.first_time_contribution,
             &mut self.first_week_contribution,
             &mut self.first_month_contribution,
@@ -227,19 +302,39 @@ impl MessageLoader {
                 &self.final_messages_first_lifetime_bonus
             }
             MsgCategory::FinalMessagesFeedbackForm => &self.final_messages_feedback_form,
+            MsgCategory::FinalMessagesReferral => &self.final_messages_referral,
+            MsgCategory::FinalMessagesAchievements => &self.final_messages_achievements,
             MsgCategory::FinalMessagesLifetimeBonus => &self.final_messages_lifetime_bonus,
             MsgCategory::StaleMessage => &self.stale_messages,
+            MsgCategory::StaleWarningMessage => &self.stale_warning_messages,
+            MsgCategory::SnoozeMessage => &self.snooze_messages,
+            MsgCategory::ReopenMessage => &self.reopen_messages,
+            MsgCategory::RevertDetectedMessage => &self.revert_detected_messages,
+            MsgCategory::RevertMessage => &self.revert_messages,
+            MsgCategory::TeamRequestMessage => &self.team_request_messages,
+            MsgCategory::TeamJoinedMessage => &self.team_joined_messages,
+            MsgCategory::ReferralMessage => &self.referral_messages,
+            MsgCategory::LinkAccountMessage => &self.link_account_messages,
+            MsgCategory::OptOutMessage => &self.opt_out_messages,
             MsgCategory::ErrorUnknownCommandMessage => &self.error_unknown_command_messages,
             MsgCategory::ErrorRightsViolationMessage => &self.error_rights_violation_messages,
             MsgCategory::ErrorLateIncludeMessage => &self.error_late_include_messages,
             MsgCategory::ErrorLateScoringMessage => &self.error_late_scoring_messages,
             MsgCategory::ErrorSelfScore => &self.error_selfscore_messages,
+            MsgCategory::ErrorChangesRequestedMessage => &self.error_changes_requested_messages,
             MsgCategory::ErrorOrgNotInAllowedListMessage => {
                 &self.error_org_not_in_allowed_list_messages
             }
             MsgCategory::ErrorPausePausedMessage => &self.error_pause_paused_messages,
             MsgCategory::ErrorUnpauseUnpausedMessage => &self.error_unpause_unpaused_messages,
             MsgCategory::ErrorPausedMessage => &self.error_paused_messages,
+            MsgCategory::ErrorTeamNoRequestMessage => &self.error_team_no_request_messages,
+            MsgCategory::ErrorTeamNotFoundMessage => &self.error_team_not_found_messages,
+            MsgCategory::ErrorTeamTakenMessage => &self.error_team_taken_messages,
+            MsgCategory::ErrorReferralMessage => &self.error_referral_messages,
+            MsgCategory::ErrorLinkAccountMessage => &self.error_link_account_messages,
+            MsgCategory::ErrorSnoozeDaysMessage => &self.error_snooze_days_messages,
+            MsgCategory::ErrorRevertMessage => &self.error_revert_messages,
 
             MsgCategory::FirstTimeContribution => &self.first_time_contribution,
             MsgCategory::FirstWeekContribution => &self.first_week_contribution,
@@ -260,6 +355,7 @@ impl MessageLoader {
         check_info: &PRInfo,
         pr: &PrMetadata,
         user: &User,
+        reasons: &HashMap<Base58CryptoHash, String>,
     ) -> String {
         let user_specific_message = self.user_specific_message(user);
         let message = self
@@ -273,7 +369,7 @@ impl MessageLoader {
                 .collect(),
             )
             .unwrap_or_default();
-        let status_message = self.status_message(bot_name, check_info, pr);
+        let status_message = self.status_message(bot_name, check_info, pr, reasons);
         let message = self.update_pr_status_message(message, status_message);
         let common = self
             .get_message(MsgCategory::IncludeCommonMessage)
@@ -286,22 +382,17 @@ impl MessageLoader {
         message + &common
     }
 
-    pub fn status_message(&self, bot_name: &str, check_info: &PRInfo, pr: &PrMetadata) -> String {
+    // Reasons are matched with the votes by their hash, the unknown ones are left empty
+    pub fn status_message(
+        &self,
+        bot_name: &str,
+        check_info: &PRInfo,
+        pr: &PrMetadata,
+        reasons: &HashMap<Base58CryptoHash, String>,
+    ) -> String {
         let mut message = String::new();
 
-        let status = if check_info.excluded {
-            "excluded"
-        } else if !check_info.exist {
-            "stale" // PR was removed for inactivity
-        } else if check_info.executed {
-            "executed"
-        } else if check_info.votes.is_empty() {
-            "waiting for scoring"
-        } else if !check_info.merged {
-            "waiting for merge"
-        } else {
-            "waiting for finalization"
-        };
+        let status = pr_status(check_info);
 
         message.push_str(&format!(
             "\n<details><summary>Current status: <i>{status}</i></summary>\n",
@@ -311,20 +402,55 @@ impl MessageLoader {
             message.push_str(&format!("\nWe're waiting for maintainer to score this pull request with `@{bot_name} score [0,1,2,3,5,8,13]` command"));
         }
 
+        if !self.reaction_scores.is_empty()
+            && (status == "waiting for scoring" || status == "waiting for finalization")
+        {
+            message.push_str(&format!(
+                "\nMaintainers can also score by reacting to this comment: {}",
+                self.reaction_scores_text()
+            ));
+        }
+
+        if status == "pending" {
+            message.push_str("\nThis pull request is a draft. It will enter the race once it's marked as ready for review");
+        }
+
         if status == "stale" {
             message.push_str(&format!("\nThis pull request was removed from the race, but you can include it again with `@{bot_name} include` command"));
         }
 
         if status == "waiting for finalization" {
-            message.push_str(&format!("\nThe pull request is merged, you have 24 hours to finalize your scoring. The scoring ends {}", pr.merged.unwrap().add(chrono::Duration::days(1)).format("%c")));
+            message.push_str(&format!("\nThe pull request is merged, you have {} to finalize your scoring. The scoring ends {}", check_info.score_timeout_text(), pr.merged.unwrap().add(chrono::Duration::nanoseconds(check_info.score_timeout as i64)).format("%c")));
         }
 
         if !check_info.votes.is_empty() {
-            message.push_str("\n| Reviewer | Score |\n");
-            message.push_str("|--------|--------|\n");
+            let with_reasons = check_info
+                .votes
+                .iter()
+                .any(|vote| vote.reason_hash.is_some());
+            if with_reasons {
+                message.push_str("\n| Reviewer | Score | Reason |\n");
+                message.push_str("|--------|--------|--------|\n");
+            } else {
+                message.push_str("\n| Reviewer | Score |\n");
+                message.push_str("|--------|--------|\n");
+            }
 
             for vote in &check_info.votes {
-                message.push_str(&format!("| @{}  | {} |\n", vote.user, vote.score));
+                if !with_reasons {
+                    message.push_str(&format!("| @{}  | {} |\n", vote.user, vote.score));
+                    continue;
+                }
+                let reason = vote
+                    .reason_hash
+                    .as_ref()
+                    .and_then(|hash| reasons.get(hash))
+                    .map(|reason| reason.replace('|', "\\|").replace('\n', " "))
+                    .unwrap_or_default();
+                message.push_str(&format!(
+                    "| @{}  | {} | {} |\n",
+                    vote.user, vote.score, reason
+                ));
             }
             let final_score = check_info.average_score();
             message.push_str(&format!("\n**The average score is {}**\n", final_score));
@@ -342,6 +468,27 @@ impl MessageLoader {
         message
     }
 
+    fn reaction_scores_text(&self) -> String {
+        let mut scores: Vec<_> = self.reaction_scores.iter().collect();
+        scores.sort_by_key(|(_, score)| **score);
+        scores
+            .into_iter()
+            .map(|(reaction, score)| {
+                let emoji = match reaction.as_str() {
+                    "laugh" => "😄",
+                    "confused" => "😕",
+                    "heart" => "❤️",
+                    "hooray" => "🎉",
+                    "rocket" => "🚀",
+                    "eyes" => "👀",
+                    other => other,
+                };
+                format!("{emoji} {score}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     fn user_specific_message(&self, user: &User) -> String {
         let timestamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
         let current_period = TimePeriod::Month.time_string(timestamp);
@@ -492,6 +639,49 @@ impl MessageLoader {
 
         Ok(format!("{}\n\n{}", final_common, optional_message))
     }
+
+    pub fn achievements_message(
+        &self,
+        user_name: &str,
+        achievements: &[Achievement],
+    ) -> anyhow::Result<String> {
+        let achievements = achievements
+            .iter()
+            .map(|achievement| format!("**{}**", achievement.title()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = self
+            .get_message(MsgCategory::FinalMessagesAchievements)
+            .format(
+                [
+                    ("pr_author_username".to_string(), user_name.to_string()),
+                    ("achievements".to_string(), achievements),
+                ]
+                .into_iter()
+                .collect(),
+            )?;
+        Ok(format!("\n\n{message}"))
+    }
+
+    pub fn referral_message(
+        &self,
+        user_name: &str,
+        referrer: &str,
+        bonus_rating: u32,
+    ) -> anyhow::Result<String> {
+        let message = self
+            .get_message(MsgCategory::FinalMessagesReferral)
+            .format(
+                [
+                    ("pr_author_username".to_string(), user_name.to_string()),
+                    ("referrer".to_string(), referrer.to_string()),
+                    ("bonus_rating".to_string(), bonus_rating.to_string()),
+                ]
+                .into_iter()
+                .collect(),
+            )?;
+        Ok(format!("\n\n{message}"))
+    }
 }
 
 fn rating_breakthrough(
@@ -524,11 +714,36 @@ fn rating_breakthrough(
     result
 }
 
+pub fn pr_status(check_info: &PRInfo) -> &'static str {
+    if check_info.excluded {
+        "excluded"
+    } else if !check_info.exist {
+        "stale" // PR was removed for inactivity
+    } else if check_info.executed {
+        "executed"
+    } else if check_info.pending {
+        "pending"
+    } else if check_info.votes.is_empty() {
+        "waiting for scoring"
+    } else if !check_info.merged {
+        "waiting for merge"
+    } else {
+        "waiting for finalization"
+    }
+}
+
+// e.g. `sloth:waiting-for-scoring`
+pub fn status_label(check_info: &PRInfo) -> String {
+    format!("{LABEL_PREFIX}{}", pr_status(check_info).replace(' ', "-"))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use shared::{
         github::{PrMetadata, User},
-        Score,
+        reason_hash, Score,
     };
 
     use super::MessageLoader;
@@ -576,6 +791,7 @@ mod tests {
             percentage_bonus: 5,
             period_data: vec![("all-time".to_string(), period_data(1))],
             streaks: vec![],
+            achievements: vec![],
         };
 
         let mut pr_info = shared::PRInfo {
@@ -586,10 +802,13 @@ mod tests {
             executed: false,
             excluded: false,
             exist: true,
+            score_timeout: shared::SCORE_TIMEOUT_IN_NANOSECONDS,
+            snoozed_until: None,
         };
         let pr = PrMetadata {
             owner: "a".to_string(),
             repo: "a".to_string(),
+            repo_id: None,
             author: User::new(
                 "a".to_string(),
                 octocrab::models::AuthorAssociation::Contributor,
@@ -599,21 +818,28 @@ mod tests {
             number: 0,
             updated_at: chrono::Utc::now(),
             full_id: "a/a/0".to_string(),
+            title: "".to_string(),
             body: "".to_string(),
             closed: false,
+            reviews: Default::default(),
+            labels: Default::default(),
+            review_requested: false,
+            draft: false,
         };
 
-        let text1 = message_loader.include_message_text("bot", &pr_info, &pr, &user);
-        let status_message_init = message_loader.status_message("bot", &pr_info, &pr);
+        let reasons = HashMap::new();
+        let text1 = message_loader.include_message_text("bot", &pr_info, &pr, &user, &reasons);
+        let status_message_init = message_loader.status_message("bot", &pr_info, &pr, &reasons);
         println!("{}", text1);
         assert!(text1.contains(&status_message_init));
 
         pr_info.votes.push(Score {
             user: "b".to_string(),
             score: 5,
+            reason_hash: None,
         });
 
-        let new_status_message = message_loader.status_message("bot", &pr_info, &pr);
+        let new_status_message = message_loader.status_message("bot", &pr_info, &pr, &reasons);
         assert_ne!(status_message_init, new_status_message);
 
         let text2 =
@@ -623,7 +849,7 @@ mod tests {
         assert!(!text2.contains(&status_message_init));
 
         pr_info.executed = true;
-        let new_status_message = message_loader.status_message("bot", &pr_info, &pr);
+        let new_status_message = message_loader.status_message("bot", &pr_info, &pr, &reasons);
         assert_ne!(status_message_init, new_status_message);
 
         let text3 =
@@ -633,6 +859,75 @@ mod tests {
         assert!(text3.contains(&new_status_message));
     }
 
+    #[test]
+    fn score_reasons() {
+        let message_loader = load_message_loader();
+        let pr_info = shared::PRInfo {
+            votes: vec![
+                Score {
+                    user: "a".to_string(),
+                    score: 2,
+                    reason_hash: Some(reason_hash("No tests | docs")),
+                },
+                Score {
+                    user: "b".to_string(),
+                    score: 5,
+                    reason_hash: None,
+                },
+            ],
+            allowed_org: true,
+            allowed_repo: true,
+            exist: true,
+            ..Default::default()
+        };
+        let pr = PrMetadata {
+            owner: "a".to_string(),
+            repo: "a".to_string(),
+            repo_id: None,
+            author: User::new(
+                "c".to_string(),
+                octocrab::models::AuthorAssociation::Contributor,
+            ),
+            started: chrono::Utc::now(),
+            merged: None,
+            number: 0,
+            updated_at: chrono::Utc::now(),
+            full_id: "a/a/0".to_string(),
+            title: "".to_string(),
+            body: "".to_string(),
+            closed: false,
+            reviews: Default::default(),
+            labels: Default::default(),
+            review_requested: false,
+            draft: false,
+        };
+
+        let reasons: HashMap<_, _> = [(
+            reason_hash("No tests | docs"),
+            "No tests | docs".to_string(),
+        )]
+        .into_iter()
+        .collect();
+        let status = message_loader.status_message("bot", &pr_info, &pr, &reasons);
+        assert!(status.contains("| Reviewer | Score | Reason |"));
+        assert!(status.contains("| @a  | 2 | No tests \\| docs |"));
+        assert!(status.contains("| @b  | 5 |  |"));
+
+        // The text is unknown, e.g. after the restart
+        let status = message_loader.status_message("bot", &pr_info, &pr, &HashMap::new());
+        assert!(status.contains("| @a  | 2 |  |"));
+    }
+
+    #[test]
+    fn reaction_scores_are_sorted() {
+        let message_loader = load_message_loader();
+
+        assert_eq!(
+            message_loader.reaction_scores_text(),
+            "😄 2, ❤️ 3, 🎉 5, 🚀 8"
+        );
+    }
+
     #[test]
     fn rating_breakthrough_full() {
         let total_rating = 100;
@@ -659,4 +954,30 @@ mod tests {
         let result = super::rating_breakthrough(100, 5, 0, 5, 0);
         assert_eq!(result, "100 (50 base + 5 monthly bonus)");
     }
+
+    #[test]
+    fn achievements_message() {
+        let message_loader = load_message_loader();
+
+        let result = message_loader
+            .achievements_message(
+                "a",
+                &[
+                    shared::Achievement::FirstPR,
+                    shared::Achievement::FirstMaxScore,
+                ],
+            )
+            .unwrap();
+        assert!(result.contains("**First contribution**, **First 13 score**"));
+    }
+
+    #[test]
+    fn referral_message() {
+        let message_loader = load_message_loader();
+
+        let result = message_loader.referral_message("a", "b", 25).unwrap();
+        assert!(result.contains("@b"));
+        assert!(result.contains("@a"));
+        assert!(result.contains("+25"));
+    }
 }
@@ -0,0 +1,312 @@
+use std::collections::BTreeSet;
+
+use near_sdk::{near_bindgen, store::TreeMap};
+use shared::{Rank, RankBadge, UserAchievements};
+
+use super::*;
+
+#[near_bindgen]
+impl Contract {
+    // Moves the account to the new GitHub handle. If the new handle already has an account
+    // (the contributor was counted as a new sloth after the rename), it's merged into the old one.
+    pub fn sloth_rename(&mut self, old: GithubHandle, new: GithubHandle) {
+        self.assert_sloth_method("sloth_rename");
+
+        let user_id = match self.account_ids.get(&old) {
+            Some(user_id) => *user_id,
+            None => ContractError::UserNotFound.panic(),
+        };
+        if old == new {
+            ContractError::SameHandle.panic()
+        }
+
+        self.rename_account(user_id, new);
+    }
+
+    // Removes the contributor from the race. The on-chain history can't be erased,
+    // but the user is hidden from the views and can't be included anymore.
+    // Open PRs are excluded, merged ones are still finalized.
+    pub fn sloth_opt_out(&mut self, user: GithubHandle) {
+        self.assert_sloth_method("sloth_opt_out");
+
+        let user_id = match self.account_ids.get(&user) {
+            Some(user_id) => *user_id,
+            None => ContractError::UserNotFound.panic(),
+        };
+
+        if let Some(team) = self.user_teams.remove(&user_id) {
+            if let Some(VersionedTeam::V1(team)) = self.teams.get_mut(&team) {
+                team.remove_member(&user);
+            }
+        }
+        self.team_requests.remove(&user_id);
+        self.opted_out.insert(user_id);
+
+        let pr_ids: Vec<PRId> = self
+            .prs_by_author
+            .get(&user_id)
+            .map(|prs| prs.iter().map(|(pr_id, _)| pr_id.clone()).collect())
+            .unwrap_or_default();
+        for pr_id in pr_ids {
+            let Some(pr) = self.prs.get(&pr_id).cloned() else {
+                continue;
+            };
+            let pr: PRWithRating = pr.into();
+            if pr.merged_at.is_none() {
+                self.exclude_pr(user_id, pr_id, pr);
+            }
+        }
+    }
+
+    pub fn opted_out_users(&self) -> Vec<GithubHandle> {
+        self.opted_out
+            .iter()
+            .map(|user_id| {
+                let account: AccountWithPermanentPercentageBonus =
+                    self.users[*user_id].clone().into();
+                account.github_handle
+            })
+            .collect()
+    }
+
+    pub fn github_id_user(&self, github_id: u64) -> Option<GithubHandle> {
+        let user_id = *self.github_ids.get(&github_id)?;
+        if !self.is_live_account(user_id) {
+            return None;
+        }
+        let account: AccountWithPermanentPercentageBonus = self.users[user_id].clone().into();
+        Some(account.github_handle)
+    }
+}
+
+impl Contract {
+    // Binds the immutable GitHub id to the account and follows the login renames
+    pub fn track_github_id(&mut self, user: &GithubHandle, github_id: u64) {
+        let user_id = self
+            .github_ids
+            .get(&github_id)
+            .copied()
+            .filter(|user_id| self.is_live_account(*user_id));
+
+        match user_id {
+            Some(user_id) => {
+                let account: AccountWithPermanentPercentageBonus =
+                    self.users[user_id].clone().into();
+                if account.github_handle != *user {
+                    self.rename_account(user_id, user.clone());
+                }
+            }
+            None => {
+                let (user_id, _) = self.get_or_create_account(user);
+                self.github_ids.insert(github_id, user_id);
+                self.user_github_ids.insert(user_id, github_id);
+            }
+        }
+    }
+
+    // Merged accounts stay in the `users`, but their handle points to another account
+    pub fn is_live_account(&self, user_id: UserId) -> bool {
+        let Some(account) = self.users.get(user_id) else {
+            return false;
+        };
+        let account: AccountWithPermanentPercentageBonus = account.clone().into();
+        self.account_ids.get(&account.github_handle) == Some(&user_id)
+    }
+
+    fn rename_account(&mut self, user_id: UserId, new: GithubHandle) {
+        let mut account: AccountWithPermanentPercentageBonus = self.users[user_id].clone().into();
+        let old = account.github_handle.clone();
+
+        if let Some(other_id) = self.account_ids.get(&new).copied() {
+            self.merge_account(user_id, other_id, &mut account);
+        }
+
+        self.account_ids.remove(&old);
+        self.account_ids.insert(new.clone(), user_id);
+        account.github_handle = new.clone();
+        self.users[user_id] = VersionedAccount::V1(account);
+
+        let pr_ids: Vec<PRId> = self
+            .prs_by_author
+            .get(&user_id)
+            .map(|prs| prs.iter().map(|(pr_id, _)| pr_id.clone()).collect())
+            .unwrap_or_default();
+        for pr_id in pr_ids {
+            if let Some(VersionedPR::V1(pr)) = self.prs.get_mut(&pr_id) {
+                pr.author = new.clone();
+            } else if let Some(VersionedPR::V1(pr)) = self.executed_prs.get_mut(&pr_id) {
+                pr.author = new.clone();
+            }
+        }
+
+        if let Some(team) = self.user_teams.get(&user_id).cloned() {
+            if let Some(VersionedTeam::V1(team)) = self.teams.get_mut(&team) {
+                team.remove_member(&old);
+                team.add_member(new.clone());
+            }
+        }
+
+        for rank in Rank::iter() {
+            if let Some(VersionedRankBadge::V1(badge)) = self
+                .rank_badges
+                .get_mut(&RankBadge::token_id(user_id, rank))
+            {
+                badge.github_handle = new.clone();
+            }
+        }
+        self.mint_rank_badges(user_id);
+    }
+
+    // Moves all the data of the other account into the account
+    fn merge_account(
+        &mut self,
+        user_id: UserId,
+        other_id: UserId,
+        account: &mut AccountWithPermanentPercentageBonus,
+    ) {
+        let other: AccountWithPermanentPercentageBonus = self.users[other_id].clone().into();
+        account.merge(other.clone());
+        self.merged_accounts += 1;
+
+        let pr_ids: Vec<PRId> = match self.prs_by_author.remove(&other_id) {
+            Some(mut prs) => {
+                let pr_ids = prs.iter().map(|(pr_id, _)| pr_id.clone()).collect();
+                prs.clear();
+                pr_ids
+            }
+            None => vec![],
+        };
+        let mut timestamps = vec![];
+        for pr_id in &pr_ids {
+            let Some(pr) = self.prs.get(pr_id).or(self.executed_prs.get(pr_id)) else {
+                continue;
+            };
+            let pr: PRWithRating = pr.clone().into();
+            timestamps.extend([Some(pr.created_at), pr.merged_at].into_iter().flatten());
+        }
+
+        // Referees of the other account are moved with the bonuses they brought
+        let referee_ids: Vec<UserId> = match self.referees.remove(&other_id) {
+            Some(mut referees) => {
+                let referee_ids = referees.iter().copied().collect();
+                referees.clear();
+                referee_ids
+            }
+            None => vec![],
+        };
+        for referee_id in referee_ids {
+            let Some(referral) = self.referrals.get(&referee_id).cloned() else {
+                continue;
+            };
+            let mut referral: Referral = referral.into();
+            if let Some(bonus) = &referral.rewarded {
+                timestamps.push(bonus.rewarded_at);
+            }
+            if referee_id == user_id {
+                self.referrals.remove(&referee_id);
+                continue;
+            }
+
+            referral.referrer = user_id;
+            self.referrals
+                .insert(referee_id, VersionedReferral::V1(referral));
+            self.referees
+                .entry(user_id)
+                .or_insert_with(|| IterableSet::new(storage::StorageKey::RefereesEntry { user_id }))
+                .insert(referee_id);
+        }
+
+        if let Some(referral) = self.referrals.remove(&other_id) {
+            let referral: Referral = referral.into();
+            let referrer_id = referral.referrer;
+            if let Some(referees) = self.referees.get_mut(&referrer_id) {
+                referees.remove(&other_id);
+                if referrer_id != user_id && !self.referrals.contains_key(&user_id) {
+                    referees.insert(user_id);
+                    self.referrals
+                        .insert(user_id, VersionedReferral::V1(referral));
+                }
+            }
+        }
+
+        let mut periods = BTreeSet::new();
+        for timestamp in timestamps {
+            for period in TimePeriod::iter().filter(|p| *p != TimePeriod::Day) {
+                periods.insert(period.time_string(timestamp));
+            }
+        }
+        for period in periods {
+            let Some(data) = self.sloths_per_period.remove(&(other_id, period.clone())) else {
+                continue;
+            };
+            self.sloths_per_period
+                .entry((user_id, period))
+                .or_insert(VersionedUserPeriodData::V1(Default::default()))
+                .merge(data.into());
+        }
+
+        let streak_ids: Vec<StreakId> = self.streaks.iter().map(|s| s.id()).collect();
+        for streak_id in streak_ids {
+            let Some(data) = self.user_streaks.remove(&(other_id, streak_id)) else {
+                continue;
+            };
+            let data: StreakUserData = data.into();
+            let data = match self.user_streaks.get(&(user_id, streak_id)).cloned() {
+                Some(existing) => StreakUserData::from(existing).merge(data),
+                None => data,
+            };
+            self.user_streaks
+                .insert((user_id, streak_id), VersionedStreakUserData::V1(data));
+        }
+
+        let prs = self
+            .prs_by_author
+            .entry(user_id)
+            .or_insert_with(|| TreeMap::new(storage::StorageKey::PRsByAuthorEntry { user_id }));
+        for pr_id in pr_ids {
+            prs.insert(pr_id, ());
+        }
+
+        if let Some(achievements) = self.user_achievements.remove(&other_id) {
+            let mut merged: UserAchievements = self
+                .user_achievements
+                .get(&user_id)
+                .cloned()
+                .map(Into::into)
+                .unwrap_or_default();
+            merged.merge(achievements.into());
+            self.user_achievements
+                .insert(user_id, VersionedUserAchievements::V1(merged));
+        }
+
+        // Badges are minted again for the ranks of the merged account
+        for rank in Rank::iter() {
+            let token_id = RankBadge::token_id(other_id, rank);
+            let Some(VersionedRankBadge::V1(badge)) = self.rank_badges.remove(&token_id) else {
+                continue;
+            };
+            if let Some(tokens) = self.rank_badges_per_owner.get_mut(&badge.owner_id) {
+                tokens.retain(|id| *id != token_id);
+            }
+            events::log_nft_burn(badge.owner_id, vec![token_id]);
+        }
+
+        if self.opted_out.remove(&other_id) {
+            self.opted_out.insert(user_id);
+        }
+
+        if let Some(github_id) = self.user_github_ids.remove(&other_id) {
+            if !self.user_github_ids.contains_key(&user_id) {
+                self.github_ids.insert(github_id, user_id);
+                self.user_github_ids.insert(user_id, github_id);
+            }
+        }
+
+        if let Some(team) = self.user_teams.remove(&other_id) {
+            if let Some(VersionedTeam::V1(team)) = self.teams.get_mut(&team) {
+                team.remove_member(&other.github_handle);
+            }
+        }
+        self.team_requests.remove(&other_id);
+    }
+}
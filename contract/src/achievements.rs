@@ -0,0 +1,89 @@
+use shared::{UserAchievements, ACHIEVEMENT_REPOS_AMOUNT};
+
+use super::*;
+
+impl Contract {
+    // Achievements are unlocked only on finalization, so the progress of the users
+    // that contributed before the achievements were introduced is tracked since their next PR
+    pub fn unlock_achievements(
+        &mut self,
+        user_id: UserId,
+        pr: &PRWithRating,
+        score: u32,
+        timestamp: Timestamp,
+    ) {
+        let all_time = self
+            .period_data(user_id, &TimePeriod::AllTime.time_string(timestamp))
+            .unwrap_or_default();
+
+        let mut progress: UserAchievements = self
+            .user_achievements
+            .get(&user_id)
+            .cloned()
+            .map(Into::into)
+            .unwrap_or_default();
+
+        progress.add_repo(format!("{}/{}", pr.organization, pr.repo));
+        progress.first_contribution.get_or_insert(pr.created_at);
+
+        let unlocked = progress.unlock(&all_time, score, timestamp);
+        for achievement in unlocked.iter().copied() {
+            events::log_event(Event::AchievementUnlocked { achievement });
+        }
+        if !unlocked.is_empty() {
+            self.pr_achievements.insert(pr.pr_id(), unlocked);
+        }
+
+        self.user_achievements
+            .insert(user_id, VersionedUserAchievements::V1(progress));
+    }
+
+    // Takes back the achievements unlocked by the reverted PR, unless they are still achieved
+    // without it. The max score can't be checked against the other PRs, so it's always taken back
+    pub fn revert_achievements(&mut self, user_id: UserId, pr: &PRWithRating) {
+        let pr_id = pr.pr_id();
+        let achievements = self.pr_achievements.remove(&pr_id).unwrap_or_default();
+        let mut progress: UserAchievements = match self.user_achievements.get(&user_id) {
+            Some(progress) => progress.clone().into(),
+            None => return,
+        };
+
+        // The repos are collected again from the other executed PRs of the user
+        if progress
+            .repos
+            .contains(&format!("{}/{}", pr.organization, pr.repo))
+        {
+            progress.repos.clear();
+            let pr_ids = self
+                .prs_by_author
+                .get(&user_id)
+                .into_iter()
+                .flat_map(|prs| prs.iter());
+            for (other_id, _) in pr_ids {
+                if progress.repos.len() >= ACHIEVEMENT_REPOS_AMOUNT {
+                    break;
+                }
+                if *other_id == pr_id {
+                    continue;
+                }
+                if let Some(other) = self.executed_prs.get(other_id) {
+                    let other: PRWithRating = other.clone().into();
+                    progress.add_repo(format!("{}/{}", other.organization, other.repo));
+                }
+            }
+        }
+
+        let timestamp = env::block_timestamp();
+        let all_time = self
+            .period_data(user_id, &TimePeriod::AllTime.time_string(timestamp))
+            .unwrap_or_default();
+        let revoked: Vec<Achievement> = achievements
+            .into_iter()
+            .filter(|a| !a.is_achieved(&all_time, &progress, 0, timestamp))
+            .collect();
+        progress.unlocked.retain(|(a, _)| !revoked.contains(a));
+
+        self.user_achievements
+            .insert(user_id, VersionedUserAchievements::V1(progress));
+    }
+}
@@ -0,0 +1,197 @@
+use near_sdk::{
+    borsh,
+    json_types::{Base58CryptoHash, U64},
+    near_bindgen,
+    serde::{Deserialize, Serialize},
+    CryptoHash, NearSchema,
+};
+
+use super::*;
+
+// Executed PRs are kept on-chain for 6 months, after that they can be archived
+pub const ARCHIVE_AFTER_IN_NANOSECONDS: u64 = 180 * 24 * 60 * 60 * 1_000_000_000;
+
+// Every storage record carries 40 bytes of the protocol overhead
+pub const STORAGE_RECORD_OVERHEAD: u64 = 40;
+
+const REPORT_SAMPLE_SIZE: usize = 20;
+
+#[derive(BorshDeserialize, BorshSerialize, Default, Debug, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PRArchive {
+    pub prs: u64,
+    pub last_merged_at: Timestamp,
+    // Chained sha256 over the archived PRs, so the off-chain copy can be verified
+    pub hash: CryptoHash,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PRArchiveView {
+    pub prs: U64,
+    pub last_merged_at: U64,
+    pub hash: Base58CryptoHash,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionReport {
+    pub name: String,
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageReport {
+    pub total_bytes: u64,
+    // Approximate usage of the iterable collections
+    pub collections: Vec<CollectionReport>,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Archives executed PRs merged more than 6 months ago, and drops PRs closed or reverted
+    // more than 6 months ago. Exclusions are kept, so the excluded PRs can't be included again.
+    // Returns the amount of the pruned PRs, unknown or fresh PRs are skipped.
+    pub fn sloth_prune(&mut self, pr_ids: Vec<PRId>) -> u32 {
+        self.assert_sloth_method("sloth_prune");
+
+        let cutoff = env::block_timestamp().saturating_sub(ARCHIVE_AFTER_IN_NANOSECONDS);
+        let mut pruned = 0;
+        for pr_id in pr_ids {
+            if self.prune_closed_pr(&pr_id, cutoff) {
+                pruned += 1;
+                continue;
+            }
+            if self
+                .reverted_prs
+                .get(&pr_id)
+                .is_some_and(|reverted_at| *reverted_at < cutoff)
+            {
+                self.reverted_prs.remove(&pr_id);
+            }
+
+            self.backfill_pr(&pr_id);
+            let pr: PRWithRating = match self.executed_prs.get(&pr_id).cloned() {
+                Some(pr) => pr.into(),
+                None => continue,
+            };
+            let merged_at = pr.merged_at.unwrap_or_default();
+            if merged_at >= cutoff {
+                continue;
+            }
+
+            self.archive_pr(&pr_id, &pr);
+            if let Some(user_id) = self.account_ids.get(&pr.author).copied() {
+                self.unindex_pr(user_id, &pr_id, &pr);
+            }
+            self.forget_score_reasons(&pr_id, &pr);
+            self.pr_achievements.remove(&pr_id);
+            self.executed_prs.remove(&pr_id);
+
+            events::log_event(Event::PRArchived { pr });
+            pruned += 1;
+        }
+
+        pruned
+    }
+
+    pub fn pr_archive(&self) -> PRArchiveView {
+        PRArchiveView {
+            prs: self.pr_archive.prs.into(),
+            last_merged_at: self.pr_archive.last_merged_at.into(),
+            hash: self.pr_archive.hash.into(),
+        }
+    }
+
+    pub fn storage_report(&self) -> StorageReport {
+        StorageReport {
+            total_bytes: env::storage_usage(),
+            collections: vec![
+                collection_report("users", self.users.len(), self.users.iter().enumerate()),
+                collection_report(
+                    "organizations",
+                    self.organizations.len(),
+                    self.organizations.iter(),
+                ),
+                collection_report("prs", self.prs.len(), self.prs.iter()),
+                collection_report(
+                    "executed_prs",
+                    self.executed_prs.len(),
+                    self.executed_prs.iter(),
+                ),
+                collection_report(
+                    "streaks",
+                    self.streaks.len(),
+                    self.streaks.iter().enumerate(),
+                ),
+                collection_report("teams", self.teams.len(), self.teams.iter()),
+                collection_report(
+                    "rank_badges",
+                    self.rank_badges.len(),
+                    self.rank_badges.iter(),
+                ),
+                collection_report("repo_ids", self.repo_ids.len(), self.repo_ids.iter()),
+                collection_report(
+                    "reverted_prs",
+                    self.reverted_prs.len(),
+                    self.reverted_prs.iter(),
+                ),
+            ],
+        }
+    }
+}
+
+impl Contract {
+    fn prune_closed_pr(&mut self, pr_id: &PRId, cutoff: Timestamp) -> bool {
+        let pr: PRWithRating = match self.closed_prs.get(pr_id) {
+            Some(closed) if closed.closed_at < cutoff => closed.pr.clone().into(),
+            _ => return false,
+        };
+
+        self.forget_score_reasons(pr_id, &pr);
+        self.unindex_inactive_pr(pr_id, &pr.organization, &pr.repo);
+        self.closed_prs.remove(pr_id);
+        true
+    }
+
+    fn archive_pr(&mut self, pr_id: &PRId, pr: &PRWithRating) {
+        let mut data = self.pr_archive.hash.to_vec();
+        data.extend(borsh::to_vec(&(pr_id, pr)).unwrap());
+
+        self.pr_archive.hash = env::sha256_array(&data);
+        self.pr_archive.prs += 1;
+        self.pr_archive.last_merged_at = self
+            .pr_archive
+            .last_merged_at
+            .max(pr.merged_at.unwrap_or_default());
+    }
+}
+
+// Entries come from the collection counter, the size is extrapolated from the first records,
+// so the view doesn't walk the whole collection
+fn collection_report<K: BorshSerialize, V: BorshSerialize>(
+    name: &str,
+    entries: u32,
+    iter: impl Iterator<Item = (K, V)>,
+) -> CollectionReport {
+    let (sampled, bytes) =
+        iter.take(REPORT_SAMPLE_SIZE)
+            .fold((0, 0), |(sampled, bytes), (key, value)| {
+                let size =
+                    borsh::to_vec(&key).unwrap().len() + borsh::to_vec(&value).unwrap().len();
+                (sampled + 1, bytes + size as u64 + STORAGE_RECORD_OVERHEAD)
+            });
+
+    let entries = entries as u64;
+    CollectionReport {
+        name: name.to_string(),
+        entries,
+        bytes: if sampled == 0 {
+            0
+        } else {
+            bytes * entries / sampled
+        },
+    }
+}
@@ -0,0 +1,60 @@
+use near_sdk::near_bindgen;
+use shared::{Operation, MAX_BATCH_SIZE};
+
+use super::*;
+
+#[near_bindgen]
+impl Contract {
+    // Applies the operations atomically, so any failed operation reverts the whole batch
+    pub fn sloth_batch(&mut self, operations: Vec<Operation>) {
+        self.assert_sloth_method("sloth_batch");
+        if operations.len() > MAX_BATCH_SIZE {
+            ContractError::TooManyOperations.panic()
+        }
+
+        for (index, operation) in operations.into_iter().enumerate() {
+            events::log_event(Event::BatchOperation {
+                index: index as u32,
+            });
+            self.apply_operation(operation);
+        }
+    }
+}
+
+impl Contract {
+    fn apply_operation(&mut self, operation: Operation) {
+        match operation {
+            Operation::Include {
+                organization,
+                repo,
+                user,
+                pr_number,
+                started_at,
+                override_exclude,
+                github_id,
+                draft,
+                repo_id,
+            } => self.sloth_include(
+                organization,
+                repo,
+                user,
+                pr_number,
+                started_at,
+                override_exclude,
+                github_id,
+                draft,
+                repo_id,
+            ),
+            Operation::Scored {
+                pr_id,
+                user,
+                score,
+                reason,
+            } => self.sloth_scored(pr_id, user, score, reason),
+            Operation::Merged { pr_id, merged_at } => self.sloth_merged(pr_id, merged_at),
+            Operation::Stale { pr_id, closed } => self.sloth_stale(pr_id, closed),
+            Operation::Exclude { pr_id } => self.sloth_exclude(pr_id),
+            Operation::Finalize { pr_id, timestamp } => self.sloth_finalize(pr_id, timestamp),
+        }
+    }
+}
@@ -1,6 +1,33 @@
-use near_sdk::env;
+use near_sdk::{env, serde_json::json, AccountId};
 use shared::Event;
 
 pub fn log_event(event: Event) {
     env::log_str(&near_sdk::serde_json::to_string(&event).unwrap());
 }
+
+// NEP-297 event, so indexers and wallets can pick up the soulbound rank badges
+pub fn log_nft_mint(owner_id: AccountId, token_ids: Vec<String>) {
+    let event = json!({
+        "standard": "nep171",
+        "version": "1.2.0",
+        "event": "nft_mint",
+        "data": [{
+            "owner_id": owner_id,
+            "token_ids": token_ids,
+        }],
+    });
+    env::log_str(&format!("EVENT_JSON:{event}"));
+}
+
+pub fn log_nft_burn(owner_id: AccountId, token_ids: Vec<String>) {
+    let event = json!({
+        "standard": "nep171",
+        "version": "1.2.0",
+        "event": "nft_burn",
+        "data": [{
+            "owner_id": owner_id,
+            "token_ids": token_ids,
+        }],
+    });
+    env::log_str(&format!("EVENT_JSON:{event}"));
+}
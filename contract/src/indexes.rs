@@ -0,0 +1,205 @@
+use std::ops::Bound;
+
+use near_sdk::{near_bindgen, store::TreeMap};
+
+use super::*;
+
+// The PRs of the author and the repo indexes are ordered by the id,
+// so the pages are fetched by the key cursor and stay stable between the calls
+pub type PRIdTree = TreeMap<PRId, ()>;
+
+impl Contract {
+    pub fn index_pr(&mut self, user_id: UserId, pr_id: &PRId, pr: &PRWithRating) {
+        self.prs_by_author
+            .entry(user_id)
+            .or_insert_with(|| TreeMap::new(storage::StorageKey::PRsByAuthorEntry { user_id }))
+            .insert(pr_id.clone(), ());
+        let repo = (pr.organization.clone(), pr.repo.clone());
+        self.prs_by_repo
+            .entry(repo.clone())
+            .or_insert_with(|| TreeMap::new(repo_index_key(&repo)))
+            .insert(pr_id.clone(), ());
+
+        self.pr_index.insert(pr_id.clone());
+        if pr.merged_at.is_none() {
+            self.unmerged_index.insert(pr_id.clone());
+        }
+    }
+
+    pub fn unindex_pr(&mut self, user_id: UserId, pr_id: &PRId, pr: &PRWithRating) {
+        if let Some(prs) = self.prs_by_author.get_mut(&user_id) {
+            prs.remove(pr_id);
+            if prs.is_empty() {
+                self.prs_by_author.remove(&user_id);
+            }
+        }
+        let repo = (pr.organization.clone(), pr.repo.clone());
+        self.remove_from_pr_repo_index(&repo, pr_id);
+
+        self.pr_index.remove(pr_id);
+        self.unmerged_index.remove(pr_id);
+    }
+
+    // Excluded and closed PRs are indexed only by the repo, so they follow the repo moves
+    pub fn index_inactive_pr(&mut self, pr_id: &PRId, organization: &str, repo: &str) {
+        let repo = (organization.to_string(), repo.to_string());
+        self.inactive_prs_by_repo
+            .entry(repo.clone())
+            .or_insert_with(|| IterableSet::new(inactive_index_key(&repo)))
+            .insert(pr_id.clone());
+    }
+
+    pub fn remove_from_pr_repo_index(&mut self, repo: &(String, String), pr_id: &PRId) {
+        if let Some(prs) = self.prs_by_repo.get_mut(repo) {
+            prs.remove(pr_id);
+            if prs.is_empty() {
+                self.prs_by_repo.remove(repo);
+            }
+        }
+    }
+
+    pub fn unindex_inactive_pr(&mut self, pr_id: &PRId, organization: &str, repo: &str) {
+        let repo = (organization.to_string(), repo.to_string());
+        remove_from_repo_index(&mut self.inactive_prs_by_repo, &repo, pr_id);
+    }
+
+    // Indexes, counts and queues the PR stored before the indexes and the stats were introduced.
+    // Excluded PRs keep only their ids, so the stats don't count the ones excluded before.
+    // Called before any change of the PR, so the change isn't counted twice.
+    // Returns false if the PR is unknown or already indexed.
+    pub fn backfill_pr(&mut self, pr_id: &PRId) -> bool {
+        if self.pr_index.contains(pr_id) {
+            return false;
+        }
+
+        let (pr, executed): (PRWithRating, bool) = match self.prs.get(pr_id) {
+            Some(pr) => (pr.clone().into(), false),
+            None => match self.executed_prs.get(pr_id) {
+                Some(pr) => (pr.clone().into(), true),
+                None => return false,
+            },
+        };
+        let (user_id, _) = self.get_or_create_account(&pr.author);
+        self.index_pr(user_id, pr_id, &pr);
+
+        let rating = pr.rating();
+        self.record_stats(&pr.organization, |stats| {
+            stats.opened += 1;
+            if pr.merged_at.is_some() {
+                stats.merged += 1;
+            }
+            if executed {
+                stats.executed += 1;
+                stats.total_rating += rating as u64;
+            }
+        });
+        if pr.merged_at.is_some() && !executed {
+            self.enqueue_merged(pr_id.clone());
+        }
+
+        true
+    }
+
+    // The PRs stored before the indexes were introduced are indexed by `sloth_index_prs`,
+    // until then the views read the PRs from the storage
+    pub fn is_index_complete(&self) -> bool {
+        self.pr_index.len() as u64 == self.prs.len() as u64 + self.executed_prs.len() as u64
+    }
+
+    pub fn enqueue_merged(&mut self, pr_id: PRId) {
+        self.merge_queue.insert(self.merge_queue_tail, pr_id);
+        self.merge_queue_tail += 1;
+    }
+
+    // Drops the finalized PRs from the head of the queue.
+    // PRs finalized out of order stay in the queue until the head reaches them.
+    pub fn advance_merge_queue(&mut self) {
+        while self.merge_queue_head < self.merge_queue_tail {
+            let pending = match self.merge_queue.get(&self.merge_queue_head) {
+                Some(pr_id) => self.prs.contains_key(pr_id),
+                None => false,
+            };
+            if pending {
+                break;
+            }
+
+            self.merge_queue.remove(&self.merge_queue_head);
+            self.merge_queue_head += 1;
+        }
+    }
+
+    // Returns the indexed PRs following the `from_key` PR id
+    pub fn paginate_prs(
+        &self,
+        pr_ids: Option<&PRIdTree>,
+        from_key: Option<PRId>,
+        limit: u64,
+    ) -> Vec<(PRWithRating, bool)> {
+        let Some(pr_ids) = pr_ids else {
+            return vec![];
+        };
+        let start = match from_key {
+            Some(pr_id) => Bound::Excluded(pr_id),
+            None => Bound::Unbounded,
+        };
+
+        pr_ids
+            .range((start, Bound::Unbounded))
+            .take(limit as usize)
+            .filter_map(|(pr_id, _)| self.pr_with_status(pr_id))
+            .collect()
+    }
+
+    // Returns the stored PR with the scoring reasons and the execution status
+    pub fn pr_with_status(&self, pr_id: &PRId) -> Option<(PRWithRating, bool)> {
+        let (pr, executed): (PRWithRating, bool) = match self.prs.get(pr_id) {
+            Some(pr) => (pr.clone().into(), false),
+            None => (self.executed_prs.get(pr_id)?.clone().into(), true),
+        };
+        Some((self.with_score_reasons(pr), executed))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Builds the indexes, the stats and the merge queue for the PRs stored before they were introduced.
+    // The merged PRs are queued in the given order, so the caller sorts them by the merge time.
+    // Returns the amount of the indexed PRs, already indexed or unknown PRs are skipped.
+    pub fn sloth_index_prs(&mut self, pr_ids: Vec<PRId>) -> u32 {
+        self.assert_sloth_method("sloth_index_prs");
+
+        let mut indexed = 0;
+        for pr_id in pr_ids {
+            if self.backfill_pr(&pr_id) {
+                indexed += 1;
+            }
+        }
+
+        indexed
+    }
+}
+
+pub fn remove_from_repo_index(
+    index: &mut LookupMap<(String, String), IterableSet<PRId>>,
+    repo: &(String, String),
+    pr_id: &PRId,
+) {
+    if let Some(prs) = index.get_mut(repo) {
+        prs.remove(pr_id);
+        if prs.is_empty() {
+            index.remove(repo);
+        }
+    }
+}
+
+fn repo_index_key((organization, repo): &(String, String)) -> storage::StorageKey {
+    storage::StorageKey::PRsByRepoEntry {
+        repo_hash: env::sha256_array(format!("{organization}/{repo}").as_bytes()),
+    }
+}
+
+fn inactive_index_key((organization, repo): &(String, String)) -> storage::StorageKey {
+    storage::StorageKey::InactivePRsByRepoEntry {
+        repo_hash: env::sha256_array(format!("{organization}/{repo}").as_bytes()),
+    }
+}
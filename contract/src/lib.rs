@@ -1,29 +1,47 @@
+use std::collections::HashSet;
+
+use indexes::PRIdTree;
 #[allow(deprecated)]
-use near_sdk::store::UnorderedMap;
+use near_sdk::store::{UnorderedMap, UnorderedSet};
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
-    require,
-    store::{LookupMap, LookupSet, Vector},
+    store::{IterableMap, IterableSet, LookupMap, LookupSet, Vector},
     Timestamp,
 };
-use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault};
+use near_sdk::{env, near_bindgen, AccountId, CryptoHash, PanicOnDefault};
 use shared::{
-    AccountWithPermanentPercentageBonus, AllowedRepos, Event, GithubHandle, IntoEnumIterator, PRId,
-    PRWithRating, Streak, StreakId, StreakReward, StreakType, StreakUserData, TimePeriod,
-    TimePeriodString, UserId, UserPeriodData, VersionedAccount, VersionedPR, VersionedStreak,
-    VersionedStreakUserData, VersionedUserPeriodData,
+    AccountWithPermanentPercentageBonus, Achievement, AllowedRepos, ContractError, Event,
+    GithubHandle, IntoEnumIterator, PRId, PRStats, PRWithRating, Referral, Streak, StreakId,
+    StreakReward, StreakType, StreakUserData, TeamName, TimePeriod, TimePeriodString, UserId,
+    UserPeriodData, VersionedAccount, VersionedPR, VersionedRankBadge, VersionedReferral,
+    VersionedStreak, VersionedStreakUserData, VersionedTeam, VersionedUserAchievements,
+    VersionedUserPeriodData, MAX_SCORE_TIMEOUT_IN_NANOSECONDS, MAX_SNOOZE_IN_NANOSECONDS,
+    MIN_SCORE_TIMEOUT_IN_NANOSECONDS, SCORE_TIMEOUT_IN_NANOSECONDS,
 };
-use types::{Organization, VersionedOrganization};
+use types::{ClosedPR, Organization, VersionedOrganization};
 
+pub mod accounts;
+pub mod achievements;
+pub mod archive;
+pub mod batch;
 pub mod events;
+pub mod indexes;
 pub mod migrate;
 pub mod mock;
+pub mod nft;
+pub mod pause;
+pub mod reasons;
+pub mod repos;
+pub mod stats;
 pub mod storage;
+pub mod teams;
 #[cfg(test)]
 mod tests;
 pub mod types;
 pub mod views;
 
+pub const DEFAULT_BADGE_URL: &str = "https://badge.race-of-sloths.com";
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -34,9 +52,9 @@ pub struct Contract {
     sloths_per_period: LookupMap<(UserId, TimePeriodString), VersionedUserPeriodData>,
     #[allow(deprecated)]
     organizations: UnorderedMap<GithubHandle, VersionedOrganization>,
-    // We need to think about removing PRs that are stale for a long time
     #[allow(deprecated)]
     prs: UnorderedMap<PRId, VersionedPR>,
+    // Old executed PRs are compacted into the `pr_archive` with `sloth_prune`
     #[allow(deprecated)]
     executed_prs: UnorderedMap<PRId, VersionedPR>,
     excluded_prs: LookupSet<PRId>,
@@ -44,6 +62,82 @@ pub struct Contract {
     // Configured streaks
     streaks: Vector<VersionedStreak>,
     user_streaks: LookupMap<(UserId, StreakId), VersionedStreakUserData>,
+
+    // Teams of sloths competing together
+    #[allow(deprecated)]
+    teams: UnorderedMap<TeamName, VersionedTeam>,
+    user_teams: LookupMap<UserId, TeamName>,
+    // Join requests waiting for the maintainer approval
+    team_requests: LookupMap<UserId, TeamName>,
+
+    // Referee -> referrer that onboarded them
+    referrals: LookupMap<UserId, VersionedReferral>,
+    // Reverse index of the referrals, so the referees follow the merged referrer accounts
+    referees: LookupMap<UserId, IterableSet<UserId>>,
+
+    user_achievements: LookupMap<UserId, VersionedUserAchievements>,
+    // Achievements unlocked by the executed PR, so they can be taken back on revert
+    pr_achievements: LookupMap<PRId, Vec<Achievement>>,
+
+    // Soulbound NEP-171 tokens minted for the rank promotions
+    #[allow(deprecated)]
+    rank_badges: UnorderedMap<nft::TokenId, VersionedRankBadge>,
+    rank_badges_per_owner: LookupMap<AccountId, Vec<nft::TokenId>>,
+    account_link_requests: LookupMap<AccountId, nft::AccountLinkRequest>,
+    badge_url: String,
+
+    // Secondary indexes over both active and executed PRs
+    prs_by_author: LookupMap<UserId, PRIdTree>,
+    prs_by_repo: LookupMap<(String, String), PRIdTree>,
+    // Excluded and closed PRs, so they follow the repo moves
+    inactive_prs_by_repo: LookupMap<(String, String), IterableSet<PRId>>,
+    pr_index: IterableSet<PRId>,
+    unmerged_index: IterableSet<PRId>,
+    // Merged PRs waiting for the finalization in the merge order
+    merge_queue: LookupMap<u64, PRId>,
+    merge_queue_head: u64,
+    merge_queue_tail: u64,
+
+    pr_archive: archive::PRArchive,
+
+    // Immutable GitHub ids, so the accounts follow the login renames
+    github_ids: LookupMap<u64, UserId>,
+    user_github_ids: LookupMap<UserId, u64>,
+
+    #[allow(deprecated)]
+    opted_out: UnorderedSet<UserId>,
+    // Merged accounts stay in `users`, so they are counted to report only the active users
+    merged_accounts: u32,
+
+    owner: AccountId,
+    paused: bool,
+    paused_methods: HashSet<String>,
+
+    // Scoring window per organization, the default one is used if not set
+    score_timeouts: LookupMap<String, Timestamp>,
+
+    stats: PRStats,
+    organization_stats: LookupMap<String, PRStats>,
+
+    // sha256 of the reviewer's reason for the score, the text is kept off-chain
+    score_reasons: LookupMap<(PRId, GithubHandle), CryptoHash>,
+
+    // Unmerged PRs that shouldn't be marked as stale until the timestamp
+    snoozed_prs: LookupMap<PRId, Timestamp>,
+
+    // Draft PRs that don't count towards the stats and streaks until they are ready for review
+    pending_prs: LookupSet<PRId>,
+    // PRs converted back to draft after they were counted, so they aren't counted twice
+    drafted_prs: LookupSet<PRId>,
+
+    // Closed PRs are kept with their votes, so they can be restored on reopen
+    closed_prs: LookupMap<PRId, ClosedPR>,
+    // Reverted PRs with the revert timestamp, so the off-chain copies drop them
+    reverted_prs: IterableMap<PRId, Timestamp>,
+
+    // Immutable GitHub repo ids, so the PRs follow the repo renames and transfers
+    #[allow(deprecated)]
+    repo_ids: UnorderedMap<u64, (String, String)>,
 }
 
 #[near_bindgen]
@@ -64,6 +158,47 @@ impl Contract {
             excluded_prs: LookupSet::new(storage::StorageKey::ExcludedPRs),
             streaks: Vector::new(storage::StorageKey::Streaks),
             user_streaks: LookupMap::new(storage::StorageKey::UserStreaks),
+            #[allow(deprecated)]
+            teams: UnorderedMap::new(storage::StorageKey::Teams),
+            user_teams: LookupMap::new(storage::StorageKey::UserTeams),
+            team_requests: LookupMap::new(storage::StorageKey::TeamRequests),
+            referrals: LookupMap::new(storage::StorageKey::Referrals),
+            referees: LookupMap::new(storage::StorageKey::Referees),
+            user_achievements: LookupMap::new(storage::StorageKey::UserAchievements),
+            pr_achievements: LookupMap::new(storage::StorageKey::PRAchievements),
+            #[allow(deprecated)]
+            rank_badges: UnorderedMap::new(storage::StorageKey::RankBadges),
+            rank_badges_per_owner: LookupMap::new(storage::StorageKey::RankBadgesPerOwner),
+            account_link_requests: LookupMap::new(storage::StorageKey::AccountLinkRequests),
+            badge_url: DEFAULT_BADGE_URL.to_string(),
+            prs_by_author: LookupMap::new(storage::StorageKey::PRsByAuthor),
+            prs_by_repo: LookupMap::new(storage::StorageKey::PRsByRepo),
+            inactive_prs_by_repo: LookupMap::new(storage::StorageKey::InactivePRsByRepo),
+            pr_index: IterableSet::new(storage::StorageKey::PRIndex),
+            unmerged_index: IterableSet::new(storage::StorageKey::UnmergedIndex),
+            merge_queue: LookupMap::new(storage::StorageKey::MergeQueue),
+            merge_queue_head: 0,
+            merge_queue_tail: 0,
+            pr_archive: Default::default(),
+            github_ids: LookupMap::new(storage::StorageKey::GithubIds),
+            user_github_ids: LookupMap::new(storage::StorageKey::UserGithubIds),
+            #[allow(deprecated)]
+            opted_out: UnorderedSet::new(storage::StorageKey::OptedOut),
+            merged_accounts: 0,
+            owner: env::predecessor_account_id(),
+            paused: false,
+            paused_methods: HashSet::new(),
+            score_timeouts: LookupMap::new(storage::StorageKey::ScoreTimeouts),
+            stats: Default::default(),
+            organization_stats: LookupMap::new(storage::StorageKey::OrganizationStats),
+            score_reasons: LookupMap::new(storage::StorageKey::ScoreReasons),
+            snoozed_prs: LookupMap::new(storage::StorageKey::SnoozedPRs),
+            pending_prs: LookupSet::new(storage::StorageKey::PendingPRs),
+            drafted_prs: LookupSet::new(storage::StorageKey::DraftedPRs),
+            closed_prs: LookupMap::new(storage::StorageKey::ClosedPRs),
+            reverted_prs: IterableMap::new(storage::StorageKey::RevertedPRs),
+            #[allow(deprecated)]
+            repo_ids: UnorderedMap::new(storage::StorageKey::RepoIds),
         };
 
         for org in allowed_repos {
@@ -141,7 +276,7 @@ impl Contract {
 
         let streak = self.streaks.get_mut(id);
         if streak.is_none() {
-            env::panic_str("Streak doesn't exist")
+            ContractError::StreakNotFound.panic()
         }
 
         let streak = streak.unwrap();
@@ -161,82 +296,138 @@ impl Contract {
         pr_number: u64,
         started_at: Timestamp,
         override_exclude: bool,
+        github_id: Option<u64>,
+        draft: Option<bool>,
+        repo_id: Option<u64>,
     ) {
-        self.assert_sloth();
+        self.assert_sloth_method("sloth_include");
+        // The repo could be renamed since the last include, so its PRs are moved first
+        if let Some(repo_id) = repo_id {
+            self.track_repo_id(&organization, &repo, repo_id);
+        }
         self.assert_organization_allowed(&organization, &repo);
+        if let Some(github_id) = github_id {
+            self.track_github_id(&user, github_id);
+        }
         let (user_id, _) = self.get_or_create_account(&user);
+        if self.opted_out.contains(&user_id) {
+            ContractError::UserOptedOut.panic()
+        }
 
         let pr_id = format!("{organization}/{repo}/{pr_number}");
 
         if self.excluded_prs.contains(&pr_id) {
             if !override_exclude {
-                env::panic_str("Excluded PR cannot be included without override flag")
+                ContractError::PrExcluded.panic()
             }
             self.excluded_prs.remove(&pr_id);
+            self.reverted_prs.remove(&pr_id);
+        }
+
+        // Including the closed PR again starts it from scratch
+        if let Some(closed) = self.closed_prs.remove(&pr_id) {
+            self.forget_score_reasons(&pr_id, &closed.pr.into());
         }
+        self.unindex_inactive_pr(&pr_id, &organization, &repo);
 
         // Check if PR already exists
         let pr = self.prs.get(&pr_id).or(self.executed_prs.get(&pr_id));
         if pr.is_some() {
-            env::panic_str("PR already exists: {pr_id}")
+            ContractError::PrAlreadyExists.panic_with(format!("PR already exists: {pr_id}"))
         }
 
         let pr = PRWithRating::new(organization, repo, pr_number, user, started_at);
 
-        self.apply_to_periods(started_at, user_id, |data| data.pr_opened());
+        if draft.unwrap_or_default() {
+            self.pending_prs.insert(pr_id.clone());
+        } else {
+            self.apply_to_periods(started_at, user_id, |data| data.pr_opened());
+            self.record_stats(&pr.organization, |stats| stats.opened += 1);
+        }
+        self.index_pr(user_id, &pr_id, &pr);
         self.prs.insert(pr_id, VersionedPR::V1(pr));
     }
 
-    pub fn sloth_scored(&mut self, pr_id: String, user: String, score: u32) {
-        self.assert_sloth();
+    pub fn sloth_scored(
+        &mut self,
+        pr_id: String,
+        user: String,
+        score: u32,
+        reason: Option<String>,
+    ) {
+        self.assert_sloth_method("sloth_scored");
 
         let mut pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
             Some(x) => x.into(),
-            None => env::panic_str("PR is not started or already executed"),
+            None => ContractError::PrNotFound.panic(),
         };
 
+        self.set_score_reason(&pr_id, &user, reason);
         pr.add_score(user, score);
         self.prs.insert(pr_id.clone(), VersionedPR::V1(pr));
     }
 
+    pub fn sloth_unscored(&mut self, pr_id: String, user: String) {
+        self.assert_sloth_method("sloth_unscored");
+
+        let mut pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
+            Some(x) => x.into(),
+            None => ContractError::PrNotFound.panic(),
+        };
+
+        self.set_score_reason(&pr_id, &user, None);
+        pr.remove_score(&user);
+        self.prs.insert(pr_id, VersionedPR::V1(pr));
+    }
+
     pub fn sloth_merged(&mut self, pr_id: String, merged_at: Timestamp) {
-        self.assert_sloth();
+        self.assert_sloth_method("sloth_merged");
+        self.backfill_pr(&pr_id);
 
         let mut pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
             Some(pr) => pr.into(),
-            None => env::panic_str("PR is not started or already executed"),
+            None => ContractError::PrNotFound.panic(),
         };
+        let already_merged = pr.merged_at.is_some();
         pr.add_merge_info(merged_at);
         let (user_id, _) = self.get_or_create_account(&pr.author);
 
+        if self.pending_prs.remove(&pr_id) {
+            self.apply_to_periods(pr.created_at, user_id, |data| data.pr_opened());
+            self.record_stats(&pr.organization, |stats| stats.opened += 1);
+        }
+
         self.apply_to_periods(merged_at, user_id, |data| data.pr_merged());
+        self.unmerged_index.remove(&pr_id);
+        if !already_merged {
+            self.record_stats(&pr.organization, |stats| stats.merged += 1);
+            self.enqueue_merged(pr_id.clone());
+        }
+        self.snoozed_prs.remove(&pr_id);
+        self.drafted_prs.remove(&pr_id);
         self.prs.insert(pr_id, VersionedPR::V1(pr));
     }
 
     pub fn sloth_exclude(&mut self, pr_id: String) {
-        self.assert_sloth();
+        self.assert_sloth_method("sloth_exclude");
+        self.backfill_pr(&pr_id);
         let pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
             Some(pr) => pr.into(),
-            None => env::panic_str("PR is not started or already executed"),
+            None => ContractError::PrNotFound.panic(),
         };
         if pr.merged_at.is_some() {
-            env::panic_str("Merged PR cannot be excluded")
+            ContractError::MergedPrCannotBeExcluded.panic()
         }
         let (user_id, _) = self.get_or_create_account(&pr.author);
 
-        self.apply_to_periods(pr.created_at, user_id, |data| {
-            data.pr_closed();
-        });
-
-        self.prs.remove(&pr_id);
-        self.excluded_prs.insert(pr_id);
+        self.exclude_pr(user_id, pr_id, pr);
     }
 
     pub fn allow_organization(&mut self, organization: String) {
         self.assert_sloth();
 
         if self.organizations.get(&organization).is_some() {
-            env::panic_str("Organization already allowlisted")
+            ContractError::OrganizationAlreadyAllowed.panic()
         }
 
         let org = Organization::new_all(organization);
@@ -249,7 +440,7 @@ impl Contract {
 
         let org = match self.organizations.get_mut(&organization) {
             Some(VersionedOrganization::V1(org)) => org,
-            _ => env::panic_str("Organization is not in the list"),
+            _ => ContractError::OrganizationNotFound.panic(),
         };
 
         org.exclude(&repo);
@@ -270,34 +461,255 @@ impl Contract {
         }
     }
 
-    pub fn sloth_stale(&mut self, pr_id: String) {
+    // Sets the scoring window of the organization, `None` resets it to the default one
+    pub fn set_score_timeout(&mut self, organization: String, score_timeout: Option<Timestamp>) {
         self.assert_sloth();
 
+        if self.organizations.get(&organization).is_none() {
+            ContractError::OrganizationNotFound.panic()
+        }
+
+        match score_timeout {
+            Some(score_timeout) => {
+                if !(MIN_SCORE_TIMEOUT_IN_NANOSECONDS..=MAX_SCORE_TIMEOUT_IN_NANOSECONDS)
+                    .contains(&score_timeout)
+                {
+                    ContractError::ScoreTimeoutOutOfRange.panic()
+                }
+                self.score_timeouts.insert(organization, score_timeout);
+            }
+            None => {
+                self.score_timeouts.remove(&organization);
+            }
+        }
+    }
+
+    pub fn sloth_stale(&mut self, pr_id: String, closed: Option<bool>) {
+        self.assert_sloth_method("sloth_stale");
+        self.backfill_pr(&pr_id);
+
         let pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
             Some(pr) => pr.into(),
-            None => env::panic_str("PR is not started or already executed"),
+            None => ContractError::PrNotFound.panic(),
+        };
+        if pr.merged_at.is_some() {
+            ContractError::MergedPrCannotBeStale.panic()
+        }
+        let (user_id, _) = self.get_or_create_account(&pr.author);
+        let pending = self.pending_prs.remove(&pr_id);
+        if !pending {
+            self.apply_to_periods(pr.created_at, user_id, |data| data.pr_closed());
+        }
+        self.unindex_pr(user_id, &pr_id, &pr);
+        self.snoozed_prs.remove(&pr_id);
+        self.drafted_prs.remove(&pr_id);
+        self.prs.remove(&pr_id);
+        if closed.unwrap_or_default() {
+            self.index_inactive_pr(&pr_id, &pr.organization, &pr.repo);
+            let closed = ClosedPR {
+                pr: VersionedPR::V1(pr),
+                pending,
+                closed_at: env::block_timestamp(),
+            };
+            self.closed_prs.insert(pr_id, closed);
+        } else {
+            self.forget_score_reasons(&pr_id, &pr);
+        }
+    }
+
+    // Restores the closed PR with its votes, the draft PR stays uncounted until it's ready
+    pub fn sloth_reopen(&mut self, pr_id: String) {
+        self.assert_sloth_method("sloth_reopen");
+
+        let closed = match self.closed_prs.get(&pr_id).cloned() {
+            Some(closed) => closed,
+            None => ContractError::PrNotFound.panic(),
+        };
+        let pr: PRWithRating = closed.pr.into();
+        self.assert_organization_allowed(&pr.organization, &pr.repo);
+        let (user_id, _) = self.get_or_create_account(&pr.author);
+        if self.opted_out.contains(&user_id) {
+            ContractError::UserOptedOut.panic()
+        }
+
+        if closed.pending {
+            self.pending_prs.insert(pr_id.clone());
+        } else {
+            self.apply_to_periods(pr.created_at, user_id, |data| data.pr_opened());
+        }
+        self.index_pr(user_id, &pr_id, &pr);
+        self.unindex_inactive_pr(&pr_id, &pr.organization, &pr.repo);
+        self.closed_prs.remove(&pr_id);
+        self.prs.insert(pr_id, VersionedPR::V1(pr));
+    }
+
+    // Takes back the merge and the rating of the reverted PR, so it can't be included again
+    pub fn sloth_revert(&mut self, pr_id: String) {
+        self.assert_sloth_method("sloth_revert");
+        self.backfill_pr(&pr_id);
+
+        let (pr, executed): (PRWithRating, bool) = match self.executed_prs.get(&pr_id) {
+            Some(pr) => (pr.clone().into(), true),
+            None => match self.prs.get(&pr_id).cloned() {
+                Some(pr) => (pr.into(), false),
+                None => ContractError::PrNotFound.panic(),
+            },
+        };
+        let Some(merged_at) = pr.merged_at else {
+            ContractError::PrNotMerged.panic()
+        };
+
+        let (score, rating) = if executed {
+            (pr.score().unwrap_or_default(), pr.rating())
+        } else {
+            (0, 0)
         };
-        require!(pr.merged_at.is_none(), "Merged PR cannot be stale");
         let (user_id, _) = self.get_or_create_account(&pr.author);
-        self.apply_to_periods(pr.created_at, user_id, |data| data.pr_closed());
+        // The streaks are taken back separately, as recalculating them at the old periods resets them
+        self.update_periods(pr.created_at, user_id, |data| data.pr_closed());
+        self.update_periods(merged_at, user_id, |data| {
+            data.pr_reverted(executed, score, rating)
+        });
+        self.revert_streaks(user_id, pr.created_at);
+        self.revert_streaks(user_id, merged_at);
+        self.record_stats(&pr.organization, |stats| {
+            stats.merged = stats.merged.saturating_sub(1);
+            stats.excluded += 1;
+            if executed {
+                stats.executed = stats.executed.saturating_sub(1);
+                stats.total_rating = stats.total_rating.saturating_sub(rating as u64);
+            }
+        });
+
+        if executed {
+            self.revert_referral(user_id, &pr_id, &pr.organization);
+            self.revert_achievements(user_id, &pr);
+        }
+
+        self.unindex_pr(user_id, &pr_id, &pr);
+        self.forget_score_reasons(&pr_id, &pr);
         self.prs.remove(&pr_id);
+        self.executed_prs.remove(&pr_id);
+        self.reverted_prs
+            .insert(pr_id.clone(), env::block_timestamp());
+        self.index_inactive_pr(&pr_id, &pr.organization, &pr.repo);
+        self.excluded_prs.insert(pr_id);
+        self.advance_merge_queue();
+
+        events::log_event(Event::PRReverted { pr, rating });
+    }
+
+    // Keeps the unmerged PR from being marked as stale until the timestamp
+    pub fn sloth_snooze(&mut self, pr_id: String, until: Timestamp) {
+        self.assert_sloth_method("sloth_snooze");
+
+        let pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
+            Some(pr) => pr.into(),
+            None => ContractError::PrNotFound.panic(),
+        };
+        if pr.merged_at.is_some() {
+            ContractError::MergedPrCannotBeStale.panic()
+        }
+        if until > env::block_timestamp() + MAX_SNOOZE_IN_NANOSECONDS {
+            ContractError::SnoozeTooLong.panic()
+        }
+        self.snoozed_prs.insert(pr_id, until);
+    }
+
+    // The race starts when the draft PR is ready for review for the first time.
+    // The PR is counted in the period it was opened in, so toggling the draft doesn't move it
+    pub fn sloth_ready(&mut self, pr_id: String) {
+        self.assert_sloth_method("sloth_ready");
+
+        let pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
+            Some(pr) => pr.into(),
+            None => ContractError::PrNotFound.panic(),
+        };
+        if self.drafted_prs.remove(&pr_id) || !self.pending_prs.remove(&pr_id) {
+            return;
+        }
+
+        let (user_id, _) = self.get_or_create_account(&pr.author);
+        self.apply_to_periods(pr.created_at, user_id, |data| data.pr_opened());
+        self.record_stats(&pr.organization, |stats| stats.opened += 1);
+    }
+
+    pub fn sloth_draft(&mut self, pr_id: String) {
+        self.assert_sloth_method("sloth_draft");
+
+        let pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
+            Some(pr) => pr.into(),
+            None => ContractError::PrNotFound.panic(),
+        };
+        if pr.merged_at.is_some() || self.pending_prs.contains(&pr_id) {
+            return;
+        }
+
+        // The PR stays counted, it's taken back only if it's closed or excluded
+        self.drafted_prs.insert(pr_id);
+    }
+
+    // Referrer can be declared only on the first PR of the user
+    pub fn sloth_referred_by(&mut self, user: GithubHandle, referrer: GithubHandle) {
+        self.assert_sloth_method("sloth_referred_by");
+
+        if user.eq_ignore_ascii_case(&referrer) {
+            ContractError::SelfReferral.panic()
+        }
+
+        let all_time = TimePeriod::AllTime.time_string(env::block_timestamp());
+        let referrer_id = match self.account_ids.get(&referrer) {
+            Some(referrer_id) => *referrer_id,
+            None => ContractError::ReferrerWithoutExecutedPrs.panic(),
+        };
+        let referrer_data = self.period_data(referrer_id, &all_time).unwrap_or_default();
+        if referrer_data.executed_prs == 0 {
+            ContractError::ReferrerWithoutExecutedPrs.panic()
+        }
+
+        let (user_id, _) = self.get_or_create_account(&user);
+        if self.referrals.contains_key(&user_id) {
+            ContractError::ReferrerAlreadySet.panic()
+        }
+
+        let user_data = self.period_data(user_id, &all_time).unwrap_or_default();
+        if user_data.executed_prs > 0 || user_data.prs_opened > 1 {
+            ContractError::ReferralNotOnFirstPr.panic()
+        }
+
+        self.referrals
+            .insert(user_id, VersionedReferral::V1(Referral::new(referrer_id)));
+        self.referees
+            .entry(referrer_id)
+            .or_insert_with(|| {
+                IterableSet::new(storage::StorageKey::RefereesEntry {
+                    user_id: referrer_id,
+                })
+            })
+            .insert(user_id);
     }
 
     pub fn sloth_finalize(&mut self, pr_id: String, timestamp: Option<Timestamp>) {
-        self.assert_sloth();
+        self.assert_sloth_method("sloth_finalize");
+        self.backfill_pr(&pr_id);
 
         let timestamp = timestamp.unwrap_or_else(env::block_timestamp);
 
         let mut pr: PRWithRating = match self.prs.get(&pr_id).cloned() {
             Some(pr) => pr.into(),
-            None => env::panic_str("PR is not started or already executed"),
+            None => ContractError::PrNotFound.panic(),
         };
 
-        if !pr.is_ready_to_move(timestamp) {
-            env::panic_str("PR is not ready to be finalized")
+        if !pr.is_ready_to_move(timestamp, self.score_timeout(pr.organization.clone())) {
+            ContractError::NotReadyToFinalize.panic()
         }
 
         let (user_id, _) = self.get_or_create_account(&pr.author);
+        let first_executed_pr = self
+            .period_data(user_id, &TimePeriod::AllTime.time_string(timestamp))
+            .unwrap_or_default()
+            .executed_prs
+            == 0;
 
         let score = pr.score().unwrap_or_default();
         self.apply_to_periods(pr.merged_at.unwrap(), user_id, |data| {
@@ -353,12 +765,25 @@ impl Contract {
         });
 
         self.users[user_id] = VersionedAccount::V1(user);
+        if new_bonus > 0 {
+            self.mint_rank_badges(user_id);
+        }
         self.apply_to_periods(pr.merged_at.unwrap(), user_id, |data| {
             data.pr_final_rating(rating)
         });
+        self.record_stats(&pr.organization, |stats| {
+            stats.executed += 1;
+            stats.total_rating += rating as u64;
+        });
+
+        if first_executed_pr {
+            self.reward_referrer(user_id, &full_id, &pr.organization, timestamp);
+        }
+        self.unlock_achievements(user_id, &pr, score, timestamp);
 
         self.prs.remove(&full_id);
         self.executed_prs.insert(full_id, VersionedPR::V1(pr));
+        self.advance_merge_queue();
     }
 }
 
@@ -425,6 +850,73 @@ impl Contract {
         }
     }
 
+    // Breaks the streaks at the period that is no longer achieved after the revert.
+    // Only the periods of the current streak are checked, the older ones are already lost
+    pub fn revert_streaks(&mut self, user_id: UserId, timestamp: Timestamp) {
+        let now = env::block_timestamp();
+        for streak in self.streaks.into_iter().cloned().collect::<Vec<_>>() {
+            let streak: Streak = streak.into();
+            if !streak.is_active {
+                continue;
+            }
+
+            let reverted_time_string = streak.time_period.time_string(timestamp);
+            let achieved = self
+                .sloths_per_period
+                .get(&(user_id, reverted_time_string.clone()))
+                .map(|s| streak.is_streak_achieved(s))
+                .unwrap_or_default();
+            let key = (user_id, streak.id);
+            let mut streak_data: StreakUserData = match self.user_streaks.get(&key) {
+                Some(streak_data) if !achieved => streak_data.clone().into(),
+                _ => continue,
+            };
+
+            let current_time_string = streak.time_period.time_string(now);
+            let prev_time = streak.time_period.previous_period(now);
+            let mut time = if streak_data.latest_time_string == current_time_string {
+                Some(now)
+            } else {
+                prev_time
+            };
+            let mut lost_at = None;
+            for k in 0..streak_data.amount {
+                let Some(t) = time else {
+                    break;
+                };
+                let time_string = streak.time_period.time_string(t);
+                if time_string != streak_data.latest_time_string && k == 0 {
+                    // The streak is already lost
+                    break;
+                }
+                if time_string == reverted_time_string {
+                    lost_at = Some(k);
+                    break;
+                }
+                time = streak.time_period.previous_period(t);
+            }
+
+            match lost_at {
+                // The current period is still in progress, so the older streak is kept
+                Some(0) if reverted_time_string == current_time_string => {
+                    streak_data.amount -= 1;
+                    streak_data.latest_time_string = prev_time
+                        .map(|t| streak.time_period.time_string(t))
+                        .unwrap_or_default();
+                }
+                Some(amount) => streak_data.amount = amount,
+                None => continue,
+            }
+
+            let mut account: AccountWithPermanentPercentageBonus =
+                self.users[user_id].clone().into();
+            account.revoke_flat_bonus(streak.id, streak_data.amount);
+            self.users[user_id] = VersionedAccount::V1(account);
+            self.user_streaks
+                .insert(key, VersionedStreakUserData::V1(streak_data));
+        }
+    }
+
     pub fn reward_streak(&mut self, user_id: UserId, streak: &Streak, achieved: u32) -> bool {
         let reward = match streak.get_streak_reward(achieved) {
             Some(reward) => reward,
@@ -442,11 +934,91 @@ impl Contract {
         result
     }
 
+    pub fn exclude_pr(&mut self, user_id: UserId, pr_id: PRId, pr: PRWithRating) {
+        if !self.pending_prs.remove(&pr_id) {
+            self.apply_to_periods(pr.created_at, user_id, |data| {
+                data.pr_closed();
+            });
+        }
+
+        self.record_stats(&pr.organization, |stats| stats.excluded += 1);
+        self.unindex_pr(user_id, &pr_id, &pr);
+        self.forget_score_reasons(&pr_id, &pr);
+        self.snoozed_prs.remove(&pr_id);
+        self.drafted_prs.remove(&pr_id);
+        self.prs.remove(&pr_id);
+        self.index_inactive_pr(&pr_id, &pr.organization, &pr.repo);
+        self.excluded_prs.insert(pr_id);
+    }
+
+    pub fn reward_referrer(
+        &mut self,
+        user_id: UserId,
+        pr_id: &PRId,
+        organization: &str,
+        timestamp: Timestamp,
+    ) {
+        let mut referral: Referral = match self.referrals.get(&user_id) {
+            Some(referral) => referral.clone().into(),
+            None => return,
+        };
+
+        let reward = referral.use_reward(pr_id.clone(), timestamp);
+        if reward == 0 {
+            return;
+        }
+
+        let referrer_id = referral.referrer;
+        self.referrals
+            .insert(user_id, VersionedReferral::V1(referral));
+        self.apply_to_periods(timestamp, referrer_id, |data| data.referral_bonus(reward));
+        self.record_stats(organization, |stats| stats.total_rating += reward as u64);
+
+        let referrer: AccountWithPermanentPercentageBonus = self.users[referrer_id].clone().into();
+        events::log_event(Event::ReferralRewarded {
+            referrer: referrer.github_handle,
+            bonus_rating: reward,
+        });
+    }
+
+    // Takes back the referral bonus earned by the reverted PR
+    pub fn revert_referral(&mut self, user_id: UserId, pr_id: &PRId, organization: &str) {
+        let mut referral: Referral = match self.referrals.get(&user_id) {
+            Some(referral) => referral.clone().into(),
+            None => return,
+        };
+
+        let Some(bonus) = referral.revoke_reward(pr_id) else {
+            return;
+        };
+
+        let referrer_id = referral.referrer;
+        self.referrals
+            .insert(user_id, VersionedReferral::V1(referral));
+        self.update_periods(bonus.rewarded_at, referrer_id, |data| {
+            data.referral_bonus_revoked(bonus.rating)
+        });
+        self.record_stats(organization, |stats| {
+            stats.total_rating = stats.total_rating.saturating_sub(bonus.rating as u64)
+        });
+    }
+
     pub fn apply_to_periods(
         &mut self,
         timestamp: Timestamp,
         user_id: UserId,
         func: impl Fn(&mut VersionedUserPeriodData),
+    ) {
+        self.update_periods(timestamp, user_id, func);
+        self.calculate_streak(user_id, timestamp);
+    }
+
+    // Same as `apply_to_periods`, but keeps the streaks as they are
+    pub fn update_periods(
+        &mut self,
+        timestamp: Timestamp,
+        user_id: UserId,
+        func: impl Fn(&mut VersionedUserPeriodData),
     ) {
         for period in TimePeriod::iter() {
             if period == TimePeriod::Day {
@@ -460,8 +1032,6 @@ impl Contract {
                 .or_insert(VersionedUserPeriodData::V1(Default::default()));
             func(entry);
         }
-
-        self.calculate_streak(user_id, timestamp);
     }
 
     pub fn get_or_create_account(
@@ -482,13 +1052,12 @@ impl Contract {
                 ));
                 user_id
             });
-        dbg!(user_id, self.users.len());
         (user_id, self.users[user_id].clone().into())
     }
 
     pub fn assert_sloth(&self) {
         if env::predecessor_account_id() != self.sloth {
-            env::panic_str("Only sloth can call this method")
+            ContractError::NotSloth.panic()
         }
     }
 
@@ -496,10 +1065,10 @@ impl Contract {
         let org = self.organizations.get(organization);
         if let Some(org) = org {
             if !org.is_allowed(repo) {
-                env::panic_str("The repository is not allowlisted for the organization")
+                ContractError::RepoPaused.panic()
             }
         } else {
-            env::panic_str("Organization is not allowlisted")
+            ContractError::OrganizationNotAllowed.panic()
         }
     }
 }
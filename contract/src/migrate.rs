@@ -2,5 +2,85 @@ use near_sdk::near;
 
 use super::*;
 
+// Layout of the currently deployed contract
+#[derive(BorshDeserialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldState {
+    sloth: AccountId,
+    account_ids: LookupMap<GithubHandle, UserId>,
+    users: Vector<VersionedAccount>,
+    sloths_per_period: LookupMap<(UserId, TimePeriodString), VersionedUserPeriodData>,
+    #[allow(deprecated)]
+    organizations: UnorderedMap<GithubHandle, VersionedOrganization>,
+    #[allow(deprecated)]
+    prs: UnorderedMap<PRId, VersionedPR>,
+    #[allow(deprecated)]
+    executed_prs: UnorderedMap<PRId, VersionedPR>,
+    excluded_prs: LookupSet<PRId>,
+    streaks: Vector<VersionedStreak>,
+    user_streaks: LookupMap<(UserId, StreakId), VersionedStreakUserData>,
+}
+
 #[near]
-impl Contract {}
+impl Contract {
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state: OldState = env::state_read().expect("Failed to read the old state");
+
+        Self {
+            // The deployed contract is administered by the sloth, so it stays the owner
+            owner: state.sloth.clone(),
+            sloth: state.sloth,
+            account_ids: state.account_ids,
+            users: state.users,
+            sloths_per_period: state.sloths_per_period,
+            organizations: state.organizations,
+            prs: state.prs,
+            executed_prs: state.executed_prs,
+            excluded_prs: state.excluded_prs,
+            streaks: state.streaks,
+            user_streaks: state.user_streaks,
+            #[allow(deprecated)]
+            teams: UnorderedMap::new(storage::StorageKey::Teams),
+            user_teams: LookupMap::new(storage::StorageKey::UserTeams),
+            team_requests: LookupMap::new(storage::StorageKey::TeamRequests),
+            referrals: LookupMap::new(storage::StorageKey::Referrals),
+            referees: LookupMap::new(storage::StorageKey::Referees),
+            user_achievements: LookupMap::new(storage::StorageKey::UserAchievements),
+            pr_achievements: LookupMap::new(storage::StorageKey::PRAchievements),
+            #[allow(deprecated)]
+            rank_badges: UnorderedMap::new(storage::StorageKey::RankBadges),
+            rank_badges_per_owner: LookupMap::new(storage::StorageKey::RankBadgesPerOwner),
+            account_link_requests: LookupMap::new(storage::StorageKey::AccountLinkRequests),
+            badge_url: DEFAULT_BADGE_URL.to_string(),
+            prs_by_author: LookupMap::new(storage::StorageKey::PRsByAuthor),
+            prs_by_repo: LookupMap::new(storage::StorageKey::PRsByRepo),
+            inactive_prs_by_repo: LookupMap::new(storage::StorageKey::InactivePRsByRepo),
+            pr_index: IterableSet::new(storage::StorageKey::PRIndex),
+            unmerged_index: IterableSet::new(storage::StorageKey::UnmergedIndex),
+            merge_queue: LookupMap::new(storage::StorageKey::MergeQueue),
+            merge_queue_head: 0,
+            merge_queue_tail: 0,
+            pr_archive: Default::default(),
+            github_ids: LookupMap::new(storage::StorageKey::GithubIds),
+            user_github_ids: LookupMap::new(storage::StorageKey::UserGithubIds),
+            #[allow(deprecated)]
+            opted_out: UnorderedSet::new(storage::StorageKey::OptedOut),
+            merged_accounts: 0,
+            paused: false,
+            paused_methods: HashSet::new(),
+            score_timeouts: LookupMap::new(storage::StorageKey::ScoreTimeouts),
+            stats: Default::default(),
+            organization_stats: LookupMap::new(storage::StorageKey::OrganizationStats),
+            score_reasons: LookupMap::new(storage::StorageKey::ScoreReasons),
+            snoozed_prs: LookupMap::new(storage::StorageKey::SnoozedPRs),
+            pending_prs: LookupSet::new(storage::StorageKey::PendingPRs),
+            drafted_prs: LookupSet::new(storage::StorageKey::DraftedPRs),
+            closed_prs: LookupMap::new(storage::StorageKey::ClosedPRs),
+            reverted_prs: IterableMap::new(storage::StorageKey::RevertedPRs),
+            #[allow(deprecated)]
+            repo_ids: UnorderedMap::new(storage::StorageKey::RepoIds),
+        }
+    }
+}
@@ -67,7 +67,7 @@ impl Contract {
 
         // Simulate scoring
         let score = 10; // Example fixed score
-        self.sloth_scored(pr_id.to_string(), "reviewer".to_string(), score);
+        self.sloth_scored(pr_id.to_string(), "reviewer".to_string(), score, None);
 
         // Simulate merging
         let merged_at = timestamp + 1000000000; // Example: Merge 1,000 seconds later
@@ -0,0 +1,286 @@
+use near_sdk::{
+    borsh,
+    json_types::U128,
+    near_bindgen,
+    serde::{Deserialize, Serialize},
+    NearSchema, NearToken, Promise,
+};
+use shared::{Rank, RankBadge, VersionedRankBadge};
+
+use super::*;
+
+pub type TokenId = String;
+
+pub const NFT_METADATA_SPEC: &str = "nft-1.0.0";
+
+// The requester pays for the storage of the request and gets the deposit back once it's resolved
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct AccountLinkRequest {
+    pub github_handle: GithubHandle,
+    pub deposit: NearToken,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NFTContractMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub base_uri: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<String>,
+    pub copies: Option<u64>,
+    pub issued_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub starts_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Token {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub metadata: Option<TokenMetadata>,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Called by the contributor from the NEAR account they want to link.
+    // A new request of the same account replaces the previous one.
+    #[payable]
+    pub fn request_account_link(&mut self, github_handle: GithubHandle) {
+        let account_id = env::predecessor_account_id();
+        let request = AccountLinkRequest {
+            github_handle,
+            deposit: env::attached_deposit(),
+        };
+
+        let bytes =
+            borsh::to_vec(&account_id).unwrap().len() + borsh::to_vec(&request).unwrap().len();
+        let required = env::storage_byte_cost()
+            .saturating_mul((bytes as u64 + archive::STORAGE_RECORD_OVERHEAD) as u128);
+        if request.deposit < required {
+            ContractError::InsufficientDeposit.panic()
+        }
+
+        if let Some(previous) = self
+            .account_link_requests
+            .insert(account_id.clone(), request)
+        {
+            Promise::new(account_id).transfer(previous.deposit);
+        }
+    }
+
+    pub fn cancel_account_link(&mut self) {
+        let account_id = env::predecessor_account_id();
+        match self.account_link_requests.remove(&account_id) {
+            Some(request) => {
+                Promise::new(account_id).transfer(request.deposit);
+            }
+            None => ContractError::AccountLinkNotRequested.panic(),
+        }
+    }
+
+    // Called by the sloth once the contributor confirms the account from the GitHub
+    pub fn sloth_link_account(&mut self, user: GithubHandle, account_id: AccountId) {
+        self.assert_sloth_method("sloth_link_account");
+
+        let request = match self.account_link_requests.get(&account_id) {
+            Some(request) if request.github_handle == user => request.clone(),
+            _ => ContractError::AccountLinkNotRequested.panic(),
+        };
+
+        let (user_id, mut account) = self.get_or_create_account(&user);
+        if self.opted_out.contains(&user_id) {
+            ContractError::UserOptedOut.panic()
+        }
+        account.account_id = Some(account_id.clone());
+        self.users[user_id] = VersionedAccount::V1(account);
+        self.account_link_requests.remove(&account_id);
+        Promise::new(account_id).transfer(request.deposit);
+
+        // Ranks reached before the account was linked are minted right away
+        self.mint_rank_badges(user_id);
+    }
+
+    pub fn account_link_request(&self, account_id: AccountId) -> Option<GithubHandle> {
+        self.account_link_requests
+            .get(&account_id)
+            .map(|request| request.github_handle.clone())
+    }
+
+    pub fn set_badge_url(&mut self, badge_url: String) {
+        self.assert_sloth();
+        self.badge_url = badge_url;
+    }
+
+    #[payable]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        let _ = (receiver_id, token_id, approval_id, memo);
+        ContractError::Soulbound.panic()
+    }
+
+    #[payable]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) {
+        let _ = (receiver_id, token_id, approval_id, memo, msg);
+        ContractError::Soulbound.panic()
+    }
+
+    pub fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.rank_badges
+            .get(&token_id)
+            .cloned()
+            .map(|badge| self.token(token_id, badge.into()))
+    }
+
+    pub fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "Race of Sloths ranks".to_string(),
+            symbol: "SLOTH".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    pub fn nft_total_supply(&self) -> U128 {
+        U128(self.rank_badges.len() as u128)
+    }
+
+    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        let from_index = from_index.map(|i| i.0 as usize).unwrap_or_default();
+        self.rank_badges
+            .iter()
+            .skip(from_index)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .map(|(token_id, badge)| self.token(token_id.clone(), badge.clone().into()))
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        U128(
+            self.rank_badges_per_owner
+                .get(&account_id)
+                .map(|tokens| tokens.len())
+                .unwrap_or_default() as u128,
+        )
+    }
+
+    pub fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let from_index = from_index.map(|i| i.0 as usize).unwrap_or_default();
+        self.rank_badges_per_owner
+            .get(&account_id)
+            .map(|tokens| {
+                tokens
+                    .iter()
+                    .skip(from_index)
+                    .take(limit.unwrap_or(u64::MAX) as usize)
+                    .filter_map(|token_id| self.nft_token(token_id.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn rank_badges(&self, user: GithubHandle) -> Vec<Token> {
+        let Some(user_id) = self.account_ids.get(&user) else {
+            return vec![];
+        };
+        Rank::iter()
+            .filter_map(|rank| self.nft_token(RankBadge::token_id(*user_id, rank)))
+            .collect()
+    }
+}
+
+impl Contract {
+    pub fn mint_rank_badges(&mut self, user_id: UserId) {
+        let account: AccountWithPermanentPercentageBonus = self.users[user_id].clone().into();
+        let Some(owner_id) = account.account_id.clone() else {
+            return;
+        };
+
+        let mut minted = vec![];
+        for rank in Rank::reached(account.lifetime_percentage_bonus()) {
+            let token_id = RankBadge::token_id(user_id, rank);
+            if self.rank_badges.get(&token_id).is_some() {
+                continue;
+            }
+
+            self.rank_badges.insert(
+                token_id.clone(),
+                VersionedRankBadge::V1(RankBadge {
+                    owner_id: owner_id.clone(),
+                    github_handle: account.github_handle.clone(),
+                    rank,
+                    issued_at: env::block_timestamp(),
+                }),
+            );
+            minted.push(token_id);
+        }
+
+        if minted.is_empty() {
+            return;
+        }
+
+        self.rank_badges_per_owner
+            .entry(owner_id.clone())
+            .or_insert(vec![])
+            .extend(minted.clone());
+        events::log_nft_mint(owner_id, minted);
+    }
+
+    fn token(&self, token_id: TokenId, badge: RankBadge) -> Token {
+        Token {
+            token_id,
+            owner_id: badge.owner_id,
+            metadata: Some(TokenMetadata {
+                title: Some(format!("{} Sloth", badge.rank.name())),
+                description: Some(format!(
+                    "@{} reached the {} rank in the Race of Sloths",
+                    badge.github_handle,
+                    badge.rank.name()
+                )),
+                media: Some(format!(
+                    "{}/{}?type=share",
+                    self.badge_url, badge.github_handle
+                )),
+                issued_at: Some(badge.issued_at.to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use near_sdk::near_bindgen;
+
+use super::*;
+
+#[near_bindgen]
+impl Contract {
+    // Emergency stop for all the sloth mutations, e.g. when the bot misbehaves
+    pub fn pause(&mut self) {
+        self.assert_owner();
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.assert_owner();
+        self.paused = false;
+    }
+
+    // Circuit breaker for a single sloth method
+    pub fn set_method_paused(&mut self, method: String, paused: bool) {
+        self.assert_owner();
+        if paused {
+            self.paused_methods.insert(method);
+        } else {
+            self.paused_methods.remove(&method);
+        }
+    }
+
+    pub fn set_owner(&mut self, owner: AccountId) {
+        self.assert_owner();
+        self.owner = owner;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn paused_methods(&self) -> Vec<String> {
+        self.paused_methods.iter().cloned().collect()
+    }
+
+    pub fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
+
+impl Contract {
+    pub fn assert_owner(&self) {
+        if env::predecessor_account_id() != self.owner {
+            ContractError::NotOwner.panic()
+        }
+    }
+
+    // Admin methods only check the sloth, so the configuration can be fixed while paused
+    pub fn assert_sloth_method(&self, method: &str) {
+        self.assert_sloth();
+        if self.paused {
+            ContractError::ContractPaused.panic()
+        }
+        if self.paused_methods.contains(method) {
+            ContractError::MethodPaused.panic_with(format!("Method {method} is paused"))
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+impl Contract {
+    pub fn set_score_reason(&mut self, pr_id: &PRId, user: &GithubHandle, reason: Option<String>) {
+        let key = (pr_id.clone(), user.clone());
+        match reason.filter(|reason| !reason.is_empty()) {
+            Some(reason) => {
+                self.score_reasons
+                    .insert(key, env::sha256_array(reason.as_bytes()));
+            }
+            None => {
+                self.score_reasons.remove(&key);
+            }
+        }
+    }
+
+    pub fn forget_score_reasons(&mut self, pr_id: &PRId, pr: &PRWithRating) {
+        for score in &pr.score {
+            self.score_reasons
+                .remove(&(pr_id.clone(), score.user.clone()));
+        }
+    }
+
+    pub fn move_score_reasons(&mut self, pr_id: &PRId, new_id: &PRId, pr: &PRWithRating) {
+        for score in &pr.score {
+            if let Some(hash) = self
+                .score_reasons
+                .remove(&(pr_id.clone(), score.user.clone()))
+            {
+                self.score_reasons
+                    .insert((new_id.clone(), score.user.clone()), hash);
+            }
+        }
+    }
+
+    // Scores are stored without the reasons, so the views fill them in
+    pub fn with_score_reasons(&self, mut pr: PRWithRating) -> PRWithRating {
+        let pr_id = pr.pr_id();
+        for score in &mut pr.score {
+            score.reason_hash = self
+                .score_reasons
+                .get(&(pr_id.clone(), score.user.clone()))
+                .map(|hash| (*hash).into());
+        }
+        pr
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use near_sdk::near_bindgen;
+
+use crate::indexes::remove_from_repo_index;
+
+use super::*;
+
+// PRs moved per call, so the moves of the big repos fit into the gas limit
+pub const MOVE_REPO_BATCH_SIZE: usize = 50;
+
+#[near_bindgen]
+impl Contract {
+    // Moves the PRs of the renamed or transferred repository to the new name in batches.
+    // Returns the amount of the PRs left under the old name, the repo id follows the new name
+    // only once all of them are moved.
+    // PRs excluded before the inactive index was introduced stay under the old name.
+    pub fn sloth_move_repo(
+        &mut self,
+        organization: String,
+        repo: String,
+        new_organization: String,
+        new_repo: String,
+        repo_id: u64,
+    ) -> u32 {
+        self.assert_sloth_method("sloth_move_repo");
+
+        if organization == new_organization && repo == new_repo {
+            ContractError::SameRepo.panic()
+        }
+
+        let new = (new_organization, new_repo);
+        let left = self.move_repo((organization, repo), new.clone());
+        if left == 0 {
+            self.repo_ids.insert(repo_id, new);
+        }
+        left
+    }
+
+    pub fn repo_ids(&self) -> Vec<(u64, String, String)> {
+        self.repo_ids
+            .iter()
+            .map(|(id, (organization, repo))| (*id, organization.clone(), repo.clone()))
+            .collect()
+    }
+}
+
+impl Contract {
+    // Binds the immutable GitHub id to the repo and follows the renames.
+    // The rest of the big repo is moved by the next includes or by the bot
+    pub fn track_repo_id(&mut self, organization: &str, repo: &str, repo_id: u64) {
+        let new = (organization.to_string(), repo.to_string());
+        match self.repo_ids.get(&repo_id).cloned() {
+            Some(old) if old == new => {}
+            Some(old) => {
+                if self.move_repo(old, new.clone()) == 0 {
+                    self.repo_ids.insert(repo_id, new);
+                }
+            }
+            None => {
+                self.repo_ids.insert(repo_id, new);
+            }
+        }
+    }
+
+    // Returns the amount of the PRs left under the old name
+    fn move_repo(&mut self, old: (String, String), new: (String, String)) -> u32 {
+        self.move_repo_settings(&old, &new);
+
+        let mut stats = PRStats::default();
+        let mut renamed = HashMap::new();
+        let pr_ids: Vec<PRId> = self
+            .prs_by_repo
+            .get(&old)
+            .map(|prs| {
+                prs.iter()
+                    .take(MOVE_REPO_BATCH_SIZE)
+                    .map(|(pr_id, _)| pr_id.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for pr_id in pr_ids {
+            if let Some(new_id) = self.move_pr(&pr_id, &old, &new, &mut stats) {
+                renamed.insert(pr_id, new_id);
+            }
+        }
+
+        let limit = MOVE_REPO_BATCH_SIZE.saturating_sub(renamed.len());
+        let pr_ids: Vec<PRId> = self
+            .inactive_prs_by_repo
+            .get(&old)
+            .map(|prs| prs.iter().take(limit).cloned().collect())
+            .unwrap_or_default();
+        for pr_id in pr_ids {
+            self.move_inactive_pr(&pr_id, &old, &new, &mut stats);
+        }
+
+        if old.0 != new.0 {
+            self.move_stats(&old.0, &new.0, &stats);
+        }
+        for index in self.merge_queue_head..self.merge_queue_tail {
+            if let Some(pr_id) = self.merge_queue.get_mut(&index) {
+                if let Some(new_id) = renamed.get(&*pr_id) {
+                    *pr_id = new_id.clone();
+                }
+            }
+        }
+
+        let active = self.prs_by_repo.get(&old).map(|prs| prs.len());
+        let inactive = self.inactive_prs_by_repo.get(&old).map(|prs| prs.len());
+        active.unwrap_or_default() + inactive.unwrap_or_default()
+    }
+
+    // Carries the pause state and the scoring window over to the new name.
+    // The permission is moved only while the old repo has its own entry, so it's moved once
+    fn move_repo_settings(&mut self, old: &(String, String), new: &(String, String)) {
+        if let Some(VersionedOrganization::V1(org)) = self.organizations.get_mut(&old.0) {
+            let allowed = org.is_allowed(&old.1);
+            let listed = org.is_listed(&old.1);
+            org.forget(&old.1);
+
+            match self.organizations.get_mut(&new.0) {
+                Some(VersionedOrganization::V1(org)) if listed && allowed => org.include(&new.1),
+                Some(VersionedOrganization::V1(org)) if listed => org.exclude(&new.1),
+                Some(_) => {}
+                None if allowed => {
+                    let org = Organization::new_only(
+                        new.0.clone(),
+                        [new.1.clone()].into_iter().collect(),
+                    );
+                    self.organizations
+                        .insert(org.name.clone(), VersionedOrganization::V1(org));
+                }
+                None => {}
+            }
+        }
+
+        if !self.score_timeouts.contains_key(&new.0) {
+            if let Some(timeout) = self.score_timeouts.get(&old.0).copied() {
+                self.score_timeouts.insert(new.0.clone(), timeout);
+            }
+        }
+    }
+
+    // Returns the new id of the moved PR
+    fn move_pr(
+        &mut self,
+        pr_id: &PRId,
+        old: &(String, String),
+        new: &(String, String),
+        stats: &mut PRStats,
+    ) -> Option<PRId> {
+        self.backfill_pr(pr_id);
+        let (old_pr, executed): (PRWithRating, bool) = match self.prs.get(pr_id) {
+            Some(pr) => (pr.clone().into(), false),
+            None => match self.executed_prs.get(pr_id) {
+                Some(pr) => (pr.clone().into(), true),
+                None => {
+                    self.remove_from_pr_repo_index(old, pr_id);
+                    return None;
+                }
+            },
+        };
+        let mut pr = old_pr.clone();
+        pr.organization = new.0.clone();
+        pr.repo = new.1.clone();
+        let new_id = pr.pr_id();
+
+        // The PR was included again under the new name, so the old copy isn't moved
+        if self.prs.contains_key(&new_id) || self.executed_prs.contains_key(&new_id) {
+            self.remove_from_pr_repo_index(old, pr_id);
+            return None;
+        }
+
+        if executed {
+            self.executed_prs.remove(pr_id);
+            self.executed_prs
+                .insert(new_id.clone(), VersionedPR::V1(pr.clone()));
+        } else {
+            self.prs.remove(pr_id);
+            self.prs.insert(new_id.clone(), VersionedPR::V1(pr.clone()));
+        }
+
+        self.move_score_reasons(pr_id, &new_id, &pr);
+        if self.pending_prs.remove(pr_id) {
+            self.pending_prs.insert(new_id.clone());
+        } else {
+            stats.opened += 1;
+        }
+        if self.drafted_prs.remove(pr_id) {
+            self.drafted_prs.insert(new_id.clone());
+        }
+        if let Some(until) = self.snoozed_prs.remove(pr_id) {
+            self.snoozed_prs.insert(new_id.clone(), until);
+        }
+        if let Some(achievements) = self.pr_achievements.remove(pr_id) {
+            self.pr_achievements.insert(new_id.clone(), achievements);
+        }
+        let (user_id, _) = self.get_or_create_account(&pr.author);
+        self.unindex_pr(user_id, pr_id, &old_pr);
+        self.index_pr(user_id, &new_id, &pr);
+
+        if pr.merged_at.is_some() {
+            stats.merged += 1;
+        }
+        if executed {
+            stats.executed += 1;
+            stats.total_rating += pr.rating() as u64;
+        }
+        Some(new_id)
+    }
+
+    fn move_inactive_pr(
+        &mut self,
+        pr_id: &PRId,
+        old: &(String, String),
+        new: &(String, String),
+        stats: &mut PRStats,
+    ) {
+        remove_from_repo_index(&mut self.inactive_prs_by_repo, old, pr_id);
+        let Some(number) = pr_id.rsplit('/').next() else {
+            return;
+        };
+        let new_id = format!("{}/{}/{number}", new.0, new.1);
+
+        // The PR was included again under the new name, so the old copy isn't moved
+        if self.prs.contains_key(&new_id)
+            || self.executed_prs.contains_key(&new_id)
+            || self.excluded_prs.contains(&new_id)
+            || self.closed_prs.contains_key(&new_id)
+        {
+            return;
+        }
+
+        // Excluded PRs keep only the id, so they are moved as the counted ones
+        if self.excluded_prs.remove(pr_id) {
+            self.excluded_prs.insert(new_id.clone());
+            stats.opened += 1;
+            stats.excluded += 1;
+        }
+        if let Some(reverted_at) = self.reverted_prs.remove(pr_id) {
+            self.reverted_prs.insert(new_id.clone(), reverted_at);
+        }
+        if let Some(mut closed) = self.closed_prs.remove(pr_id) {
+            let mut pr: PRWithRating = closed.pr.into();
+            pr.organization = new.0.clone();
+            pr.repo = new.1.clone();
+            self.move_score_reasons(pr_id, &new_id, &pr);
+            if !closed.pending {
+                stats.opened += 1;
+            }
+            closed.pr = VersionedPR::V1(pr);
+            self.closed_prs.insert(new_id.clone(), closed);
+        }
+        self.index_inactive_pr(&new_id, &new.0, &new.1);
+    }
+}
@@ -0,0 +1,53 @@
+use near_sdk::near_bindgen;
+use shared::{PRStats, Stats};
+
+use super::*;
+
+#[near_bindgen]
+impl Contract {
+    pub fn stats(&self) -> Stats {
+        Stats {
+            users: self
+                .users
+                .len()
+                .saturating_sub(self.merged_accounts)
+                .saturating_sub(self.opted_out.len()),
+            prs: self.stats.clone(),
+            organizations: self
+                .organizations
+                .keys()
+                .map(|organization| {
+                    let stats = self
+                        .organization_stats
+                        .get(organization)
+                        .cloned()
+                        .unwrap_or_default();
+                    (organization.clone(), stats)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Contract {
+    // Applies the change to the global stats and the stats of the organization
+    pub fn record_stats(&mut self, organization: &str, func: impl Fn(&mut PRStats)) {
+        func(&mut self.stats);
+        func(
+            self.organization_stats
+                .entry(organization.to_string())
+                .or_insert(Default::default()),
+        );
+    }
+
+    // Moves the counts of the PRs to the stats of another organization, the global ones stay
+    pub fn move_stats(&mut self, from: &str, to: &str, stats: &PRStats) {
+        if let Some(old) = self.organization_stats.get_mut(from) {
+            old.sub(stats);
+        }
+        self.organization_stats
+            .entry(to.to_string())
+            .or_insert(Default::default())
+            .add(stats);
+    }
+}
@@ -1,7 +1,8 @@
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
-    BorshStorageKey,
+    BorshStorageKey, CryptoHash,
 };
+use shared::UserId;
 
 #[derive(BorshSerialize, BorshDeserialize, BorshStorageKey)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -15,4 +16,36 @@ pub enum StorageKey {
     Streaks,
     UserStreaks,
     AccountIds,
+    Teams,
+    UserTeams,
+    TeamRequests,
+    Referrals,
+    UserAchievements,
+    RankBadges,
+    RankBadgesPerOwner,
+    AccountLinkRequests,
+    PRsByAuthor,
+    PRsByRepo,
+    MergeQueue,
+    GithubIds,
+    OptedOut,
+    ScoreTimeouts,
+    OrganizationStats,
+    ScoreReasons,
+    SnoozedPRs,
+    PendingPRs,
+    ClosedPRs,
+    RepoIds,
+    PRsByAuthorEntry { user_id: UserId },
+    PRsByRepoEntry { repo_hash: CryptoHash },
+    PRIndex,
+    UnmergedIndex,
+    Referees,
+    RefereesEntry { user_id: UserId },
+    UserGithubIds,
+    DraftedPRs,
+    PRAchievements,
+    RevertedPRs,
+    InactivePRsByRepo,
+    InactivePRsByRepoEntry { repo_hash: CryptoHash },
 }
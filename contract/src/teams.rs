@@ -0,0 +1,106 @@
+use near_sdk::near_bindgen;
+use shared::Team;
+
+use super::*;
+
+#[near_bindgen]
+impl Contract {
+    // Teams are created on behalf of the organization maintainers, so the contributors
+    // can only ask to join the existing ones
+    pub fn sloth_team_create(&mut self, team: TeamName, organization: String) {
+        self.assert_sloth_method("sloth_team_create");
+
+        match self.teams.get(&team) {
+            Some(VersionedTeam::V1(existing)) if existing.organization != organization => {
+                ContractError::TeamOfAnotherOrganization.panic()
+            }
+            Some(_) => {}
+            None => {
+                self.teams.insert(
+                    team.clone(),
+                    VersionedTeam::V1(Team::new(team, organization)),
+                );
+            }
+        }
+    }
+
+    pub fn sloth_team_request(&mut self, team: TeamName, organization: String, user: GithubHandle) {
+        self.assert_sloth_method("sloth_team_request");
+
+        match self.teams.get(&team) {
+            Some(VersionedTeam::V1(existing)) if existing.organization != organization => {
+                ContractError::TeamOfAnotherOrganization.panic()
+            }
+            Some(_) => {}
+            None => ContractError::TeamNotFound.panic(),
+        }
+
+        let (user_id, _) = self.get_or_create_account(&user);
+        if self.user_teams.get(&user_id) == Some(&team) {
+            ContractError::AlreadyTeamMember.panic()
+        }
+
+        self.team_requests.insert(user_id, team);
+    }
+
+    // Approves the pending request of the user to join a team owned by the organization
+    pub fn sloth_team_approve(&mut self, organization: String, user: GithubHandle) -> TeamName {
+        self.assert_sloth_method("sloth_team_approve");
+
+        let user_id = match self.account_ids.get(&user) {
+            Some(user_id) => *user_id,
+            None => ContractError::UserNotFound.panic(),
+        };
+
+        let team_name = match self.team_requests.get(&user_id) {
+            Some(team) => team.clone(),
+            None => ContractError::NoTeamRequest.panic(),
+        };
+
+        let mut team: Team = match self.teams.get(&team_name).cloned() {
+            Some(team) => team.into(),
+            None => ContractError::TeamNotFound.panic(),
+        };
+        if team.organization != organization {
+            ContractError::TeamOfAnotherOrganization.panic()
+        }
+
+        if let Some(old_team) = self.user_teams.get(&user_id).cloned() {
+            if let Some(VersionedTeam::V1(old_team)) = self.teams.get_mut(&old_team) {
+                old_team.remove_member(&user);
+            }
+        }
+
+        team.add_member(user.clone());
+        self.teams
+            .insert(team_name.clone(), VersionedTeam::V1(team));
+        self.team_requests.remove(&user_id);
+        self.user_teams.insert(user_id, team_name.clone());
+
+        events::log_event(Event::TeamJoined {
+            team: team_name.clone(),
+            github_handle: user,
+        });
+
+        team_name
+    }
+
+    pub fn team(&self, name: TeamName) -> Option<Team> {
+        self.teams.get(&name).cloned().map(Into::into)
+    }
+
+    pub fn teams(&self, page: u64, limit: u64) -> Vec<Team> {
+        self.teams
+            .values()
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn team_request(&self, user: GithubHandle) -> Option<TeamName> {
+        let user_id = self.account_ids.get(&user)?;
+        self.team_requests.get(user_id).cloned()
+    }
+}
@@ -1,5 +1,8 @@
-use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId, VMContext};
-use shared::{PRWithRating, SCORE_TIMEOUT_IN_NANOSECONDS};
+use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId, NearToken, VMContext};
+use shared::{
+    Achievement, Operation, PRWithRating, UserAchievements, MAX_SNOOZE_IN_NANOSECONDS,
+    REFERRAL_BONUS_RATING, SCORE_TIMEOUT_IN_NANOSECONDS,
+};
 
 use super::*;
 
@@ -52,12 +55,15 @@ impl ContractExt {
             pr_id,
             started_at,
             true,
+            None,
+            None,
+            None,
         );
     }
 
     pub fn score(&mut self, pr_id: u64, id: u8, score: u32) {
         self.contract
-            .sloth_scored(pr_id_str(pr_id), github_handle(id), score);
+            .sloth_scored(pr_id_str(pr_id), github_handle(id), score, None);
     }
 
     pub fn merge(&mut self, pr_id: u64, merged_at: u64) {
@@ -446,3 +452,1383 @@ fn monthly_streak_awarded_imediatly() {
 
     assert_eq!(pr.streak_bonus_rating, 10 + 10);
 }
+
+#[test]
+fn team_membership_flow() {
+    let mut contract = ContractExt::new();
+
+    contract
+        .contract
+        .sloth_team_create("devhub".to_owned(), "NEAR-DevHub".to_owned());
+    contract
+        .contract
+        .sloth_team_create("sloths".to_owned(), "NEAR-DevHub".to_owned());
+    contract.contract.sloth_team_request(
+        "devhub".to_owned(),
+        "NEAR-DevHub".to_owned(),
+        github_handle(0),
+    );
+    assert!(contract
+        .contract
+        .team("devhub".to_owned())
+        .unwrap()
+        .members
+        .is_empty());
+
+    contract
+        .contract
+        .sloth_team_approve("NEAR-DevHub".to_owned(), github_handle(0));
+    assert_eq!(
+        contract.contract.team("devhub".to_owned()).unwrap().members,
+        vec![github_handle(0)]
+    );
+    assert!(contract.contract.team_request(github_handle(0)).is_none());
+
+    // Switching the team removes the user from the previous one
+    contract.contract.sloth_team_request(
+        "sloths".to_owned(),
+        "NEAR-DevHub".to_owned(),
+        github_handle(0),
+    );
+    contract
+        .contract
+        .sloth_team_approve("NEAR-DevHub".to_owned(), github_handle(0));
+    assert!(contract
+        .contract
+        .team("devhub".to_owned())
+        .unwrap()
+        .members
+        .is_empty());
+    assert_eq!(
+        contract.contract.team("sloths".to_owned()).unwrap().members,
+        vec![github_handle(0)]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Team is owned by another organization")]
+fn team_approve_by_another_organization() {
+    let mut contract = ContractExt::new();
+
+    contract
+        .contract
+        .sloth_team_create("devhub".to_owned(), "NEAR-DevHub".to_owned());
+    contract.contract.sloth_team_request(
+        "devhub".to_owned(),
+        "NEAR-DevHub".to_owned(),
+        github_handle(0),
+    );
+    contract
+        .contract
+        .sloth_team_approve("blahh".to_owned(), github_handle(0));
+}
+
+#[test]
+#[should_panic(expected = "Team doesn't exist")]
+fn team_request_to_unknown_team() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_team_request(
+        "devhub".to_owned(),
+        "NEAR-DevHub".to_owned(),
+        github_handle(0),
+    );
+}
+
+#[test]
+fn referral_bonus_flow() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 1);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    contract.include_sloth_common_repo(2, 1, SCORE_TIMEOUT_IN_NANOSECONDS + 2);
+    contract
+        .contract
+        .sloth_referred_by(github_handle(2), github_handle(0));
+    contract.score(1, 1, 5);
+    contract.merge(1, SCORE_TIMEOUT_IN_NANOSECONDS + 3);
+    contract.context.block_timestamp = 2 * SCORE_TIMEOUT_IN_NANOSECONDS + 4;
+    testing_env!(contract.context.clone());
+
+    let rating_before = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap()
+        .period_data[0]
+        .1
+        .total_rating;
+    let stats_before = contract.contract.stats().prs.total_rating;
+    contract.finalize(1);
+
+    let pr: PRWithRating = contract
+        .contract
+        .executed_prs
+        .get(&pr_id_str(1))
+        .cloned()
+        .unwrap()
+        .into();
+    assert_eq!(
+        contract.contract.stats().prs.total_rating,
+        stats_before + (pr.rating() + REFERRAL_BONUS_RATING) as u64
+    );
+    let referrer = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(
+        referrer.period_data[0].1.total_rating,
+        rating_before + REFERRAL_BONUS_RATING
+    );
+    let bonus = contract
+        .contract
+        .referral(github_handle(2))
+        .unwrap()
+        .rewarded
+        .unwrap();
+    assert_eq!(bonus.pr_id, pr_id_str(1));
+    assert_eq!(bonus.rating, REFERRAL_BONUS_RATING);
+}
+
+#[test]
+#[should_panic(expected = "Self-referral is not allowed")]
+fn self_referral() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract
+        .contract
+        .sloth_referred_by(github_handle(0), github_handle(0));
+}
+
+#[test]
+#[should_panic(expected = "Referrer has no executed PRs")]
+fn referral_without_executed_prs() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(1, 1, 0);
+    contract
+        .contract
+        .sloth_referred_by(github_handle(1), github_handle(0));
+}
+
+#[test]
+fn achievements_unlocked_on_finalize() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 13);
+    contract.merge(0, 1);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let achievements: Vec<Achievement> = contract
+        .contract
+        .achievements(0)
+        .into_iter()
+        .map(|(achievement, _)| achievement)
+        .collect();
+    assert_eq!(
+        achievements,
+        vec![Achievement::FirstPR, Achievement::FirstMaxScore]
+    );
+
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.achievements.len(), 2);
+}
+
+#[test]
+fn rank_badge_minted_on_account_link() {
+    let mut contract = ContractExt::new();
+
+    let mut start = 0;
+    for i in 0..5 {
+        contract.include_sloth_common_repo(0, i, start);
+        contract.merge(i, start + 1);
+
+        start += WEEK_IN_NANOSECONDS + 1;
+        contract.context.block_timestamp = start;
+        testing_env!(contract.context.clone());
+        contract.finalize(i);
+    }
+    assert!(contract.contract.rank_badges(github_handle(0)).is_empty());
+
+    let account: AccountId = "sloth.near".parse().unwrap();
+    contract.context.predecessor_account_id = account.clone();
+    contract.context.attached_deposit = NearToken::from_millinear(10);
+    testing_env!(contract.context.clone());
+    contract.contract.request_account_link(github_handle(0));
+
+    contract.context.predecessor_account_id = admin();
+    contract.context.attached_deposit = NearToken::from_near(0);
+    testing_env!(contract.context.clone());
+    contract
+        .contract
+        .sloth_link_account(github_handle(0), account.clone());
+
+    let badges = contract.contract.rank_badges(github_handle(0));
+    assert_eq!(badges.len(), 1);
+    assert_eq!(badges[0].token_id, "0-bronze");
+    assert_eq!(badges[0].owner_id, account);
+    assert_eq!(contract.contract.nft_supply_for_owner(account).0, 1);
+    assert_eq!(contract.contract.nft_total_supply().0, 1);
+}
+
+#[test]
+#[should_panic(expected = "Account link wasn't requested by the account")]
+fn account_link_without_request() {
+    let mut contract = ContractExt::new();
+
+    contract
+        .contract
+        .sloth_link_account(github_handle(0), "sloth.near".parse().unwrap());
+}
+
+#[test]
+#[should_panic(expected = "Attached deposit doesn't cover the storage")]
+fn account_link_without_deposit() {
+    let mut contract = ContractExt::new();
+
+    contract.context.predecessor_account_id = "sloth.near".parse().unwrap();
+    testing_env!(contract.context.clone());
+    contract.contract.request_account_link(github_handle(0));
+}
+
+#[test]
+fn account_link_cancelled() {
+    let mut contract = ContractExt::new();
+
+    let account: AccountId = "sloth.near".parse().unwrap();
+    contract.context.predecessor_account_id = account.clone();
+    contract.context.attached_deposit = NearToken::from_millinear(10);
+    testing_env!(contract.context.clone());
+    contract.contract.request_account_link(github_handle(0));
+    assert_eq!(
+        contract.contract.account_link_request(account.clone()),
+        Some(github_handle(0))
+    );
+
+    contract.context.attached_deposit = NearToken::from_near(0);
+    testing_env!(contract.context.clone());
+    contract.contract.cancel_account_link();
+    assert!(contract.contract.account_link_request(account).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Rank badges are soulbound")]
+fn rank_badge_transfer() {
+    let mut contract = ContractExt::new();
+
+    contract
+        .contract
+        .nft_transfer(admin(), "0-bronze".to_string(), None, None);
+}
+
+#[test]
+fn unfinalized_prs_follow_merge_queue() {
+    let mut contract = ContractExt::new();
+
+    for i in 0..3 {
+        contract.include_sloth_common_repo(0, i, 0);
+    }
+    contract.merge(2, 1);
+    contract.merge(0, 2);
+    assert_eq!(contract.contract.merge_queue_tail, 2);
+
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 3;
+    testing_env!(contract.context.clone());
+    let numbers: Vec<u64> = contract
+        .contract
+        .unfinalized_prs(0, 50)
+        .into_iter()
+        .map(|pr| pr.number)
+        .collect();
+    assert_eq!(numbers, vec![2, 0]);
+
+    // Out of order finalization keeps the head until the first PR is finalized
+    contract.finalize(0);
+    assert_eq!(contract.contract.merge_queue_head, 0);
+    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 1);
+
+    contract.finalize(2);
+    assert_eq!(contract.contract.merge_queue_head, 2);
+    assert!(contract.contract.unfinalized_prs(0, 50).is_empty());
+}
+
+#[test]
+fn prs_by_author_and_repo_from_key() {
+    let mut contract = ContractExt::new();
+
+    for i in 0..3 {
+        contract.include_sloth_common_repo(0, i, 0);
+    }
+    contract.include_sloth_common_repo(1, 3, 0);
+    contract.exclude(1);
+
+    contract.merge(0, 1);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let prs = contract.contract.prs_by_author(github_handle(0), None, 1);
+    assert_eq!(prs.len(), 1);
+    assert_eq!(prs[0].0.number, 0);
+    assert!(prs[0].1);
+
+    let prs = contract
+        .contract
+        .prs_by_author(github_handle(0), Some(pr_id_str(0)), 50);
+    assert_eq!(prs.len(), 1);
+    assert_eq!(prs[0].0.number, 2);
+    assert!(!prs[0].1);
+
+    let prs = contract.contract.prs_by_repo(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        Some(pr_id_str(0)),
+        50,
+    );
+    assert_eq!(prs.len(), 2);
+    assert!(prs.iter().any(|(pr, _)| pr.author == github_handle(1)));
+    assert_eq!(contract.contract.prs(50, 0).len(), 3);
+    assert_eq!(contract.contract.unmerged_prs(0, 50).len(), 2);
+}
+
+#[test]
+fn index_backfill_in_batches() {
+    let mut contract = ContractExt::new();
+
+    for i in 0..3 {
+        contract.include_sloth_common_repo(0, i, 0);
+    }
+    contract.merge(1, 1);
+
+    // Drop the indexes as if the PRs were stored before they were introduced
+    let user_id = *contract
+        .contract
+        .account_ids
+        .get(&github_handle(0))
+        .unwrap();
+    for i in 0..3 {
+        let pr: PRWithRating = contract
+            .contract
+            .prs
+            .get(&pr_id_str(i))
+            .cloned()
+            .unwrap()
+            .into();
+        contract.contract.unindex_pr(user_id, &pr_id_str(i), &pr);
+    }
+    contract.contract.merge_queue_head = contract.contract.merge_queue_tail;
+    contract.contract.stats = Default::default();
+    contract.contract.organization_stats.remove("NEAR-DevHub");
+    assert_eq!(contract.contract.unindexed_prs(0, 50).unwrap().len(), 3);
+    assert!(contract.contract.unindexed_prs(1, 50).is_none());
+
+    // Until the backfill is done, the views read the PRs from the storage
+    assert_eq!(contract.contract.prs(50, 0).len(), 3);
+    assert_eq!(contract.contract.unmerged_prs(0, 50).len(), 2);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 1);
+
+    assert_eq!(
+        contract
+            .contract
+            .sloth_index_prs(vec![pr_id_str(1), pr_id_str(0)]),
+        2
+    );
+    assert_eq!(
+        contract
+            .contract
+            .sloth_index_prs(vec![pr_id_str(0), pr_id_str(1), pr_id_str(2)]),
+        1
+    );
+
+    assert!(contract.contract.unindexed_prs(0, 50).unwrap().is_empty());
+    assert_eq!(
+        contract
+            .contract
+            .prs_by_author(github_handle(0), None, 50)
+            .len(),
+        3
+    );
+    assert_eq!(contract.contract.unmerged_prs(0, 50).len(), 2);
+    assert_eq!(
+        contract.contract.merge_queue_tail - contract.contract.merge_queue_head,
+        1
+    );
+    let stats = contract.contract.stats();
+    assert_eq!(stats.prs.opened, 3);
+    assert_eq!(stats.prs.merged, 1);
+    assert_eq!(stats.prs.executed, 0);
+    assert_eq!(stats.organizations[0].1.opened, 3);
+
+    // A mutation of an unindexed PR backfills it first, so it isn't counted twice
+    let pr_2: PRWithRating = contract
+        .contract
+        .prs
+        .get(&pr_id_str(2))
+        .cloned()
+        .unwrap()
+        .into();
+    contract.contract.unindex_pr(user_id, &pr_id_str(2), &pr_2);
+    contract.contract.stats = Default::default();
+    contract.exclude(2);
+    let stats = contract.contract.stats();
+    assert_eq!(stats.prs.opened, 1);
+    assert_eq!(stats.prs.excluded, 1);
+}
+
+#[test]
+fn prune_archives_old_executed_prs() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(0, 1, 0);
+    contract.include_sloth_common_repo(0, 2, 0);
+    contract.exclude(2);
+    contract.merge(0, 1);
+    contract.merge(1, 1);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let report = contract.contract.storage_report();
+    let executed = report
+        .collections
+        .iter()
+        .find(|c| c.name == "executed_prs")
+        .unwrap();
+    assert_eq!(executed.entries, 1);
+    assert!(executed.bytes > 0);
+
+    // Fresh PRs stay on-chain
+    assert_eq!(contract.contract.sloth_prune(vec![pr_id_str(0)]), 0);
+
+    contract.context.block_timestamp = archive::ARCHIVE_AFTER_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    let pruned = contract
+        .contract
+        .sloth_prune(vec![pr_id_str(0), pr_id_str(1), pr_id_str(2)]);
+
+    // Unfinalized PR is skipped, the exclusion is kept
+    assert_eq!(pruned, 1);
+    assert!(contract.contract.executed_prs.get(&pr_id_str(0)).is_none());
+    assert!(contract.contract.excluded_prs.contains(&pr_id_str(2)));
+    assert!(contract.contract.prs.get(&pr_id_str(1)).is_some());
+    assert_eq!(contract.contract.pr_archive().prs.0, 1);
+    assert_eq!(
+        contract
+            .contract
+            .prs_by_author(github_handle(0), None, 50)
+            .len(),
+        1
+    );
+
+    // User stats aren't affected by the archive
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.executed_prs, 1);
+}
+
+#[test]
+fn github_rename_merges_accounts() {
+    let mut contract = ContractExt::new();
+
+    let include = |contract: &mut ContractExt, id: u8, pr_id: u64, github_id: Option<u64>| {
+        contract.contract.sloth_include(
+            "NEAR-DevHub".to_owned(),
+            "devbot".to_string(),
+            github_handle(id),
+            pr_id,
+            0,
+            true,
+            github_id,
+            None,
+            None,
+        );
+    };
+
+    include(&mut contract, 0, 0, Some(42));
+    contract.merge(0, 1);
+    // The contributor renamed the account before the bot learned about GitHub ids
+    include(&mut contract, 1, 1, None);
+    contract.merge(1, 1);
+    assert_eq!(contract.contract.users.len(), 2);
+
+    // The next PR reveals that both handles belong to the same contributor
+    include(&mut contract, 1, 2, Some(42));
+
+    assert!(contract.contract.user(&github_handle(0), vec![]).is_none());
+    assert_eq!(contract.contract.github_id_user(42), Some(github_handle(1)));
+    let user = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.id, 0);
+    assert_eq!(user.period_data[0].1.prs_opened, 3);
+    assert_eq!(user.period_data[0].1.prs_merged, 2);
+    assert!(contract.contract.user_by_id(1, vec![]).is_none());
+    assert_eq!(contract.contract.stats().users, 1);
+
+    let prs = contract.contract.prs_by_author(github_handle(1), None, 50);
+    assert_eq!(prs.len(), 3);
+    assert!(prs.iter().all(|(pr, _)| pr.author == github_handle(1)));
+}
+
+#[test]
+fn rename_merges_referrals_and_bonus_periods() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 1);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    contract.include_sloth_common_repo(2, 1, SCORE_TIMEOUT_IN_NANOSECONDS + 2);
+    contract
+        .contract
+        .sloth_referred_by(github_handle(2), github_handle(0));
+    contract.merge(1, SCORE_TIMEOUT_IN_NANOSECONDS + 3);
+
+    // The bonus lands in a month without PRs of the referrer
+    let bonus_at = 100 * 24 * 60 * 60 * 1_000_000_000;
+    contract.context.block_timestamp = bonus_at;
+    testing_env!(contract.context.clone());
+    contract.finalize(1);
+    contract.contract.sloth_opt_out(github_handle(0));
+
+    contract.include_sloth_common_repo(5, 2, bonus_at);
+    let user_id = *contract
+        .contract
+        .account_ids
+        .get(&github_handle(5))
+        .unwrap();
+    contract
+        .contract
+        .sloth_rename(github_handle(5), github_handle(0));
+
+    let month = TimePeriod::Month.time_string(bonus_at);
+    let data: UserPeriodData = contract
+        .contract
+        .sloths_per_period
+        .get(&(user_id, month))
+        .cloned()
+        .unwrap()
+        .into();
+    assert_eq!(data.total_rating, REFERRAL_BONUS_RATING);
+
+    let referral = contract.contract.referral(github_handle(2)).unwrap();
+    assert_eq!(referral.referrer, user_id);
+    assert!(contract.contract.opted_out.contains(&user_id));
+}
+
+#[test]
+fn sloth_rename_moves_account() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract
+        .contract
+        .sloth_rename(github_handle(0), github_handle(5));
+
+    assert!(contract.contract.user(&github_handle(0), vec![]).is_none());
+    assert_eq!(
+        contract
+            .contract
+            .user(&github_handle(5), vec![])
+            .unwrap()
+            .id,
+        0
+    );
+    let pr: PRWithRating = contract
+        .contract
+        .prs
+        .get(&pr_id_str(0))
+        .unwrap()
+        .clone()
+        .into();
+    assert_eq!(pr.author, github_handle(5));
+}
+
+#[test]
+fn opted_out_user_is_hidden() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(0, 2, 0);
+    contract.merge(2, 1);
+    contract.include_sloth_common_repo(1, 1, 0);
+    contract.contract.sloth_opt_out(github_handle(0));
+
+    assert!(contract.contract.user(&github_handle(0), vec![]).is_none());
+    assert_eq!(contract.contract.users(50, 0, vec![]).unwrap().len(), 1);
+    // The page of the opted out account is empty, but the next one isn't
+    assert!(contract.contract.users(1, 0, vec![]).unwrap().is_empty());
+    assert_eq!(contract.contract.users(1, 1, vec![]).unwrap().len(), 1);
+    assert!(contract.contract.users(1, 2, vec![]).is_none());
+    assert_eq!(contract.contract.opted_out_users(), vec![github_handle(0)]);
+
+    // Open PRs are excluded, the merged one waits for the finalization
+    assert!(contract.contract.excluded_prs.contains(&pr_id_str(0)));
+    assert!(contract.contract.prs.get(&pr_id_str(2)).is_some());
+}
+
+#[test]
+#[should_panic(expected = "User opted out of the race")]
+fn opted_out_user_cannot_be_included() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.contract.sloth_opt_out(github_handle(0));
+
+    contract.include_sloth_common_repo(0, 1, 0);
+}
+
+#[test]
+#[should_panic(expected = "Contract is paused")]
+fn paused_contract_rejects_sloth_calls() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.pause();
+    assert!(contract.contract.is_paused());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+}
+
+#[test]
+fn paused_contract_accepts_admin_calls() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.pause();
+    contract.contract.allow_organization("near".to_string());
+    contract
+        .contract
+        .exclude_repo("near".to_string(), "nearcore".to_string());
+    contract
+        .contract
+        .set_score_timeout("near".to_string(), Some(MIN_SCORE_TIMEOUT_IN_NANOSECONDS));
+
+    assert_eq!(
+        contract.contract.score_timeout("near".to_string()),
+        MIN_SCORE_TIMEOUT_IN_NANOSECONDS
+    );
+}
+
+#[test]
+#[should_panic(expected = "Method sloth_scored is paused")]
+fn paused_method_rejects_calls() {
+    let mut contract = ContractExt::new();
+
+    contract
+        .contract
+        .set_method_paused("sloth_scored".to_string(), true);
+    contract.include_sloth_common_repo(0, 0, 0);
+
+    contract.score(0, 1, 5);
+}
+
+#[test]
+#[should_panic(expected = "Only owner can call this method")]
+fn pause_by_not_owner() {
+    let mut contract = ContractExt::new();
+
+    contract.context.predecessor_account_id = "sloth.near".parse().unwrap();
+    testing_env!(contract.context.clone());
+    contract.contract.pause();
+}
+
+#[test]
+fn custom_score_timeout() {
+    let mut contract = ContractExt::new();
+    contract.contract.set_score_timeout(
+        "NEAR-DevHub".to_string(),
+        Some(2 * SCORE_TIMEOUT_IN_NANOSECONDS),
+    );
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.merge(0, 10);
+
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 0);
+    assert_eq!(
+        contract
+            .contract
+            .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0)
+            .score_timeout_text(),
+        "48 hours"
+    );
+
+    contract.context.block_timestamp = 2 * SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 1);
+    contract.finalize(0);
+
+    contract
+        .contract
+        .set_score_timeout("NEAR-DevHub".to_string(), None);
+    assert_eq!(
+        contract.contract.score_timeout("NEAR-DevHub".to_string()),
+        SCORE_TIMEOUT_IN_NANOSECONDS
+    );
+}
+
+#[test]
+#[should_panic(expected = "Score timeout is out of the allowed range")]
+fn score_timeout_out_of_range() {
+    let mut contract = ContractExt::new();
+    contract
+        .contract
+        .set_score_timeout("NEAR-DevHub".to_string(), Some(2 * WEEK_IN_NANOSECONDS));
+}
+
+#[test]
+fn batch_applies_operations_in_order() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_batch(vec![
+        Operation::Include {
+            organization: "NEAR-DevHub".to_string(),
+            repo: "devbot".to_string(),
+            user: github_handle(0),
+            pr_number: 0,
+            started_at: 0,
+            override_exclude: false,
+            github_id: None,
+            draft: None,
+            repo_id: None,
+        },
+        Operation::Scored {
+            pr_id: pr_id_str(0),
+            user: github_handle(1),
+            score: 8,
+            reason: None,
+        },
+        Operation::Merged {
+            pr_id: pr_id_str(0),
+            merged_at: 10,
+        },
+    ]);
+    assert_eq!(contract.contract.unmerged_prs(0, 50).len(), 0);
+
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.contract.sloth_batch(vec![Operation::Finalize {
+        pr_id: pr_id_str(0),
+        timestamp: None,
+    }]);
+
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.executed_prs, 1);
+    assert_eq!(user.period_data[0].1.total_score, 8);
+}
+
+#[test]
+#[should_panic(expected = "PR is not started or already executed")]
+fn batch_fails_on_invalid_operation() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_batch(vec![
+        Operation::Include {
+            organization: "NEAR-DevHub".to_string(),
+            repo: "devbot".to_string(),
+            user: github_handle(0),
+            pr_number: 0,
+            started_at: 0,
+            override_exclude: false,
+            github_id: None,
+            draft: None,
+            repo_id: None,
+        },
+        Operation::Merged {
+            pr_id: pr_id_str(1),
+            merged_at: 10,
+        },
+    ]);
+}
+
+#[test]
+fn stats_are_counted_on_mutations() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(1, 1, 0);
+    contract.include_sloth_common_repo(1, 2, 0);
+    contract.score(0, 1, 5);
+    contract.merge(0, 10);
+    contract.merge(0, 11);
+    contract.exclude(1);
+
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let stats = contract.contract.stats();
+    assert_eq!(stats.users, 2);
+    assert_eq!(stats.prs.opened, 3);
+    assert_eq!(stats.prs.merged, 1);
+    assert_eq!(stats.prs.excluded, 1);
+    assert_eq!(stats.prs.executed, 1);
+    assert!(stats.prs.total_rating > 0);
+    assert_eq!(stats.organizations.len(), 1);
+    assert_eq!(stats.organizations[0].0, "NEAR-DevHub");
+    assert_eq!(stats.organizations[0].1.opened, 3);
+
+    // Opted out users aren't counted
+    contract.contract.sloth_opt_out(github_handle(1));
+    assert_eq!(contract.contract.stats().users, 1);
+}
+
+#[test]
+fn unscored_removes_only_reviewer_vote() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 13);
+    contract.score(0, 2, 5);
+    contract
+        .contract
+        .sloth_unscored(pr_id_str(0), github_handle(1));
+    // Removing a vote that doesn't exist is a no-op
+    contract
+        .contract
+        .sloth_unscored(pr_id_str(0), github_handle(3));
+
+    let pr: PRWithRating = contract
+        .contract
+        .prs
+        .get(&pr_id_str(0))
+        .unwrap()
+        .clone()
+        .into();
+    assert_eq!(pr.score(), Some(5));
+}
+
+#[test]
+fn score_reasons_are_kept_as_hashes() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.contract.sloth_scored(
+        pr_id_str(0),
+        github_handle(1),
+        2,
+        Some("Missing tests".to_string()),
+    );
+    contract.score(0, 2, 5);
+
+    let votes = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0)
+        .votes;
+    let expected: near_sdk::json_types::Base58CryptoHash =
+        env::sha256_array(b"Missing tests").into();
+    assert_eq!(votes[0].reason_hash, Some(expected));
+    assert_eq!(votes[1].reason_hash, None);
+
+    // Rescoring without the reason drops the old one
+    contract.score(0, 1, 3);
+    let votes = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0)
+        .votes;
+    assert_eq!(votes[0].reason_hash, None);
+}
+
+#[test]
+fn snoozed_pr_is_forgotten_after_stale() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.contract.sloth_snooze(pr_id_str(0), 100);
+
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert_eq!(info.snoozed_until, Some(100));
+
+    contract.contract.sloth_stale(pr_id_str(0), None);
+    contract.include_sloth_common_repo(0, 0, 0);
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert_eq!(info.snoozed_until, None);
+}
+
+#[test]
+#[should_panic(expected = "PR is not started or already executed")]
+fn snooze_unknown_pr() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_snooze(pr_id_str(0), 100);
+}
+
+#[test]
+#[should_panic(expected = "PR cannot be snoozed for more than 30 days")]
+fn snooze_is_capped() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract
+        .contract
+        .sloth_snooze(pr_id_str(0), MAX_SNOOZE_IN_NANOSECONDS + 1);
+}
+
+#[test]
+fn draft_pr_is_counted_when_ready() {
+    let mut contract = ContractExt::new();
+    let prs_opened = |contract: &ContractExt| {
+        contract
+            .contract
+            .user(&github_handle(0), vec!["all-time".to_string()])
+            .unwrap()
+            .period_data[0]
+            .1
+            .prs_opened
+    };
+
+    contract.contract.sloth_include(
+        "NEAR-DevHub".to_owned(),
+        "devbot".to_string(),
+        github_handle(0),
+        0,
+        0,
+        true,
+        None,
+        Some(true),
+        None,
+    );
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(info.exist);
+    assert!(info.pending);
+    assert_eq!(prs_opened(&contract), 0);
+
+    contract.context.block_timestamp = 100;
+    testing_env!(contract.context.clone());
+    contract.contract.sloth_ready(pr_id_str(0));
+    assert_eq!(prs_opened(&contract), 1);
+    let pr: PRWithRating = contract
+        .contract
+        .prs
+        .get(&pr_id_str(0))
+        .cloned()
+        .unwrap()
+        .into();
+    assert_eq!(pr.created_at, 0);
+
+    // Toggling the draft doesn't count the PR again
+    contract.contract.sloth_draft(pr_id_str(0));
+    assert_eq!(prs_opened(&contract), 1);
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(info.pending);
+    assert_eq!(
+        contract
+            .contract
+            .pending_prs(vec![pr_id_str(0), pr_id_str(1)]),
+        vec![pr_id_str(0)]
+    );
+    contract.contract.sloth_ready(pr_id_str(0));
+    contract.contract.sloth_draft(pr_id_str(0));
+    contract.contract.sloth_ready(pr_id_str(0));
+    assert_eq!(prs_opened(&contract), 1);
+    assert_eq!(contract.contract.stats().prs.opened, 1);
+
+    // Stale draft that was counted is taken back once
+    contract.contract.sloth_draft(pr_id_str(0));
+    contract.contract.sloth_stale(pr_id_str(0), None);
+    assert_eq!(prs_opened(&contract), 0);
+}
+
+#[test]
+fn stale_pending_draft_is_not_taken_back() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(1, 1, 0);
+    contract.contract.sloth_include(
+        "NEAR-DevHub".to_owned(),
+        "devbot".to_string(),
+        github_handle(1),
+        0,
+        0,
+        true,
+        None,
+        Some(true),
+        None,
+    );
+    contract.contract.sloth_stale(pr_id_str(0), None);
+
+    let prs_opened = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap()
+        .period_data[0]
+        .1
+        .prs_opened;
+    assert_eq!(prs_opened, 1);
+}
+
+#[test]
+fn reopened_pr_keeps_votes() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 5);
+    contract.contract.sloth_stale(pr_id_str(0), Some(true));
+
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(!info.exist);
+    assert!(info.closed);
+
+    contract.contract.sloth_reopen(pr_id_str(0));
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(info.exist);
+    assert!(!info.closed);
+    assert_eq!(info.votes.len(), 1);
+}
+
+#[test]
+fn reverted_pr_loses_rating() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    contract.contract.sloth_revert(pr_id_str(0));
+
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.executed_prs, 0);
+    assert_eq!(user.period_data[0].1.total_score, 0);
+    assert_eq!(user.period_data[0].1.total_rating, 0);
+    assert_eq!(user.period_data[0].1.prs_merged, 0);
+    assert_eq!(user.period_data[0].1.prs_opened, 0);
+    // Weekly streak of the opened PRs is taken back, as well as the first PR achievement
+    assert_eq!(user.streaks[0].1.amount, 0);
+    assert!(user.achievements.is_empty());
+
+    let stats = contract.contract.stats().prs;
+    assert_eq!(stats.merged, 0);
+    assert_eq!(stats.executed, 0);
+    assert_eq!(stats.total_rating, 0);
+
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(!info.exist);
+    assert!(info.excluded);
+    assert_eq!(contract.contract.reverted_prs(0, 50).len(), 1);
+}
+
+#[test]
+fn reverted_pr_repo_is_counted_from_other_prs() {
+    let mut contract = ContractExt::new();
+
+    for i in 0..2 {
+        contract.include_sloth_common_repo(0, i, 0);
+        contract.merge(i, 10);
+    }
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+    contract.finalize(1);
+
+    let repos = |contract: &ContractExt| {
+        let progress: UserAchievements = contract
+            .contract
+            .user_achievements
+            .get(&0)
+            .cloned()
+            .unwrap()
+            .into();
+        progress.repos
+    };
+
+    // The other PR is still in the repo
+    contract.contract.sloth_revert(pr_id_str(1));
+    assert_eq!(repos(&contract), vec!["NEAR-DevHub/devbot".to_string()]);
+
+    contract.contract.sloth_revert(pr_id_str(0));
+    assert!(repos(&contract).is_empty());
+}
+
+#[test]
+fn reverted_pr_takes_back_referral_bonus() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 1);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+    let rating_before = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap()
+        .period_data[0]
+        .1
+        .total_rating;
+
+    contract.include_sloth_common_repo(2, 1, SCORE_TIMEOUT_IN_NANOSECONDS + 2);
+    contract
+        .contract
+        .sloth_referred_by(github_handle(2), github_handle(0));
+    contract.score(1, 1, 5);
+    contract.merge(1, SCORE_TIMEOUT_IN_NANOSECONDS + 3);
+    contract.context.block_timestamp = 2 * SCORE_TIMEOUT_IN_NANOSECONDS + 4;
+    testing_env!(contract.context.clone());
+    contract.finalize(1);
+    let stats_before = contract.contract.stats().prs.total_rating;
+    let pr: PRWithRating = contract
+        .contract
+        .executed_prs
+        .get(&pr_id_str(1))
+        .cloned()
+        .unwrap()
+        .into();
+
+    contract.contract.sloth_revert(pr_id_str(1));
+
+    let referrer = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(referrer.period_data[0].1.total_rating, rating_before);
+    assert_eq!(
+        contract.contract.stats().prs.total_rating,
+        stats_before - (pr.rating() + REFERRAL_BONUS_RATING) as u64
+    );
+    assert!(contract
+        .contract
+        .referral(github_handle(2))
+        .unwrap()
+        .rewarded
+        .is_none());
+}
+
+#[test]
+fn reopened_draft_stays_pending() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_include(
+        "NEAR-DevHub".to_owned(),
+        "devbot".to_string(),
+        github_handle(0),
+        0,
+        0,
+        true,
+        None,
+        Some(true),
+        None,
+    );
+    contract.contract.sloth_stale(pr_id_str(0), Some(true));
+    contract.contract.sloth_reopen(pr_id_str(0));
+
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(info.exist);
+    assert!(info.pending);
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.prs_opened, 0);
+}
+
+#[test]
+fn prune_drops_old_closed_prs() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract
+        .contract
+        .sloth_scored(pr_id_str(0), github_handle(1), 5, Some("Nice".to_string()));
+    contract.contract.sloth_stale(pr_id_str(0), Some(true));
+
+    // Fresh closed PRs can still be reopened
+    assert_eq!(contract.contract.sloth_prune(vec![pr_id_str(0)]), 0);
+
+    contract.context.block_timestamp = archive::ARCHIVE_AFTER_IN_NANOSECONDS + 1;
+    testing_env!(contract.context.clone());
+    assert_eq!(contract.contract.sloth_prune(vec![pr_id_str(0)]), 1);
+
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(!info.closed);
+    assert!(contract
+        .contract
+        .score_reasons
+        .get(&(pr_id_str(0), github_handle(1)))
+        .is_none());
+}
+
+#[test]
+fn moved_repo_keeps_prs() {
+    let mut contract = ContractExt::new();
+
+    let include = |contract: &mut ContractExt, repo: &str, pr_id: u64| {
+        contract.contract.sloth_include(
+            "NEAR-DevHub".to_owned(),
+            repo.to_string(),
+            github_handle(0),
+            pr_id,
+            0,
+            true,
+            None,
+            None,
+            Some(7),
+        );
+    };
+
+    include(&mut contract, "devbot", 0);
+    contract.contract.sloth_scored(
+        pr_id_str(0),
+        github_handle(1),
+        8,
+        Some("Nice work".to_string()),
+    );
+    include(&mut contract, "devbot", 1);
+    contract.merge(1, 10);
+
+    contract.contract.sloth_move_repo(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        "near".to_string(),
+        "sloth-bot".to_string(),
+        7,
+    );
+
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(!info.exist);
+    assert!(!info.allowed_repo);
+    let info = contract
+        .contract
+        .check_info("near".to_string(), "sloth-bot".to_string(), 0);
+    assert!(info.exist);
+    assert!(info.allowed_repo);
+    assert!(info.votes[0].reason_hash.is_some());
+    assert_eq!(
+        contract
+            .contract
+            .prs_by_author(github_handle(0), None, 50)
+            .len(),
+        2
+    );
+    assert_eq!(
+        contract.contract.repo_ids(),
+        vec![(7, "near".to_string(), "sloth-bot".to_string())]
+    );
+
+    // The merge queue follows the new name
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract
+        .contract
+        .sloth_finalize("near/sloth-bot/1".to_string(), None);
+    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 0);
+}
+
+#[test]
+fn moved_repo_in_batches() {
+    let mut contract = ContractExt::new();
+
+    let prs = repos::MOVE_REPO_BATCH_SIZE as u64 + 2;
+    for pr_id in 0..prs {
+        contract.include_sloth_common_repo(0, pr_id, 0);
+    }
+    contract.exclude(0);
+    contract.contract.sloth_stale(pr_id_str(1), Some(true));
+    contract.contract.set_score_timeout(
+        "NEAR-DevHub".to_string(),
+        Some(MIN_SCORE_TIMEOUT_IN_NANOSECONDS),
+    );
+
+    let mut calls = 0;
+    while contract.contract.sloth_move_repo(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        "near".to_string(),
+        "devbot".to_string(),
+        7,
+    ) > 0
+    {
+        // The permission isn't moved twice, so the repo stays allowed between the batches
+        let info = contract
+            .contract
+            .check_info("near".to_string(), "devbot".to_string(), 2);
+        assert!(info.allowed_repo);
+        calls += 1;
+    }
+    assert_eq!(calls, 1);
+
+    let info = contract
+        .contract
+        .check_info("near".to_string(), "devbot".to_string(), 0);
+    assert!(info.excluded);
+    let info = contract
+        .contract
+        .check_info("near".to_string(), "devbot".to_string(), 1);
+    assert!(info.closed);
+    contract.contract.sloth_reopen("near/devbot/1".to_string());
+    assert_eq!(
+        contract
+            .contract
+            .prs_by_repo("near".to_string(), "devbot".to_string(), None, 100)
+            .len() as u64,
+        prs - 1
+    );
+
+    let stats = contract.contract.stats();
+    let organization = |name: &str| {
+        stats
+            .organizations
+            .iter()
+            .find(|(organization, _)| organization == name)
+            .map(|(_, stats)| stats.clone())
+            .unwrap()
+    };
+    assert_eq!(organization("NEAR-DevHub").opened, 0);
+    assert_eq!(organization("NEAR-DevHub").excluded, 0);
+    assert_eq!(organization("near").opened, prs as u32);
+    assert_eq!(organization("near").excluded, 1);
+    assert_eq!(
+        contract.contract.score_timeout("near".to_string()),
+        MIN_SCORE_TIMEOUT_IN_NANOSECONDS
+    );
+}
@@ -3,8 +3,9 @@ use std::collections::HashSet;
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
-    NearSchema,
+    NearSchema, Timestamp,
 };
+use shared::VersionedPR;
 
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
 #[serde(crate = "near_sdk::serde")]
@@ -29,6 +30,12 @@ impl VersionedOrganization {
             VersionedOrganization::V1(org) => org.is_allowed(repo),
         }
     }
+
+    pub fn is_listed(&self, repo: &str) -> bool {
+        match self {
+            VersionedOrganization::V1(org) => org.is_listed(repo),
+        }
+    }
 }
 
 impl From<VersionedOrganization> for Organization {
@@ -76,6 +83,22 @@ impl Organization {
         };
     }
 
+    // Drops the repo from the list, so it falls back to the default of the permission model
+    pub fn forget(&mut self, repo: &str) {
+        match &mut self.all {
+            PermissionModel::Allowlist(allowlist) => allowlist.remove(repo),
+            PermissionModel::Blocklist(blocklist) => blocklist.remove(repo),
+        };
+    }
+
+    // Whether the repo has its own entry, instead of the default of the permission model
+    pub fn is_listed(&self, repo: &str) -> bool {
+        match &self.all {
+            PermissionModel::Allowlist(allowlist) => allowlist.contains(repo),
+            PermissionModel::Blocklist(blocklist) => blocklist.contains(repo),
+        }
+    }
+
     pub fn is_allowed(&self, repo: &str) -> bool {
         match &self.all {
             PermissionModel::Allowlist(allowlist) => allowlist.contains(repo),
@@ -90,3 +113,12 @@ impl Organization {
         }
     }
 }
+
+// Closed PR is kept with its votes and the draft state, so it's restored as it was on reopen
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ClosedPR {
+    pub pr: VersionedPR,
+    pub pending: bool,
+    pub closed_at: Timestamp,
+}
@@ -1,5 +1,5 @@
 use near_sdk::near_bindgen;
-use shared::{PRInfo, PRWithRating, User, UserId, UserPeriodData};
+use shared::{Achievement, PRInfo, PRWithRating, User, UserAchievements, UserId, UserPeriodData};
 
 use super::*;
 
@@ -9,7 +9,8 @@ impl Contract {
         let pr_id = format!("{}/{}/{}", organization, repo, issue_id);
         let executed_pr = self.executed_prs.get(&pr_id);
         let pr = self.prs.get(&pr_id).or(executed_pr);
-        let pr: Option<PRWithRating> = pr.cloned().map(|pr| pr.into());
+        let pr: Option<PRWithRating> = pr.cloned().map(|pr| self.with_score_reasons(pr.into()));
+        let score_timeout = self.score_timeout(organization.clone());
         let organization = self.organizations.get(&organization);
         PRInfo {
             allowed_org: organization.is_some(),
@@ -24,43 +25,158 @@ impl Contract {
             executed: executed_pr.is_some(),
             excluded: self.excluded_prs.contains(&pr_id),
             votes: pr.as_ref().map(|pr| pr.score.clone()).unwrap_or_default(),
+            score_timeout,
+            snoozed_until: self.snoozed_prs.get(&pr_id).copied(),
+            pending: self.pending_prs.contains(&pr_id) || self.drafted_prs.contains(&pr_id),
+            closed: self.closed_prs.contains_key(&pr_id),
         }
     }
 
     /// Returns a list of PRs with the execution status
     pub fn prs(&self, limit: u64, page: u64) -> Vec<(PRWithRating, bool)> {
-        self.prs
-            .into_iter()
-            .chain(self.executed_prs.iter())
+        if !self.is_index_complete() {
+            return self
+                .prs
+                .iter()
+                .chain(self.executed_prs.iter())
+                .skip((page * limit) as usize)
+                .take(limit as usize)
+                .filter_map(|(pr_id, _)| self.pr_with_status(pr_id))
+                .collect();
+        }
+
+        self.pr_index
+            .iter()
             .skip((page * limit) as usize)
             .take(limit as usize)
-            .map(|(id, pr)| (pr.clone().into(), !self.prs.contains_key(id)))
+            .filter_map(|pr_id| self.pr_with_status(pr_id))
             .collect()
     }
 
     pub fn unmerged_prs(&self, page: u64, limit: u64) -> Vec<PRWithRating> {
-        self.prs
-            .values()
-            .filter(|pr| !pr.is_merged())
+        if !self.is_index_complete() {
+            return self
+                .prs
+                .values()
+                .filter(|pr| !pr.is_merged())
+                .skip((page * limit) as usize)
+                .take(limit as usize)
+                .cloned()
+                .map(Into::into)
+                .collect();
+        }
+
+        self.unmerged_index
+            .iter()
             .skip((page * limit) as usize)
             .take(limit as usize)
+            .filter_map(|pr_id| self.prs.get(pr_id))
             .cloned()
             .map(Into::into)
             .collect()
     }
 
+    /// Returns the given PRs that are drafts, so the draft transitions are checked in one call
+    pub fn pending_prs(&self, pr_ids: Vec<PRId>) -> Vec<PRId> {
+        pr_ids
+            .into_iter()
+            .filter(|pr_id| self.pending_prs.contains(pr_id) || self.drafted_prs.contains(pr_id))
+            .collect()
+    }
+
+    /// Returns ids and revert timestamps of the reverted PRs, they are kept until pruned
+    pub fn reverted_prs(&self, page: u64, limit: u64) -> Vec<(PRId, Timestamp)> {
+        self.reverted_prs
+            .iter()
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .map(|(id, reverted_at)| (id.clone(), *reverted_at))
+            .collect()
+    }
+
+    /// Returns ids and merge timestamps of the PRs that are not indexed yet among the `page` of the stored PRs.
+    /// The page may have no such PRs, `None` is returned once the page is past the stored PRs.
+    pub fn unindexed_prs(&self, page: u64, limit: u64) -> Option<Vec<(PRId, Option<Timestamp>)>> {
+        let stored = self.prs.len() as u64 + self.executed_prs.len() as u64;
+        if page * limit >= stored {
+            return None;
+        }
+
+        let prs = self
+            .prs
+            .iter()
+            .chain(self.executed_prs.iter())
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .filter(|(id, _)| !self.pr_index.contains(*id))
+            .map(|(id, pr)| (id.clone(), PRWithRating::from(pr.clone()).merged_at))
+            .collect();
+        Some(prs)
+    }
+
     pub fn unfinalized_prs(&self, page: u64, limit: u64) -> Vec<PRWithRating> {
         let timestamp = env::block_timestamp();
-        self.prs
-            .values()
-            .filter(|pr| pr.is_ready_to_move(timestamp))
+        let is_ready = |pr: &PRWithRating| {
+            pr.is_ready_to_move(timestamp, self.score_timeout(pr.organization.clone()))
+        };
+        if !self.is_index_complete() {
+            return self
+                .prs
+                .values()
+                .cloned()
+                .map(PRWithRating::from)
+                .filter(is_ready)
+                .skip((page * limit) as usize)
+                .take(limit as usize)
+                .collect();
+        }
+
+        (self.merge_queue_head..self.merge_queue_tail)
+            .filter_map(|index| self.merge_queue.get(&index))
+            .filter_map(|pr_id| self.prs.get(pr_id))
+            .cloned()
+            .map(PRWithRating::from)
+            .filter(is_ready)
             .skip((page * limit) as usize)
             .take(limit as usize)
-            .cloned()
-            .map(Into::into)
             .collect()
     }
 
+    pub fn score_timeout(&self, organization: String) -> Timestamp {
+        self.score_timeouts
+            .get(&organization)
+            .copied()
+            .unwrap_or(SCORE_TIMEOUT_IN_NANOSECONDS)
+    }
+
+    /// Returns PRs of the user with the execution status ordered by the id, following the `from_key` PR id
+    pub fn prs_by_author(
+        &self,
+        user: GithubHandle,
+        from_key: Option<PRId>,
+        limit: u64,
+    ) -> Vec<(PRWithRating, bool)> {
+        let Some(user_id) = self.account_ids.get(&user) else {
+            return vec![];
+        };
+        self.paginate_prs(self.prs_by_author.get(user_id), from_key, limit)
+    }
+
+    /// Returns PRs of the repository with the execution status ordered by the id, following the `from_key` PR id
+    pub fn prs_by_repo(
+        &self,
+        organization: String,
+        repo: String,
+        from_key: Option<PRId>,
+        limit: u64,
+    ) -> Vec<(PRWithRating, bool)> {
+        self.paginate_prs(self.prs_by_repo.get(&(organization, repo)), from_key, limit)
+    }
+
+    pub fn streaks(&self) -> Vec<Streak> {
+        self.streaks.iter().cloned().map(Into::into).collect()
+    }
+
     pub fn user_streaks(&self, user_id: UserId) -> Vec<(StreakId, StreakUserData)> {
         self.streaks
             .into_iter()
@@ -80,12 +196,28 @@ impl Contract {
             .map(Into::into)
     }
 
+    pub fn referral(&self, user: GithubHandle) -> Option<Referral> {
+        let user_id = self.account_ids.get(&user)?;
+        self.referrals.get(user_id).cloned().map(Into::into)
+    }
+
+    pub fn achievements(&self, user_id: UserId) -> Vec<(Achievement, Timestamp)> {
+        self.user_achievements
+            .get(&user_id)
+            .cloned()
+            .map(|achievements| UserAchievements::from(achievements).unlocked)
+            .unwrap_or_default()
+    }
+
     pub fn user(&self, user: &String, periods: Vec<TimePeriodString>) -> Option<User> {
         let user = *self.account_ids.get(user)?;
         self.user_by_id(user, periods)
     }
 
     pub fn user_by_id(&self, user_id: UserId, periods: Vec<TimePeriodString>) -> Option<User> {
+        if !self.is_live_account(user_id) || self.opted_out.contains(&user_id) {
+            return None;
+        }
         let u: AccountWithPermanentPercentageBonus = self.users.get(user_id)?.clone().into();
         let percentage_bonus = u.lifetime_percentage_bonus();
 
@@ -103,13 +235,28 @@ impl Contract {
                 })
                 .collect(),
             streaks: self.user_streaks(user_id),
+            achievements: self.achievements(user_id),
         })
     }
 
-    pub fn users(&self, limit: u64, page: u64, periods: Vec<TimePeriodString>) -> Vec<User> {
-        (page * limit..(page + 1) * limit)
+    /// Returns the active users among the `page` of the account ids.
+    /// Opted out and merged accounts are skipped, so the page may be empty,
+    /// `None` is returned once the page is past the accounts.
+    pub fn users(
+        &self,
+        limit: u64,
+        page: u64,
+        periods: Vec<TimePeriodString>,
+    ) -> Option<Vec<User>> {
+        let accounts = self.users.len() as u64;
+        if page * limit >= accounts {
+            return None;
+        }
+
+        let users = (page * limit..((page + 1) * limit).min(accounts))
             .filter_map(|user_id| self.user_by_id(user_id as UserId, periods.clone()))
-            .collect()
+            .collect();
+        Some(users)
     }
 
     // TODO: remove this method after we would have enough data in the PRs
@@ -0,0 +1,7 @@
+-- Rating with exponential decay over executed PRs, recalculated by the server
+ALTER TABLE
+    users
+ADD
+    COLUMN active_rating INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_users_active_rating ON users (active_rating);
@@ -0,0 +1,14 @@
+-- Teams are registered in the contract, members are approved by the maintainers of the owning organization
+CREATE TABLE IF NOT EXISTS teams (
+    id SERIAL PRIMARY KEY,
+    name TEXT UNIQUE NOT NULL,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE
+);
+
+-- User can be a member of a single team
+CREATE TABLE IF NOT EXISTS team_members (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    team_id INTEGER NOT NULL REFERENCES teams(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_team_members_team_id ON team_members (team_id);
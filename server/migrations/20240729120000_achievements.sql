@@ -0,0 +1,7 @@
+-- One-off achievements unlocked in the contract on PR finalization
+CREATE TABLE IF NOT EXISTS user_achievements (
+    user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    achievement TEXT NOT NULL,
+    unlocked_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (user_id, achievement)
+);
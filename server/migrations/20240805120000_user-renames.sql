@@ -0,0 +1,5 @@
+-- Accounts merged after the GitHub login renames are removed together with their cache
+ALTER TABLE user_cached_metadata
+    DROP CONSTRAINT user_cached_metadata_user_id_fkey,
+    ADD CONSTRAINT user_cached_metadata_user_id_fkey
+        FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
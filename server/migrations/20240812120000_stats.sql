@@ -0,0 +1,9 @@
+-- PR stats per organization are maintained by the contract and pulled as is
+CREATE TABLE IF NOT EXISTS organization_stats (
+    organization_id INTEGER PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    prs_opened INTEGER NOT NULL,
+    prs_merged INTEGER NOT NULL,
+    prs_executed INTEGER NOT NULL,
+    prs_excluded INTEGER NOT NULL,
+    total_rating INTEGER NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Scores of every reviewer, the contract keeps only the sha256 of the reason
+CREATE TABLE IF NOT EXISTS pull_request_scores (
+    pull_request_id INTEGER NOT NULL REFERENCES pull_requests(id) ON DELETE CASCADE,
+    reviewer TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    reason_hash TEXT,
+    PRIMARY KEY (pull_request_id, reviewer)
+);
+
+-- Texts of the reasons are accepted only if their hash is on-chain.
+-- They are kept apart, as the active PRs are recreated on every sync
+CREATE TABLE IF NOT EXISTS score_reasons (
+    reason_hash TEXT PRIMARY KEY,
+    reason TEXT NOT NULL
+);
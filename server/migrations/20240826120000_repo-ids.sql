@@ -0,0 +1,2 @@
+-- Immutable GitHub id, so the renamed and transferred repos keep their PRs
+ALTER TABLE repos ADD COLUMN IF NOT EXISTS github_id BIGINT UNIQUE;
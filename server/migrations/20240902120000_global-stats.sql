@@ -0,0 +1,13 @@
+-- The total rating doesn't fit into INTEGER for the big organizations
+ALTER TABLE organization_stats ALTER COLUMN total_rating TYPE BIGINT;
+
+-- Global stats of the contract, the organization stats don't add up to them after the repo moves
+CREATE TABLE IF NOT EXISTS global_stats (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    users INTEGER NOT NULL,
+    prs_opened INTEGER NOT NULL,
+    prs_merged INTEGER NOT NULL,
+    prs_executed INTEGER NOT NULL,
+    prs_excluded INTEGER NOT NULL,
+    total_rating BIGINT NOT NULL
+);
@@ -10,12 +10,16 @@ SELECT
     best as streak_best,
     amount as streak_amount,
     user_period_data.total_rating as total_rating,
+    users.active_rating as active_rating,
     period as streak_type,
     streak.name as streak_name,
     latest_time_string as streak_latest_time_string,
     RANK() OVER (
         ORDER BY
-            total_rating DESC
+            CASE
+                WHEN $5 = 'active' THEN users.active_rating
+                ELSE user_period_data.total_rating
+            END DESC
     ) as place
 FROM
     user_period_data
@@ -0,0 +1,28 @@
+SELECT
+    teams.name,
+    o.login AS organization,
+    o.full_name AS organization_full_name,
+    COUNT(DISTINCT tm.user_id) AS members,
+    COALESCE(SUM(upd.total_score), 0) AS total_score,
+    COALESCE(SUM(upd.total_rating), 0) AS total_rating,
+    COALESCE(SUM(upd.prs_opened), 0) AS prs_opened,
+    COALESCE(SUM(upd.prs_merged), 0) AS prs_merged,
+    RANK() OVER (
+        ORDER BY
+            COALESCE(SUM(upd.total_rating), 0) DESC
+    ) AS place
+FROM
+    teams
+    JOIN organizations o ON o.id = teams.organization_id
+    JOIN team_members tm ON tm.team_id = teams.id
+    LEFT JOIN user_period_data upd ON upd.user_id = tm.user_id
+    AND upd.period_type = $1
+GROUP BY
+    teams.id,
+    o.login,
+    o.full_name
+ORDER BY
+    place,
+    teams.name
+LIMIT
+    $2 OFFSET $3
@@ -1,4 +1,5 @@
 SELECT
+    pr.id as id,
     o.login as organization_login,
     o.full_name as organization_full_name,
     r.name as repo,
@@ -18,6 +19,7 @@ FROM
 WHERE
     users.login = $1
 GROUP BY
+    pr.id,
     o.login,
     o.full_name,
     r.name,
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     sync::{atomic::AtomicBool, Arc},
     time::Duration,
 };
@@ -30,20 +31,50 @@ async fn fetch_and_store_users(near_client: &NearClient, db: &DB) -> anyhow::Res
             db.upsert_streak_user_data(&streak_data, streak_id as i32, user_id)
                 .await?;
         }
+        let mut achievements = Vec::with_capacity(user.achievements.len());
+        for (achievement, unlocked_at) in user.achievements {
+            db.upsert_user_achievement(
+                user_id,
+                achievement.name(),
+                DateTime::from_timestamp_nanos(unlocked_at as i64).naive_utc(),
+            )
+            .await?;
+            achievements.push(achievement.name().to_string());
+        }
+        db.remove_other_user_achievements(user_id, &achievements)
+            .await?;
     }
 
     Ok(())
 }
 
-async fn fetch_and_store_prs(near_client: &NearClient, db: &DB) -> anyhow::Result<()> {
+async fn fetch_and_store_prs(
+    near_client: &NearClient,
+    db: &DB,
+    opted_out: &HashSet<String>,
+) -> anyhow::Result<()> {
     let prs = near_client.prs().await?;
     // TODO: more efficient way to handle exclude and outdated PRs
-    db.clear_prs().await?;
+    db.clear_active_prs().await?;
+    // Executed PRs are kept, so the reverted ones are dropped explicitly
+    for pr_id in near_client.reverted_prs().await? {
+        let mut parts = pr_id.splitn(3, '/');
+        if let (Some(organization), Some(repo), Some(Ok(number))) = (
+            parts.next(),
+            parts.next(),
+            parts.next().map(str::parse::<i32>),
+        ) {
+            db.delete_pull_request(organization, repo, number).await?;
+        }
+    }
     for (pr, executed) in prs {
+        if opted_out.contains(&pr.author) {
+            continue;
+        }
         let organization_id = db.upsert_organization(&pr.organization).await?;
         let repo_id = db.upsert_repo(organization_id, &pr.repo).await?;
         let author_id = db.get_user_id(&pr.author).await?;
-        let _ = db
+        let pull_request_id = db
             .upsert_pull_request(
                 repo_id,
                 pr.number as i32,
@@ -58,11 +89,20 @@ async fn fetch_and_store_prs(near_client: &NearClient, db: &DB) -> anyhow::Resul
                 executed,
             )
             .await?;
+        db.replace_pull_request_scores(pull_request_id, &pr.score)
+            .await?;
     }
     Ok(())
 }
 
 async fn fetch_and_store_repos(near_client: &NearClient, db: &DB) -> anyhow::Result<()> {
+    // Goes first, so the renamed repos are found by the id before the new name is stored
+    for (github_id, organization, repo) in near_client.repo_ids().await? {
+        let organization_id = db.upsert_organization(&organization).await?;
+        db.upsert_repo_with_github_id(organization_id, &repo, github_id as i64)
+            .await?;
+    }
+
     let organizations = near_client.repos().await?;
     for org in organizations {
         let organization_id = db.upsert_organization(&org.organization).await?;
@@ -73,17 +113,70 @@ async fn fetch_and_store_repos(near_client: &NearClient, db: &DB) -> anyhow::Res
     Ok(())
 }
 
+async fn delete_opted_out_users(
+    near_client: &NearClient,
+    db: &DB,
+) -> anyhow::Result<HashSet<String>> {
+    let users = near_client.opted_out_users().await?;
+    for user in &users {
+        db.delete_user(user).await?;
+    }
+    Ok(users.into_iter().collect())
+}
+
+async fn fetch_and_store_stats(near_client: &NearClient, db: &DB) -> anyhow::Result<()> {
+    let stats = near_client.stats().await?;
+    db.upsert_global_stats(&stats).await?;
+    for (organization, stats) in stats.organizations {
+        let organization_id = db.upsert_organization(&organization).await?;
+        db.upsert_organization_stats(organization_id, &stats)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn fetch_and_store_teams(near_client: &NearClient, db: &DB) -> anyhow::Result<()> {
+    let teams = near_client.teams().await?;
+    for team in teams {
+        let organization_id = db.upsert_organization(&team.organization).await?;
+        let team_id = db.upsert_team(&team.name, organization_id).await?;
+        let mut user_ids = Vec::with_capacity(team.members.len());
+        for member in team.members {
+            let user_id = db.get_user_id(&member).await?;
+            db.upsert_team_member(team_id, user_id).await?;
+            user_ids.push(user_id);
+        }
+        db.remove_other_team_members(team_id, &user_ids).await?;
+    }
+    Ok(())
+}
+
 // TODO: more efficient way to fetch only updated data
-async fn fetch_and_store_all_data(near_client: &NearClient, db: &DB) -> anyhow::Result<()> {
+async fn fetch_and_store_all_data(
+    near_client: &NearClient,
+    db: &DB,
+    active_rating_half_life_in_days: u32,
+) -> anyhow::Result<()> {
     fetch_and_store_users(near_client, db).await?;
+    let opted_out = delete_opted_out_users(near_client, db).await?;
 
     fetch_and_store_repos(near_client, db).await?;
     // It matters that we fetch users first, because we need to know their IDs
-    fetch_and_store_prs(near_client, db).await?;
+    fetch_and_store_prs(near_client, db, &opted_out).await?;
+    fetch_and_store_teams(near_client, db).await?;
+    fetch_and_store_stats(near_client, db).await?;
+    // Active rating is derived from the PRs, so it goes after them
+    db.update_active_ratings(active_rating_half_life_in_days)
+        .await?;
     Ok(())
 }
 
-pub fn stage(client: NearClient, sleep_duration: Duration, atomic_bool: Arc<AtomicBool>) -> AdHoc {
+pub fn stage(
+    client: NearClient,
+    sleep_duration: Duration,
+    active_rating_half_life_in_days: u32,
+    atomic_bool: Arc<AtomicBool>,
+) -> AdHoc {
     rocket::fairing::AdHoc::on_liftoff("Load users from Near every X minutes", move |rocket| {
         Box::pin(async move {
             // Get an actual DB connection
@@ -98,7 +191,10 @@ pub fn stage(client: NearClient, sleep_duration: Duration, atomic_bool: Arc<Atom
                     interval.tick().await;
 
                     // Execute a query of some kind
-                    if let Err(e) = fetch_and_store_all_data(&near_client, &db).await {
+                    if let Err(e) =
+                        fetch_and_store_all_data(&near_client, &db, active_rating_half_life_in_days)
+                            .await
+                    {
                         rocket::error!("Failed to fetch and store data: {:#?}", e);
                     }
                 }
@@ -3,7 +3,7 @@ use rocket::{
     Build, Rocket,
 };
 use rocket_db_pools::Database;
-use shared::{StreakUserData, TimePeriod, TimePeriodString, UserPeriodData};
+use shared::{PRStats, Score, Stats, StreakUserData, TimePeriod, TimePeriodString, UserPeriodData};
 use sqlx::PgPool;
 
 #[derive(Database, Clone, Debug)]
@@ -12,32 +12,52 @@ pub struct DB(PgPool);
 
 pub mod types;
 
-use types::LeaderboardRecord;
+use types::{LeaderboardRanking, LeaderboardRecord};
 
 use self::types::{
-    RepoLeaderboardRecord, RepoRecord, StreakRecord, User, UserCachedMetadata,
+    AchievementRecord, OrganizationStatsRecord, PullRequestScoreRecord, RepoLeaderboardRecord,
+    RepoRecord, StatsRecord, StreakRecord, TeamLeaderboardRecord, User, UserCachedMetadata,
     UserContributionRecord, UserPeriodRecord, UserRecord,
 };
 
 impl DB {
     pub async fn upsert_user(&self, user_id: u32, user: &str, percent: u32) -> anyhow::Result<i32> {
-        // First try to update the user
+        // The login could belong to an account that was merged into this one after the rename.
+        // It's released first, the account is removed once its PRs are moved to this one.
+        let merged_ids: Vec<i32> = sqlx::query!(
+            r#"
+            UPDATE users
+            SET login = 'merged-' || id
+            WHERE login = $1 AND id <> $2
+            RETURNING id
+            "#,
+            user,
+            user_id as i32
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_iter()
+        .map(|rec| rec.id)
+        .collect();
+
+        // First try to update the user, following the login renames
         let rec = sqlx::query!(
             r#"
             UPDATE users
-            SET permanent_bonus = $2
+            SET permanent_bonus = $2, login = $3
             WHERE id = $1
             RETURNING id
             "#,
             user_id as i32,
-            percent as i32
+            percent as i32,
+            user
         )
         .fetch_optional(&self.0)
         .await?;
 
         // If the update did not find a matching row, insert the user
-        if let Some(record) = rec {
-            Ok(record.id)
+        let id = if let Some(record) = rec {
+            record.id
         } else {
             let rec = sqlx::query!(
                 r#"
@@ -52,9 +72,35 @@ impl DB {
             )
             .fetch_one(&self.0)
             .await?;
+            rec.id
+        };
 
-            Ok(rec.id)
+        if !merged_ids.is_empty() {
+            // Archived PRs aren't synced from the contract anymore, so they have to be kept
+            sqlx::query!(
+                r#"
+                UPDATE pull_requests
+                SET author_id = $1
+                WHERE author_id = ANY($2)
+                "#,
+                id,
+                &merged_ids
+            )
+            .execute(&self.0)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                DELETE FROM users
+                WHERE id = ANY($1)
+                "#,
+                &merged_ids
+            )
+            .execute(&self.0)
+            .await?;
         }
+
+        Ok(id)
     }
 
     pub async fn update_user_full_name(&self, user: &str, full_name: &str) -> anyhow::Result<()> {
@@ -161,6 +207,71 @@ impl DB {
         }
     }
 
+    // The repo with the same GitHub id takes the new name. The row created for the new name
+    // in the meantime is dropped, its PRs are stored again on the sync.
+    pub async fn upsert_repo_with_github_id(
+        &self,
+        organization_id: i32,
+        name: &str,
+        github_id: i64,
+    ) -> anyhow::Result<i32> {
+        let rec = sqlx::query!(
+            r#"
+            SELECT id
+            FROM repos
+            WHERE github_id = $1
+            "#,
+            github_id
+        )
+        .fetch_optional(&self.0)
+        .await?;
+
+        if let Some(record) = rec {
+            sqlx::query!(
+                r#"
+                DELETE FROM repos
+                WHERE organization_id = $1 AND name = $2 AND id <> $3
+                "#,
+                organization_id,
+                name,
+                record.id
+            )
+            .execute(&self.0)
+            .await?;
+            sqlx::query!(
+                r#"
+                UPDATE repos
+                SET organization_id = $1, name = $2
+                WHERE id = $3
+                "#,
+                organization_id,
+                name,
+                record.id
+            )
+            .execute(&self.0)
+            .await?;
+
+            Ok(record.id)
+        } else {
+            let rec = sqlx::query!(
+                r#"
+                INSERT INTO repos (organization_id, name, github_id)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (organization_id, name) DO UPDATE
+                SET github_id = $3
+                RETURNING id
+                "#,
+                organization_id,
+                name,
+                github_id
+            )
+            .fetch_one(&self.0)
+            .await?;
+
+            Ok(rec.id)
+        }
+    }
+
     pub async fn upsert_pull_request(
         &self,
         repo_id: i32,
@@ -223,6 +334,51 @@ impl DB {
         }
     }
 
+    // Scores are replaced on every sync, as the reviewers can change or remove them
+    pub async fn replace_pull_request_scores(
+        &self,
+        pull_request_id: i32,
+        scores: &[Score],
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "DELETE FROM pull_request_scores WHERE pull_request_id = $1",
+            pull_request_id
+        )
+        .execute(&self.0)
+        .await?;
+
+        for score in scores {
+            sqlx::query!(
+                r#"
+                INSERT INTO pull_request_scores (pull_request_id, reviewer, score, reason_hash)
+                VALUES ($1, $2, $3, $4)
+                "#,
+                pull_request_id,
+                score.user,
+                score.score as i32,
+                score.reason_hash.as_ref().map(String::from),
+            )
+            .execute(&self.0)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn upsert_score_reason(&self, reason_hash: &str, reason: &str) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO score_reasons (reason_hash, reason)
+            VALUES ($1, $2)
+            ON CONFLICT (reason_hash) DO NOTHING
+            "#,
+            reason_hash,
+            reason
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
     pub async fn upsert_user_period_data(
         &self,
         period: TimePeriodString,
@@ -317,6 +473,46 @@ impl DB {
         Ok(())
     }
 
+    pub async fn upsert_user_achievement(
+        &self,
+        user_id: i32,
+        achievement: &str,
+        unlocked_at: chrono::NaiveDateTime,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_achievements (user_id, achievement, unlocked_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, achievement) DO NOTHING
+            "#,
+            user_id,
+            achievement,
+            unlocked_at
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    // Drops the achievements that were taken back on-chain
+    pub async fn remove_other_user_achievements(
+        &self,
+        user_id: i32,
+        achievements: &[String],
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            DELETE FROM user_achievements
+            WHERE user_id = $1 AND NOT (achievement = ANY($2))
+            "#,
+            user_id,
+            achievements
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
     pub async fn update_repo_metadata(
         &self,
         repo_id: i32,
@@ -375,6 +571,19 @@ impl DB {
                 .fetch_all(&self.0)
                 .await?;
 
+        let achievement_recs: Vec<AchievementRecord> = sqlx::query_as!(
+            AchievementRecord,
+            r#"
+                SELECT achievement, unlocked_at
+                FROM user_achievements
+                WHERE user_id = $1
+                ORDER BY unlocked_at
+                "#,
+            user_rec,
+        )
+        .fetch_all(&self.0)
+        .await?;
+
         let mut leaderboard_places = Vec::with_capacity(place_strings.len());
         for place in place_strings {
             let record = self.get_leaderboard_place(place, user_rec).await?;
@@ -407,6 +616,7 @@ impl DB {
             lifetime_percent: percent,
             period_data: period_data_recs,
             streaks: streak_recs,
+            achievements: achievement_recs,
             leaderboard_places,
         };
 
@@ -417,6 +627,7 @@ impl DB {
         &self,
         period: &str,
         streak_id: i32,
+        ranking: LeaderboardRanking,
         page: i64,
         limit: i64,
     ) -> anyhow::Result<(Vec<LeaderboardRecord>, i64)> {
@@ -427,7 +638,8 @@ impl DB {
             period,
             streak_id,
             limit,
-            page * limit
+            page * limit,
+            ranking.as_str()
         )
         .fetch_all(&self.0)
         .await?;
@@ -446,6 +658,30 @@ impl DB {
         Ok((records, total_count.id.unwrap_or_default()))
     }
 
+    // Recalculates active rating of every user from the executed PRs,
+    // the weight of PR rating halves every `half_life_in_days` since the merge
+    pub async fn update_active_ratings(&self, half_life_in_days: u32) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET active_rating = decayed.rating
+            FROM (
+                SELECT users.id, COALESCE(SUM(
+                    pr.rating * POWER(0.5, EXTRACT(EPOCH FROM (now() - pr.merged_at)) / ($1::INTEGER * 86400))
+                ), 0)::INTEGER as rating
+                FROM users
+                LEFT JOIN pull_requests pr ON pr.author_id = users.id AND pr.executed AND pr.merged_at IS NOT NULL
+                GROUP BY users.id
+            ) as decayed
+            WHERE users.id = decayed.id
+            "#,
+            half_life_in_days as i32
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_leaderboard_place(
         &self,
         period: &str,
@@ -491,6 +727,85 @@ impl DB {
         Ok((records, total_count.id.unwrap_or_default() as u64))
     }
 
+    pub async fn upsert_team(&self, name: &str, organization_id: i32) -> anyhow::Result<i32> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO teams (name, organization_id)
+            VALUES ($1, $2)
+            ON CONFLICT (name) DO UPDATE SET organization_id = $2
+            RETURNING id
+            "#,
+            name,
+            organization_id
+        )
+        .fetch_one(&self.0)
+        .await?;
+
+        Ok(rec.id)
+    }
+
+    pub async fn upsert_team_member(&self, team_id: i32, user_id: i32) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO team_members (user_id, team_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET team_id = $2
+            "#,
+            user_id,
+            team_id
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    // Members who left the team or switched to another one
+    pub async fn remove_other_team_members(
+        &self,
+        team_id: i32,
+        user_ids: &[i32],
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            DELETE FROM team_members
+            WHERE team_id = $1 AND NOT (user_id = ANY($2))
+            "#,
+            team_id,
+            user_ids
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_team_leaderboard(
+        &self,
+        period: &str,
+        page: i64,
+        limit: i64,
+    ) -> anyhow::Result<(Vec<TeamLeaderboardRecord>, u64)> {
+        // Unchecked as rank() doesn't return NULL, but sqlx thinks it does
+        let records = sqlx::query_file_as_unchecked!(
+            TeamLeaderboardRecord,
+            "sql/get_team_leaderboard.sql",
+            period,
+            limit,
+            page * limit
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        let total_count = sqlx::query!(
+            r#"SELECT COUNT(DISTINCT(team_id)) as id
+            FROM team_members
+            "#,
+        )
+        .fetch_one(&self.0)
+        .await?;
+
+        Ok((records, total_count.id.unwrap_or_default() as u64))
+    }
+
     pub async fn get_user_contributions(
         &self,
         user: &str,
@@ -521,6 +836,28 @@ impl DB {
         Ok((records, total.id.unwrap_or_default() as u64))
     }
 
+    // The reason is empty if its text wasn't submitted yet
+    pub async fn get_pull_request_scores(
+        &self,
+        pull_request_ids: &[i32],
+    ) -> anyhow::Result<Vec<PullRequestScoreRecord>> {
+        let records = sqlx::query_as!(
+            PullRequestScoreRecord,
+            r#"
+            SELECT s.pull_request_id, s.reviewer, s.score, sr.reason as "reason?"
+            FROM pull_request_scores s
+            LEFT JOIN score_reasons sr ON sr.reason_hash = s.reason_hash
+            WHERE s.pull_request_id = ANY($1)
+            ORDER BY s.reviewer
+            "#,
+            pull_request_ids
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        Ok(records)
+    }
+
     pub async fn get_contributors_of_the_month(
         &self,
         repo: &str,
@@ -586,8 +923,42 @@ impl DB {
         Ok(rec)
     }
 
-    pub async fn clear_prs(&self) -> anyhow::Result<()> {
-        sqlx::query!("DELETE FROM pull_requests")
+    // Executed PRs are kept, as the contract archives the old ones
+    pub async fn clear_active_prs(&self) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM pull_requests WHERE executed = false")
+            .execute(&self.0)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_pull_request(
+        &self,
+        organization: &str,
+        repo: &str,
+        number: i32,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            DELETE FROM pull_requests
+            USING repos, organizations
+            WHERE pull_requests.repo_id = repos.id
+                AND repos.organization_id = organizations.id
+                AND organizations.login = $1
+                AND repos.name = $2
+                AND pull_requests.number = $3
+            "#,
+            organization,
+            repo,
+            number
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    // Cascades to all the user data, including PRs and the cached avatar
+    pub async fn delete_user(&self, login: &str) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM users WHERE login = $1", login)
             .execute(&self.0)
             .await?;
         Ok(())
@@ -662,6 +1033,76 @@ impl DB {
 
         Ok(())
     }
+
+    pub async fn upsert_organization_stats(
+        &self,
+        organization_id: i32,
+        stats: &PRStats,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO organization_stats (organization_id, prs_opened, prs_merged, prs_executed, prs_excluded, total_rating)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (organization_id) DO UPDATE
+            SET prs_opened = $2, prs_merged = $3, prs_executed = $4, prs_excluded = $5, total_rating = $6
+            "#,
+            organization_id,
+            stats.opened as i32,
+            stats.merged as i32,
+            stats.executed as i32,
+            stats.excluded as i32,
+            i64::try_from(stats.total_rating)?
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn upsert_global_stats(&self, stats: &Stats) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO global_stats (id, users, prs_opened, prs_merged, prs_executed, prs_excluded, total_rating)
+            VALUES (1, $1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO UPDATE
+            SET users = $1, prs_opened = $2, prs_merged = $3, prs_executed = $4, prs_excluded = $5, total_rating = $6
+            "#,
+            stats.users as i32,
+            stats.prs.opened as i32,
+            stats.prs.merged as i32,
+            stats.prs.executed as i32,
+            stats.prs.excluded as i32,
+            i64::try_from(stats.prs.total_rating)?
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_stats(&self) -> anyhow::Result<(StatsRecord, Vec<OrganizationStatsRecord>)> {
+        let stats = sqlx::query_as!(
+            StatsRecord,
+            r#"
+            SELECT users, prs_opened, prs_merged, prs_executed, prs_excluded, total_rating
+            FROM global_stats"#
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .unwrap_or_default();
+
+        let organizations = sqlx::query_as!(
+            OrganizationStatsRecord,
+            r#"
+            SELECT o.login as organization, o.full_name as organization_full_name,
+                s.prs_opened, s.prs_merged, s.prs_executed, s.prs_excluded, s.total_rating
+            FROM organization_stats s
+            JOIN organizations o ON s.organization_id = o.id
+            ORDER BY s.total_rating DESC"#
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        Ok((stats, organizations))
+    }
 }
 
 async fn run_migrations(rocket: Rocket<Build>) -> fairing::Result {
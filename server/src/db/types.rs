@@ -1,11 +1,44 @@
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 use shared::{GithubHandle, TimePeriod, TimePeriodString};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LeaderboardRanking {
+    #[default]
+    TotalRating,
+    // Rating with exponential decay, so inactive sloths fall behind over time
+    Active,
+}
+
+impl LeaderboardRanking {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardRanking::TotalRating => "total_rating",
+            LeaderboardRanking::Active => "active",
+        }
+    }
+}
+
+impl FromStr for LeaderboardRanking {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "total_rating" => Ok(LeaderboardRanking::TotalRating),
+            "active" => Ok(LeaderboardRanking::Active),
+            _ => Err(anyhow::anyhow!("Unknown leaderboard ranking: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
 pub struct LeaderboardRecord {
     pub login: String,
     pub full_name: Option<String>,
     pub total_score: i32,
     pub total_rating: i32,
+    pub active_rating: i32,
     pub period_type: TimePeriodString,
     pub executed_prs: i32,
     pub largest_score: i32,
@@ -40,6 +73,12 @@ pub struct StreakRecord {
     pub latest_time_string: String,
 }
 
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct AchievementRecord {
+    pub achievement: String,
+    pub unlocked_at: chrono::NaiveDateTime,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub login: String,
@@ -55,6 +94,7 @@ pub struct UserRecord {
     pub lifetime_percent: i32,
     pub period_data: Vec<UserPeriodRecord>,
     pub streaks: Vec<StreakRecord>,
+    pub achievements: Vec<AchievementRecord>,
     pub leaderboard_places: Vec<(String, u32)>,
 }
 
@@ -88,8 +128,22 @@ pub struct RepoLeaderboardRecord {
     pub forks: Option<i32>,
 }
 
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct TeamLeaderboardRecord {
+    pub name: String,
+    pub organization: String,
+    pub organization_full_name: Option<String>,
+    pub members: i64,
+    pub total_score: i64,
+    pub total_rating: i64,
+    pub prs_opened: i64,
+    pub prs_merged: i64,
+    pub place: i64,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
 pub struct UserContributionRecord {
+    pub id: i32,
     pub organization_login: String,
     pub organization_full_name: Option<String>,
     pub repo: String,
@@ -103,6 +157,14 @@ pub struct UserContributionRecord {
     pub merged_at: Option<chrono::NaiveDateTime>,
 }
 
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct PullRequestScoreRecord {
+    pub pull_request_id: i32,
+    pub reviewer: String,
+    pub score: i32,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
 pub struct RepoRecord {
     pub organization: String,
@@ -116,3 +178,24 @@ pub struct UserCachedMetadata {
     pub image_base64: String,
     pub load_time: chrono::NaiveDateTime,
 }
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize, Deserialize)]
+pub struct StatsRecord {
+    pub users: i32,
+    pub prs_opened: i32,
+    pub prs_merged: i32,
+    pub prs_executed: i32,
+    pub prs_excluded: i32,
+    pub total_rating: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct OrganizationStatsRecord {
+    pub organization: String,
+    pub organization_full_name: Option<String>,
+    pub prs_opened: i32,
+    pub prs_merged: i32,
+    pub prs_executed: i32,
+    pub prs_excluded: i32,
+    pub total_rating: i64,
+}
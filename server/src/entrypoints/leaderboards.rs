@@ -1,26 +1,35 @@
-use race_of_sloths_server::db::DB;
+use race_of_sloths_server::db::{types::LeaderboardRanking, DB};
 use rocket::{serde::json::Json, State};
 use shared::TimePeriod;
 
-use super::types::{LeaderboardResponse, PaginatedResponse, RepoResponse};
+use super::types::{LeaderboardResponse, PaginatedResponse, RepoResponse, TeamLeaderboardResponse};
 
 #[utoipa::path(context_path = "/leaderboard", responses(
     (status = 200, description = "Get user leaderboard", body = PaginatedLeaderboardResponse)
 ))]
-#[get("/users/<period>?<page>&<limit>&<streak_id>")]
+#[get("/users/<period>?<page>&<limit>&<streak_id>&<ranking>")]
 async fn get_leaderboard(
     db: &State<DB>,
     period: Option<String>,
     page: Option<u64>,
     limit: Option<u64>,
     streak_id: Option<i32>,
+    ranking: Option<String>,
 ) -> Option<Json<PaginatedResponse<LeaderboardResponse>>> {
     let period = period.unwrap_or(TimePeriod::AllTime.time_string(0));
     let streak_id = streak_id.unwrap_or(0);
     let page = page.unwrap_or(0);
     let limit = limit.unwrap_or(50);
+    let ranking = match ranking.map(|r| r.parse::<LeaderboardRanking>()) {
+        None => LeaderboardRanking::default(),
+        Some(Ok(ranking)) => ranking,
+        Some(Err(e)) => {
+            rocket::info!("{e}, returning 404");
+            return None;
+        }
+    };
     let (records, total) = match db
-        .get_leaderboard(&period, streak_id, page as i64, limit as i64)
+        .get_leaderboard(&period, streak_id, ranking, page as i64, limit as i64)
         .await
     {
         Err(e) => {
@@ -63,8 +72,42 @@ async fn get_repos(
     )))
 }
 
+#[utoipa::path(context_path = "/leaderboard", responses(
+    (status = 200, description = "Get team leaderboard", body = PaginatedTeamLeaderboardResponse)
+))]
+#[get("/teams/<period>?<page>&<limit>")]
+async fn get_team_leaderboard(
+    db: &State<DB>,
+    period: Option<String>,
+    page: Option<u64>,
+    limit: Option<u64>,
+) -> Option<Json<PaginatedResponse<TeamLeaderboardResponse>>> {
+    let period = period.unwrap_or(TimePeriod::AllTime.time_string(0));
+    let page = page.unwrap_or(0);
+    let limit = limit.unwrap_or(50);
+    let (records, total) = match db
+        .get_team_leaderboard(&period, page as i64, limit as i64)
+        .await
+    {
+        Err(e) => {
+            rocket::error!("Failed to get team leaderboard: {period}: {e}");
+            return None;
+        }
+        Ok(value) => value,
+    };
+    Some(Json(PaginatedResponse::new(
+        records.into_iter().map(Into::into).collect(),
+        page + 1,
+        limit,
+        total,
+    )))
+}
+
 pub fn stage() -> rocket::fairing::AdHoc {
     rocket::fairing::AdHoc::on_ignite("Installing entrypoints", |rocket| async {
-        rocket.mount("/leaderboard", rocket::routes![get_repos, get_leaderboard,])
+        rocket.mount(
+            "/leaderboard",
+            rocket::routes![get_repos, get_leaderboard, get_team_leaderboard,],
+        )
     })
 }
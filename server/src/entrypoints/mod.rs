@@ -3,6 +3,8 @@ use utoipa::OpenApi;
 
 pub mod aliases;
 pub mod leaderboards;
+pub mod reasons;
+pub mod stats;
 pub mod types;
 pub mod user;
 
@@ -15,23 +17,34 @@ pub mod user;
     paths(
         leaderboards::get_leaderboard,
         leaderboards::get_repos,
+        leaderboards::get_team_leaderboard,
         user::get_user,
         user::get_user_contributions,
         user::get_badge,
+        stats::get_stats,
+        reasons::post_reason,
     ),
     components(schemas(
         types::PaginatedResponse<types::LeaderboardResponse>,
         types::PaginatedLeaderboardResponse,
         types::PaginatedResponse<types::RepoResponse>,
         types::PaginatedRepoResponse,
+        types::PaginatedResponse<types::TeamLeaderboardResponse>,
+        types::PaginatedTeamLeaderboardResponse,
         types::PaginatedResponse<types::UserContributionResponse>,
         types::PaginatedUserContributionResponse,
         types::UserContributionResponse,
+        types::ReviewResponse,
+        types::ScoreReasonRequest,
         types::LeaderboardResponse,
         types::RepoResponse,
+        types::TeamLeaderboardResponse,
         types::UserProfile,
         types::GithubMeta,
         types::Streak,
+        types::AchievementResponse,
+        types::StatsResponse,
+        types::OrganizationStatsResponse,
     )),
     tags(
         (name = "Race of Sloths", description = "Race of Sloths endpoints.")
@@ -44,6 +57,8 @@ pub fn stage() -> AdHoc {
         rocket
             .attach(user::stage())
             .attach(leaderboards::stage())
+            .attach(stats::stage())
+            .attach(reasons::stage())
             .attach(aliases::stage())
     })
 }
@@ -0,0 +1,60 @@
+use race_of_sloths_server::db::DB;
+use rocket::{http::Status, serde::json::Json, State};
+use shared::{near::NearClient, reason_hash};
+
+use super::types::ScoreReasonRequest;
+
+// The texts are public, so anyone can submit them, but only the ones matching the on-chain hash
+// of the reviewer's score are accepted
+#[utoipa::path(context_path = "/reasons", request_body = ScoreReasonRequest, responses(
+    (status = 200, description = "Reason is stored"),
+    (status = 400, description = "Reason doesn't match the score of the reviewer")
+))]
+#[post("/", data = "<request>")]
+async fn post_reason(
+    request: Json<ScoreReasonRequest>,
+    near: &State<NearClient>,
+    db: &State<DB>,
+) -> Status {
+    let info = match near
+        .check_info(&request.organization, &request.repo, request.number)
+        .await
+    {
+        Ok(info) => info,
+        Err(e) => {
+            rocket::error!(
+                "Failed to get PR info for {}/{}/{}: {e}",
+                request.organization,
+                request.repo,
+                request.number
+            );
+            return Status::InternalServerError;
+        }
+    };
+
+    let hash = reason_hash(&request.reason);
+    let matches = info
+        .votes
+        .iter()
+        .any(|vote| vote.user == request.reviewer && vote.reason_hash == Some(hash));
+    if !matches {
+        return Status::BadRequest;
+    }
+
+    match db
+        .upsert_score_reason(&String::from(&hash), &request.reason)
+        .await
+    {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            rocket::error!("Failed to store the score reason: {e}");
+            Status::InternalServerError
+        }
+    }
+}
+
+pub fn stage() -> rocket::fairing::AdHoc {
+    rocket::fairing::AdHoc::on_ignite("Installing entrypoints", |rocket| async {
+        rocket.mount("/reasons", rocket::routes![post_reason])
+    })
+}
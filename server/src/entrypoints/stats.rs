@@ -0,0 +1,24 @@
+use race_of_sloths_server::db::DB;
+use rocket::{serde::json::Json, State};
+
+use super::types::StatsResponse;
+
+#[utoipa::path(context_path = "/stats", responses(
+    (status = 200, description = "Get global stats of the race", body = StatsResponse)
+))]
+#[get("/")]
+async fn get_stats(db: &State<DB>) -> Option<Json<StatsResponse>> {
+    match db.get_stats().await {
+        Err(e) => {
+            rocket::error!("Failed to get stats: {e}");
+            None
+        }
+        Ok((stats, organizations)) => Some(Json(StatsResponse::new(stats, organizations))),
+    }
+}
+
+pub fn stage() -> rocket::fairing::AdHoc {
+    rocket::fairing::AdHoc::on_ignite("Installing entrypoints", |rocket| async {
+        rocket.mount("/stats", rocket::routes![get_stats])
+    })
+}
@@ -2,14 +2,15 @@ use std::collections::HashMap;
 
 use chrono::NaiveDateTime;
 use race_of_sloths_server::db::types::{
-    LeaderboardRecord, RepoLeaderboardRecord, UserContributionRecord, UserRecord,
+    AchievementRecord, LeaderboardRecord, OrganizationStatsRecord, PullRequestScoreRecord,
+    RepoLeaderboardRecord, StatsRecord, TeamLeaderboardRecord, UserContributionRecord, UserRecord,
 };
 use serde::{Deserialize, Serialize};
-use shared::TimePeriod;
+use shared::{Achievement, TimePeriod};
 use utoipa::ToSchema;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, ToSchema)]
-#[aliases(PaginatedLeaderboardResponse = PaginatedResponse<LeaderboardResponse>, PaginatedRepoResponse = PaginatedResponse<RepoResponse>, PaginatedUserContributionResponse = PaginatedResponse<UserContributionResponse>)]
+#[aliases(PaginatedLeaderboardResponse = PaginatedResponse<LeaderboardResponse>, PaginatedRepoResponse = PaginatedResponse<RepoResponse>, PaginatedTeamLeaderboardResponse = PaginatedResponse<TeamLeaderboardResponse>, PaginatedUserContributionResponse = PaginatedResponse<UserContributionResponse>)]
 pub struct PaginatedResponse<T: Serialize> {
     pub records: Vec<T>,
     pub page: u64,
@@ -83,6 +84,7 @@ impl From<RepoLeaderboardRecord> for RepoResponse {
 pub struct LeaderboardResponse {
     pub user: GithubMeta,
     pub rating: u32,
+    pub active_rating: u32,
     pub contributions: u32,
     pub streak: Streak,
     pub merged_prs: u32,
@@ -95,6 +97,7 @@ impl From<LeaderboardRecord> for LeaderboardResponse {
         Self {
             user: GithubMeta::new(record.login, record.full_name),
             rating: record.total_rating as u32,
+            active_rating: record.active_rating as u32,
             contributions: record.prs_opened as u32,
             streak: Streak::new(
                 record.streak_name,
@@ -110,6 +113,33 @@ impl From<LeaderboardRecord> for LeaderboardResponse {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct TeamLeaderboardResponse {
+    pub name: String,
+    pub organization: GithubMeta,
+    pub members: u32,
+    pub rating: u32,
+    pub contributions: u32,
+    pub merged_prs: u32,
+    pub score: u32,
+    pub place: u32,
+}
+
+impl From<TeamLeaderboardRecord> for TeamLeaderboardResponse {
+    fn from(record: TeamLeaderboardRecord) -> Self {
+        Self {
+            name: record.name,
+            organization: GithubMeta::new(record.organization, record.organization_full_name),
+            members: record.members as u32,
+            rating: record.total_rating as u32,
+            contributions: record.prs_opened as u32,
+            merged_prs: record.prs_merged as u32,
+            score: record.total_score as u32,
+            place: record.place as u32,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Default, ToSchema)]
 pub struct Streak {
     name: String,
@@ -180,6 +210,26 @@ impl Streak {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct AchievementResponse {
+    pub name: String,
+    pub title: String,
+    pub unlocked_at: NaiveDateTime,
+}
+
+impl From<AchievementRecord> for AchievementResponse {
+    fn from(record: AchievementRecord) -> Self {
+        let title = Achievement::from_name(&record.achievement)
+            .map(|achievement| achievement.title().to_string())
+            .unwrap_or_else(|| record.achievement.clone());
+        Self {
+            name: record.achievement,
+            title,
+            unlocked_at: record.unlocked_at,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserProfile {
     pub user_id: u32,
@@ -189,6 +239,7 @@ pub struct UserProfile {
     pub lifetime_bonus: u32,
     pub leaderboard_places: HashMap<String, u32>,
     pub streaks: Vec<Streak>,
+    pub achievements: Vec<AchievementResponse>,
     pub first_contribution: NaiveDateTime,
 }
 
@@ -222,6 +273,7 @@ impl From<UserRecord> for UserProfile {
                     )
                 })
                 .collect(),
+            achievements: record.achievements.into_iter().map(Into::into).collect(),
             leaderboard_places: record.leaderboard_places.into_iter().collect(),
             first_contribution: record.first_contribution,
         }
@@ -242,10 +294,11 @@ pub struct UserContributionResponse {
     pub streak_bonus_rating: i32,
     pub created_at: NaiveDateTime,
     pub merged_at: Option<NaiveDateTime>,
+    pub reviews: Vec<ReviewResponse>,
 }
 
-impl From<UserContributionRecord> for UserContributionResponse {
-    fn from(record: UserContributionRecord) -> Self {
+impl UserContributionResponse {
+    pub fn new(record: UserContributionRecord, reviews: Vec<ReviewResponse>) -> Self {
         let pull_request_link = format!(
             "https://github.com/{}/{}/pull/{}",
             record.organization_login, record.repo, record.number
@@ -274,6 +327,81 @@ impl From<UserContributionRecord> for UserContributionResponse {
             streak_bonus_rating: record.streak_bonus_rating,
             pr_number: record.number,
             executed: record.executed,
+            reviews,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReviewResponse {
+    pub reviewer: String,
+    pub score: i32,
+    pub reason: Option<String>,
+}
+
+impl From<PullRequestScoreRecord> for ReviewResponse {
+    fn from(record: PullRequestScoreRecord) -> Self {
+        Self {
+            reviewer: record.reviewer,
+            score: record.score,
+            reason: record.reason,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ScoreReasonRequest {
+    pub organization: String,
+    pub repo: String,
+    pub number: u64,
+    pub reviewer: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationStatsResponse {
+    pub organization: GithubMeta,
+    pub prs_opened: u32,
+    pub prs_merged: u32,
+    pub prs_executed: u32,
+    pub prs_excluded: u32,
+    pub total_rating: u64,
+}
+
+impl From<OrganizationStatsRecord> for OrganizationStatsResponse {
+    fn from(record: OrganizationStatsRecord) -> Self {
+        Self {
+            organization: GithubMeta::new(record.organization, record.organization_full_name),
+            prs_opened: record.prs_opened as u32,
+            prs_merged: record.prs_merged as u32,
+            prs_executed: record.prs_executed as u32,
+            prs_excluded: record.prs_excluded as u32,
+            total_rating: record.total_rating as u64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct StatsResponse {
+    pub users: u64,
+    pub prs_opened: u64,
+    pub prs_merged: u64,
+    pub prs_executed: u64,
+    pub prs_excluded: u64,
+    pub total_rating: u64,
+    pub organizations: Vec<OrganizationStatsResponse>,
+}
+
+impl StatsResponse {
+    pub fn new(stats: StatsRecord, organizations: Vec<OrganizationStatsRecord>) -> Self {
+        Self {
+            users: stats.users as u64,
+            prs_opened: stats.prs_opened as u64,
+            prs_merged: stats.prs_merged as u64,
+            prs_executed: stats.prs_executed as u64,
+            prs_excluded: stats.prs_excluded as u64,
+            total_rating: stats.total_rating as u64,
+            organizations: organizations.into_iter().map(Into::into).collect(),
         }
     }
 }
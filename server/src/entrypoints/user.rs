@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use base64::Engine;
 use http_body_util::BodyExt;
@@ -15,7 +15,7 @@ use rocket::{
 };
 use shared::TimePeriod;
 
-use super::types::{PaginatedResponse, UserContributionResponse, UserProfile};
+use super::types::{PaginatedResponse, ReviewResponse, UserContributionResponse, UserProfile};
 
 pub struct Badge {
     svg: Option<String>,
@@ -202,8 +202,32 @@ async fn get_user_contributions(
         }
         Ok(value) => value,
     };
+
+    let ids: Vec<i32> = repos.iter().map(|repo| repo.id).collect();
+    let mut reviews: HashMap<i32, Vec<ReviewResponse>> = HashMap::new();
+    match db.get_pull_request_scores(&ids).await {
+        Err(e) => {
+            rocket::error!("Failed to get reviews of user contributions: {username}: {e}");
+            return None;
+        }
+        Ok(scores) => {
+            for score in scores {
+                reviews
+                    .entry(score.pull_request_id)
+                    .or_default()
+                    .push(score.into());
+            }
+        }
+    }
+
     Some(Json(PaginatedResponse::new(
-        repos.into_iter().map(Into::into).collect(),
+        repos
+            .into_iter()
+            .map(|repo| {
+                let reviews = reviews.remove(&repo.id).unwrap_or_default();
+                UserContributionResponse::new(repo, reviews)
+            })
+            .collect(),
         page + 1,
         limit,
         total,
@@ -3,6 +3,7 @@ extern crate rocket;
 
 mod entrypoints;
 
+use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -21,6 +22,8 @@ pub struct Env {
     is_mainnet: bool,
     near_timeout_in_minutes: Option<u32>,
     github_timeout_in_minutes: Option<u32>,
+    // The active rating decays by the half-life, so a zero one is rejected on load
+    active_rating_half_life_in_days: Option<NonZeroU32>,
     github_token: String,
 }
 
@@ -58,9 +61,12 @@ async fn rocket() -> _ {
     rocket::build()
         .attach(cors)
         .attach(db::stage())
+        .manage(near_client.clone())
         .attach(contract_pull::stage(
             near_client,
             near_sleep,
+            env.active_rating_half_life_in_days
+                .map_or(90, NonZeroU32::get),
             atomic_bool.clone(),
         ))
         .attach(github_pull::stage(
@@ -59,6 +59,7 @@ pub fn generate_svg_bot_badge(
     let svg_icon = svg_icon.replace("{place}", &place);
     let svg_icon = svg_icon.replace("{place-type}", &place_type);
     let svg_icon = svg_icon.replace("{image}", &user_metadata.image_base64);
+    let svg_icon = process_achievements(svg_icon, &user_record, 422.0, 57.5, 20.0);
 
     postprocess_svg(svg_icon, fontdb)
 }
@@ -121,6 +122,7 @@ pub fn generate_svg_share_badge(
     let svg_icon = svg_icon.replace("{max-month-streak}", &month_streak.to_string());
     let svg_icon = svg_icon.replace("{place}", &place);
     let svg_icon = svg_icon.replace("{place-type}", &place_type);
+    let svg_icon = process_achievements(svg_icon, &user_record, 422.0, 49.5, 20.0);
 
     postprocess_svg(svg_icon, fontdb)
 }
@@ -171,7 +173,8 @@ pub fn generate_svg_meta_badge(
     let svg_icon = std::fs::read_to_string("./public/badge_meta_template.svg")?;
     let github_handle = format!("@{}", user_record.login);
 
-    let svg_icon = process_rank(svg_icon, &user_record)
+    let svg_icon = process_rank(svg_icon, &user_record);
+    let svg_icon = process_achievements(svg_icon, &user_record, 584.0, 97.5, 28.0)
         .replace(
             "{total-rating}",
             &total_period.total_rating.to_formatted_string(&Locale::en),
@@ -221,6 +224,33 @@ fn process_rank(svg_icon: String, user_record: &UserRecord) -> String {
         .replace("{rank-title}", &title)
 }
 
+// Renders unlocked achievements as a row of icons aligned to the right edge
+fn process_achievements(
+    svg_icon: String,
+    user_record: &UserRecord,
+    right: f64,
+    y: f64,
+    size: f64,
+) -> String {
+    let icons = user_record
+        .achievements
+        .iter()
+        .rev()
+        .filter_map(|record| {
+            std::fs::read_to_string(format!("./public/achievements/{}.svg", record.achievement))
+                .ok()
+        })
+        .enumerate()
+        .map(|(i, icon)| {
+            let x = right - (i + 1) as f64 * (size + 4.0) + 4.0;
+            format!(
+                r#"<svg width="{size}" height="{size}" viewBox="0 0 48 48" x="{x}" y="{y}" fill="none" xmlns="http://www.w3.org/2000/svg">{icon}</svg>"#
+            )
+        })
+        .collect::<String>();
+    svg_icon.replace("{achievements}", &icons)
+}
+
 fn rank_data(user_record: &UserRecord) -> (String, String, String) {
     let (rank, rank_svg_file, title) = match user_record.lifetime_percent {
         a if a >= 25 => ("Rust".to_string(), "rust.svg", "Rank"),
@@ -0,0 +1,149 @@
+use near_sdk::Timestamp;
+use strum::EnumIter;
+
+use super::*;
+
+pub const ACHIEVEMENT_REPOS_AMOUNT: usize = 5;
+pub const ACHIEVEMENT_YEAR_IN_NANOSECONDS: u64 = 365 * 24 * 60 * 60 * 1_000_000_000;
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    EnumIter,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum Achievement {
+    FirstPR,
+    TenthPR,
+    FirstMaxScore,
+    FiveRepos,
+    OneYearSloth,
+}
+
+impl Achievement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FirstPR => "first-pr",
+            Self::TenthPR => "tenth-pr",
+            Self::FirstMaxScore => "first-max-score",
+            Self::FiveRepos => "five-repos",
+            Self::OneYearSloth => "one-year-sloth",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::FirstPR => "First contribution",
+            Self::TenthPR => "10 contributions",
+            Self::FirstMaxScore => "First 13 score",
+            Self::FiveRepos => "Contributed to 5 repositories",
+            Self::OneYearSloth => "1 year sloth",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter().find(|achievement| achievement.name() == name)
+    }
+
+    pub fn is_achieved(
+        &self,
+        all_time: &UserPeriodData,
+        progress: &UserAchievements,
+        score: u32,
+        timestamp: Timestamp,
+    ) -> bool {
+        match self {
+            Self::FirstPR => all_time.executed_prs >= 1,
+            Self::TenthPR => all_time.executed_prs >= 10,
+            Self::FirstMaxScore => score == 13,
+            Self::FiveRepos => progress.repos.len() >= ACHIEVEMENT_REPOS_AMOUNT,
+            Self::OneYearSloth => progress
+                .first_contribution
+                .map(|first| timestamp.saturating_sub(first) >= ACHIEVEMENT_YEAR_IN_NANOSECONDS)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedUserAchievements {
+    V1(UserAchievements),
+}
+
+impl From<VersionedUserAchievements> for UserAchievements {
+    fn from(message: VersionedUserAchievements) -> Self {
+        match message {
+            VersionedUserAchievements::V1(x) => x,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Default,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct UserAchievements {
+    pub unlocked: Vec<(Achievement, Timestamp)>,
+    // Repositories are tracked only until the achievement is unlocked
+    pub repos: Vec<String>,
+    pub first_contribution: Option<Timestamp>,
+}
+
+impl UserAchievements {
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.iter().any(|(a, _)| *a == achievement)
+    }
+
+    pub fn add_repo(&mut self, repo: String) {
+        if self.repos.len() < ACHIEVEMENT_REPOS_AMOUNT && !self.repos.contains(&repo) {
+            self.repos.push(repo);
+        }
+    }
+
+    // Merges achievements of another account of the same contributor, keeping the earliest unlocks
+    pub fn merge(&mut self, other: UserAchievements) {
+        for (achievement, timestamp) in other.unlocked {
+            match self.unlocked.iter_mut().find(|(a, _)| *a == achievement) {
+                Some((_, existing)) => *existing = (*existing).min(timestamp),
+                None => self.unlocked.push((achievement, timestamp)),
+            }
+        }
+        for repo in other.repos {
+            self.add_repo(repo);
+        }
+        self.first_contribution = match (self.first_contribution, other.first_contribution) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    // Unlocks all the achieved achievements and returns the new ones
+    pub fn unlock(
+        &mut self,
+        all_time: &UserPeriodData,
+        score: u32,
+        timestamp: Timestamp,
+    ) -> Vec<Achievement> {
+        let achieved: Vec<Achievement> = Achievement::iter()
+            .filter(|a| !self.is_unlocked(*a))
+            .filter(|a| a.is_achieved(all_time, self, score, timestamp))
+            .collect();
+
+        for achievement in achieved.iter() {
+            self.unlocked.push((*achievement, timestamp));
+        }
+        achieved
+    }
+}
@@ -0,0 +1,127 @@
+use near_sdk::env;
+use strum::{AsRefStr, EnumIter};
+
+use super::*;
+
+// The code is appended to the panic message in brackets, so the client can parse the error back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, AsRefStr)]
+pub enum ContractError {
+    NotSloth,
+    NotOwner,
+    ContractPaused,
+    MethodPaused,
+    TooManyOperations,
+    OrganizationNotAllowed,
+    OrganizationAlreadyAllowed,
+    OrganizationNotFound,
+    RepoPaused,
+    ScoreTimeoutOutOfRange,
+    PrNotFound,
+    PrAlreadyExists,
+    PrExcluded,
+    MergedPrCannotBeExcluded,
+    MergedPrCannotBeStale,
+    SnoozeTooLong,
+    NotReadyToFinalize,
+    PrNotMerged,
+    UserNotFound,
+    UserOptedOut,
+    SameHandle,
+    SameRepo,
+    StreakNotFound,
+    SelfReferral,
+    ReferrerWithoutExecutedPrs,
+    ReferrerAlreadySet,
+    ReferralNotOnFirstPr,
+    AlreadyTeamMember,
+    NoTeamRequest,
+    TeamNotFound,
+    TeamOfAnotherOrganization,
+    AccountLinkNotRequested,
+    InsufficientDeposit,
+    Soulbound,
+}
+
+impl ContractError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            ContractError::NotSloth => "Only sloth can call this method",
+            ContractError::NotOwner => "Only owner can call this method",
+            ContractError::ContractPaused => "Contract is paused",
+            ContractError::MethodPaused => "Method is paused",
+            ContractError::TooManyOperations => "Too many operations in the batch",
+            ContractError::OrganizationNotAllowed => "Organization is not allowlisted",
+            ContractError::OrganizationAlreadyAllowed => "Organization already allowlisted",
+            ContractError::OrganizationNotFound => "Organization is not in the list",
+            ContractError::RepoPaused => "The repository is not allowlisted for the organization",
+            ContractError::ScoreTimeoutOutOfRange => "Score timeout is out of the allowed range",
+            ContractError::PrNotFound => "PR is not started or already executed",
+            ContractError::PrAlreadyExists => "PR already exists",
+            ContractError::PrExcluded => "Excluded PR cannot be included without override flag",
+            ContractError::MergedPrCannotBeExcluded => "Merged PR cannot be excluded",
+            ContractError::MergedPrCannotBeStale => "Merged PR cannot be stale",
+            ContractError::SnoozeTooLong => "PR cannot be snoozed for more than 30 days",
+            ContractError::NotReadyToFinalize => "PR is not ready to be finalized",
+            ContractError::PrNotMerged => "PR is not merged",
+            ContractError::UserNotFound => "User doesn't exist",
+            ContractError::UserOptedOut => "User opted out of the race",
+            ContractError::SameHandle => "User already has this handle",
+            ContractError::SameRepo => "Repository already has this name",
+            ContractError::StreakNotFound => "Streak doesn't exist",
+            ContractError::SelfReferral => "Self-referral is not allowed",
+            ContractError::ReferrerWithoutExecutedPrs => "Referrer has no executed PRs",
+            ContractError::ReferrerAlreadySet => "Referrer is already set",
+            ContractError::ReferralNotOnFirstPr => "Referrer can be set only on the first PR",
+            ContractError::AlreadyTeamMember => "User is already a member of the team",
+            ContractError::NoTeamRequest => "User didn't request to join a team",
+            ContractError::TeamNotFound => "Team doesn't exist",
+            ContractError::TeamOfAnotherOrganization => "Team is owned by another organization",
+            ContractError::AccountLinkNotRequested => {
+                "Account link wasn't requested by the account"
+            }
+            ContractError::InsufficientDeposit => "Attached deposit doesn't cover the storage",
+            ContractError::Soulbound => "Rank badges are soulbound",
+        }
+    }
+
+    pub fn panic(self) -> ! {
+        env::panic_str(&self.to_string())
+    }
+
+    // Panics with the more detailed message, keeping the error code
+    pub fn panic_with(self, message: String) -> ! {
+        env::panic_str(&format!("{message} [{}]", self.as_ref()))
+    }
+
+    pub fn from_panic(panic: &str) -> Option<Self> {
+        Self::iter().find(|error| panic.contains(&format!("[{}]", error.as_ref())))
+    }
+}
+
+impl std::fmt::Display for ContractError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.message(), self.as_ref())
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_is_parsed_from_panic() {
+        for error in ContractError::iter() {
+            let panic = format!("Smart contract panicked: {error}");
+            assert_eq!(ContractError::from_panic(&panic), Some(error));
+        }
+        assert_eq!(
+            ContractError::from_panic(
+                "Smart contract panicked: Method sloth_scored is paused [MethodPaused]"
+            ),
+            Some(ContractError::MethodPaused)
+        );
+        assert_eq!(ContractError::from_panic("Exceeded the prepaid gas"), None);
+    }
+}
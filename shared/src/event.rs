@@ -1,6 +1,6 @@
 use near_sdk::serde::{Deserialize, Serialize};
 
-use crate::StreakId;
+use crate::{Achievement, GithubHandle, PRWithRating, StreakId, TeamName};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
@@ -22,4 +22,26 @@ pub enum Event {
         applied_multiplier: u32,
         pr_number_this_week: u32,
     },
+    AchievementUnlocked {
+        achievement: Achievement,
+    },
+    ReferralRewarded {
+        referrer: GithubHandle,
+        bonus_rating: u32,
+    },
+    TeamJoined {
+        team: TeamName,
+        github_handle: GithubHandle,
+    },
+    PRArchived {
+        pr: PRWithRating,
+    },
+    PRReverted {
+        pr: PRWithRating,
+        rating: u32,
+    },
+    // Logged before each operation of the batch to split the events between them
+    BatchOperation {
+        index: u32,
+    },
 }
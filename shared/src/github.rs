@@ -1,9 +1,13 @@
+use std::collections::HashMap;
+
 use crate::PRWithRating;
-use octocrab::models::AuthorAssociation;
+use octocrab::models::{pulls::ReviewState, AuthorAssociation};
 
 #[derive(Debug, Clone)]
 pub struct User {
     pub login: String,
+    // Immutable GitHub id, that survives the login renames
+    pub id: Option<u64>,
     pub contributor_type: AuthorAssociation,
 }
 
@@ -11,10 +15,16 @@ impl User {
     pub fn new(login: String, contributor_type: AuthorAssociation) -> Self {
         Self {
             login,
+            id: None,
             contributor_type,
         }
     }
 
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
     pub fn is_participant(&self) -> bool {
         // https://docs.github.com/en/graphql/reference/enums#commentauthorassociation
         // We probably shouldn't allow collaborators / members / owners to get points
@@ -36,14 +46,70 @@ impl User {
 pub struct PrMetadata {
     pub owner: String,
     pub repo: String,
+    // Immutable GitHub id of the base repo, it survives the renames and transfers
+    pub repo_id: Option<u64>,
     pub number: u64,
     pub author: User,
     pub started: chrono::DateTime<chrono::Utc>,
     pub merged: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub full_id: String,
+    pub title: String,
     pub body: String,
     pub closed: bool,
+    // The latest review state of every reviewer
+    pub reviews: HashMap<String, ReviewState>,
+    pub labels: Vec<String>,
+    // Someone is asked to review, the PR might still wait on the author if it was a while ago
+    pub review_requested: bool,
+    pub draft: bool,
+}
+
+impl PrMetadata {
+    pub fn set_reviews(&mut self, reviews: &[octocrab::models::pulls::Review]) {
+        for review in reviews {
+            // Comments don't change the approval status of the reviewer
+            let (Some(user), Some(state)) = (&review.user, review.state) else {
+                continue;
+            };
+            if matches!(
+                state,
+                ReviewState::Approved | ReviewState::ChangesRequested | ReviewState::Dismissed
+            ) {
+                self.reviews.insert(user.login.clone(), state);
+            }
+        }
+    }
+
+    pub fn has_requested_changes(&self) -> bool {
+        self.reviews
+            .values()
+            .any(|state| *state == ReviewState::ChangesRequested)
+    }
+
+    // The number of the PR this one reverts. GitHub puts `Reverts owner/repo#123` in the body,
+    // while `git revert` of a squashed commit keeps `(#123)` in the title
+    pub fn reverted_pr(&self) -> Option<u64> {
+        fn leading_number(text: &str) -> Option<u64> {
+            let end = text
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(text.len());
+            text[..end].parse().ok()
+        }
+
+        let full_ref = format!("Reverts {}/{}#", self.owner, self.repo);
+        let from_body = [full_ref.as_str(), "Reverts #"]
+            .into_iter()
+            .find_map(|prefix| leading_number(self.body.split_once(prefix)?.1));
+        let from_title = || {
+            let title = self.title.strip_prefix("Revert \"")?;
+            leading_number(title.rsplit_once("(#")?.1)
+        };
+
+        from_body
+            .or_else(from_title)
+            .filter(|number| *number != self.number)
+    }
 }
 
 impl From<PRWithRating> for PrMetadata {
@@ -52,6 +118,7 @@ impl From<PRWithRating> for PrMetadata {
         Self {
             owner: pr.organization,
             repo: pr.repo,
+            repo_id: None,
             number: pr.number,
             author: User::new(pr.author, AuthorAssociation::None),
             body: Default::default(),
@@ -63,7 +130,12 @@ impl From<PRWithRating> for PrMetadata {
                 pr.merged_at.unwrap_or(pr.created_at) as i64,
             ),
             full_id,
+            title: Default::default(),
             closed: false,
+            reviews: Default::default(),
+            labels: Default::default(),
+            review_requested: false,
+            draft: false,
         }
     }
 }
@@ -72,7 +144,7 @@ impl TryFrom<octocrab::models::pulls::PullRequest> for PrMetadata {
     type Error = anyhow::Error;
 
     fn try_from(pr: octocrab::models::pulls::PullRequest) -> anyhow::Result<Self> {
-        let repo = pr.base.repo.map(|repo| (repo.owner, repo.name));
+        let repo = pr.base.repo.map(|repo| (repo.owner, repo.name, repo.id.0));
         let body: String = pr
             .body
             .or(pr.body_text)
@@ -80,7 +152,7 @@ impl TryFrom<octocrab::models::pulls::PullRequest> for PrMetadata {
             .unwrap_or_default();
 
         if let (
-            Some((Some(owner), repo)),
+            Some((Some(owner), repo, repo_id)),
             Some(user),
             Some(author_association),
             Some(created_at),
@@ -96,17 +168,138 @@ impl TryFrom<octocrab::models::pulls::PullRequest> for PrMetadata {
             Ok(Self {
                 owner: owner.login,
                 repo,
+                repo_id: Some(repo_id),
                 body,
                 number: pr.number,
-                author: User::new(user.login, author_association),
+                author: User::new(user.login, author_association).with_id(user.id.0),
                 started: created_at,
                 merged: pr.merged_at,
                 updated_at,
                 full_id,
+                title: pr.title.unwrap_or_default(),
                 closed: pr.closed_at.is_some(),
+                reviews: Default::default(),
+                labels: pr
+                    .labels
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|label| label.name)
+                    .collect(),
+                review_requested: pr
+                    .requested_reviewers
+                    .is_some_and(|reviewers| !reviewers.is_empty())
+                    || pr.requested_teams.is_some_and(|teams| !teams.is_empty()),
+                draft: pr.draft.unwrap_or_default(),
             })
         } else {
             Err(anyhow::anyhow!("Missing required fields"))
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentSource {
+    Issue,
+    Review,
+    ReviewComment,
+    // Quick scoring with the reaction on our status comment
+    Reaction,
+    Label,
+}
+
+// Ids of the issue comments, reviews and review comments don't share the same sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CommentRef {
+    pub source: CommentSource,
+    pub id: u64,
+}
+
+impl CommentRef {
+    pub fn issue(id: u64) -> Self {
+        Self {
+            source: CommentSource::Issue,
+            id,
+        }
+    }
+}
+
+impl std::fmt::Display for CommentRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}({})", self.source, self.id)
+    }
+}
+
+// Any text on the PR that can contain a command
+#[derive(Debug, Clone)]
+pub struct CommandComment {
+    pub id: CommentRef,
+    pub body: String,
+    pub user: User,
+    // Set only for the review summaries
+    pub review_state: Option<ReviewState>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<octocrab::models::issues::Comment> for CommandComment {
+    fn from(comment: octocrab::models::issues::Comment) -> Self {
+        Self {
+            id: CommentRef::issue(comment.id.0),
+            body: comment
+                .body
+                .or(comment.body_html)
+                .or(comment.body_text)
+                .unwrap_or_default(),
+            user: User::new(comment.user.login, comment.author_association)
+                .with_id(comment.user.id.0),
+            review_state: None,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at.unwrap_or(comment.created_at),
+        }
+    }
+}
+
+impl TryFrom<octocrab::models::pulls::Review> for CommandComment {
+    type Error = anyhow::Error;
+
+    fn try_from(review: octocrab::models::pulls::Review) -> anyhow::Result<Self> {
+        if let (Some(user), Some(author_association), Some(submitted_at)) =
+            (review.user, review.author_association, review.submitted_at)
+        {
+            Ok(Self {
+                id: CommentRef {
+                    source: CommentSource::Review,
+                    id: review.id.0,
+                },
+                body: review.body.unwrap_or_default(),
+                user: User::new(user.login, author_association).with_id(user.id.0),
+                review_state: review.state,
+                created_at: submitted_at,
+                updated_at: submitted_at,
+            })
+        } else {
+            Err(anyhow::anyhow!("Missing required fields"))
+        }
+    }
+}
+
+impl TryFrom<octocrab::models::pulls::Comment> for CommandComment {
+    type Error = anyhow::Error;
+
+    fn try_from(comment: octocrab::models::pulls::Comment) -> anyhow::Result<Self> {
+        let user = comment
+            .user
+            .ok_or_else(|| anyhow::anyhow!("Missing required fields"))?;
+        Ok(Self {
+            id: CommentRef {
+                source: CommentSource::ReviewComment,
+                id: comment.id.0,
+            },
+            body: comment.body,
+            user: User::new(user.login, comment.author_association).with_id(user.id.0),
+            review_state: None,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+        })
+    }
+}
@@ -6,9 +6,16 @@ use near_sdk::{
     AccountId, NearSchema,
 };
 
+mod achievement;
+mod error;
 mod event;
+mod operation;
 mod pr;
+mod rank;
+mod referral;
+mod stats;
 mod streak;
+mod team;
 mod timeperiod;
 
 pub type UserId = u32;
@@ -19,9 +26,16 @@ pub mod github;
 #[cfg(feature = "client")]
 pub mod near;
 
+pub use achievement::*;
+pub use error::*;
 pub use event::*;
+pub use operation::*;
 pub use pr::*;
+pub use rank::*;
+pub use referral::*;
+pub use stats::*;
 pub use streak::*;
+pub use team::*;
 pub use timeperiod::*;
 
 pub type GithubHandle = String;
@@ -144,6 +158,32 @@ impl AccountWithPermanentPercentageBonus {
         }
     }
 
+    // Drops the unused rewards of the streak days that are lost
+    pub fn revoke_flat_bonus(&mut self, streak_id: StreakId, amount: u32) {
+        self.flat_bonus
+            .retain(|bonus| bonus.streak_id != streak_id || bonus.streak_min <= amount);
+    }
+
+    // Merges bonuses of another account of the same contributor, keeping the best ones
+    pub fn merge(&mut self, other: AccountWithPermanentPercentageBonus) {
+        for bonus in other.permanent_percentage_bonus {
+            match self
+                .permanent_percentage_bonus
+                .iter_mut()
+                .find(|b| b.streak_id == bonus.streak_id)
+            {
+                Some(existing) => existing.percent = existing.percent.max(bonus.percent),
+                None => self.permanent_percentage_bonus.push(bonus),
+            }
+        }
+        for bonus in other.flat_bonus {
+            self.add_flat_bonus(bonus.streak_id, bonus.reward, bonus.streak_min);
+        }
+        if self.account_id.is_none() {
+            self.account_id = other.account_id;
+        }
+    }
+
     // Clear new flags and return the sum of all new bonuses
     // TODO: refactor this. See the comment in the struct
     pub fn clear_new_flags(&mut self) -> u32 {
@@ -199,9 +239,44 @@ impl VersionedUserPeriodData {
         *self = VersionedUserPeriodData::V1(data);
     }
 
+    pub fn referral_bonus(&mut self, rating: u32) {
+        let mut data: UserPeriodData = self.clone().into();
+        data.total_rating += rating;
+        *self = VersionedUserPeriodData::V1(data);
+    }
+
+    pub fn referral_bonus_revoked(&mut self, rating: u32) {
+        let mut data: UserPeriodData = self.clone().into();
+        data.total_rating = data.total_rating.saturating_sub(rating);
+        *self = VersionedUserPeriodData::V1(data);
+    }
+
+    pub fn pr_reverted(&mut self, executed: bool, score: u32, rating: u32) {
+        let mut data: UserPeriodData = self.clone().into();
+        data.prs_merged = data.prs_merged.saturating_sub(1);
+        if executed {
+            data.executed_prs = data.executed_prs.saturating_sub(1);
+            data.total_score = data.total_score.saturating_sub(score);
+            data.total_rating = data.total_rating.saturating_sub(rating);
+        }
+        *self = VersionedUserPeriodData::V1(data);
+    }
+
     pub fn pr_closed(&mut self) {
         let mut data: UserPeriodData = self.clone().into();
-        data.prs_opened -= 1;
+        data.prs_opened = data.prs_opened.saturating_sub(1);
+        *self = VersionedUserPeriodData::V1(data);
+    }
+
+    pub fn merge(&mut self, other: UserPeriodData) {
+        let mut data: UserPeriodData = self.clone().into();
+        data.total_score += other.total_score;
+        data.executed_prs += other.executed_prs;
+        data.largest_score = data.largest_score.max(other.largest_score);
+        data.prs_opened += other.prs_opened;
+        data.prs_merged += other.prs_merged;
+        data.total_rating += other.total_rating;
+        data.largest_rating_per_pr = data.largest_rating_per_pr.max(other.largest_rating_per_pr);
         *self = VersionedUserPeriodData::V1(data);
     }
 }
@@ -246,6 +321,8 @@ pub struct User {
     pub percentage_bonus: u32,
     pub period_data: Vec<(TimePeriodString, UserPeriodData)>,
     pub streaks: Vec<(StreakId, StreakUserData)>,
+    #[serde(default)]
+    pub achievements: Vec<(Achievement, near_sdk::Timestamp)>,
 }
 
 impl User {
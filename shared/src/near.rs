@@ -1,8 +1,13 @@
-use std::str::FromStr;
+use std::{
+    collections::HashSet,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::bail;
 use near_workspaces::{
-    result::{ExecutionResult, Value},
+    operations::CallTransaction,
+    result::{ExecutionFailure, ExecutionResult, Value},
     types::SecretKey,
     Contract,
 };
@@ -13,9 +18,22 @@ use super::github::PrMetadata;
 
 use crate::*;
 
+// Contract panics carry the error code, so the callers can downcast to the `ContractError`
+fn contract_error(failure: ExecutionFailure) -> anyhow::Error {
+    let failure = format!("{failure:?}");
+    match ContractError::from_panic(&failure) {
+        Some(error) => error.into(),
+        None => anyhow::anyhow!(failure),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct NearClient {
     contract: Contract,
+    // Operations waiting to be sent with the next `flush`
+    queue: Arc<Mutex<Vec<Operation>>>,
+    // Circuit breakers of the contract, refreshed with `refresh_paused_methods`
+    paused_methods: Arc<Mutex<HashSet<String>>>,
 }
 
 impl NearClient {
@@ -24,11 +42,43 @@ impl NearClient {
         if mainnet {
             let mainnet = near_workspaces::mainnet().await?;
             let contract = Contract::from_secret_key(contract.parse()?, sk, &mainnet);
-            return Ok(Self { contract });
+            return Ok(Self {
+                contract,
+                queue: Default::default(),
+                paused_methods: Default::default(),
+            });
         }
         let testnet = near_workspaces::testnet().await?;
         let contract = Contract::from_secret_key(contract.parse()?, sk, &testnet);
-        Ok(Self { contract })
+        Ok(Self {
+            contract,
+            queue: Default::default(),
+            paused_methods: Default::default(),
+        })
+    }
+
+    // Paused methods would fail anyway, so they aren't sent to save the gas
+    fn call(&self, method: &str) -> anyhow::Result<CallTransaction> {
+        if self.is_method_paused(method) {
+            bail!(ContractError::MethodPaused);
+        }
+        Ok(self.contract.call(method))
+    }
+
+    pub fn is_method_paused(&self, method: &str) -> bool {
+        self.paused_methods.lock().unwrap().contains(method)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn refresh_paused_methods(&self) -> anyhow::Result<()> {
+        let res = self
+            .contract
+            .view("paused_methods")
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call paused_methods: {:?}", e))?;
+        let methods: Vec<String> = res.json()?;
+        *self.paused_methods.lock().unwrap() = methods.into_iter().collect();
+        Ok(())
     }
 
     fn get_events(&self, result: ExecutionResult<Value>) -> Vec<Event> {
@@ -52,43 +102,67 @@ impl NearClient {
             "user": pr.author.login,
             "started_at": pr.started.timestamp_nanos_opt().unwrap_or(0),
             "override_exclude": is_maintainer,
+            "github_id": pr.author.id,
+            "draft": pr.draft,
+            "repo_id": pr.repo_id,
         });
 
         let result = self
-            .contract
-            .call("sloth_include")
+            .call("sloth_include")?
             .args_json(args)
             .transact_async()
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call sloth_include: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
 
         Ok(self.get_events(result))
     }
 
-    #[instrument(skip(self), fields(pr = pr.full_id, user, score))]
+    #[instrument(skip(self, reason), fields(pr = pr.full_id, user, score))]
     pub async fn send_scored(
         &self,
         pr: &PrMetadata,
         user: &str,
         score: u64,
+        reason: Option<&str>,
     ) -> anyhow::Result<Vec<Event>> {
         let args = json!({
             "pr_id": pr.full_id,
             "user": user,
             "score": score,
+            "reason": reason,
         });
 
         let result = self
-            .contract
-            .call("sloth_scored")
+            .call("sloth_scored")?
             .args_json(args)
             .transact_async()
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call sloth_scored: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self), fields(pr = pr.full_id, user))]
+    pub async fn send_unscored(&self, pr: &PrMetadata, user: &str) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "pr_id": pr.full_id,
+            "user": user,
+        });
+
+        let result = self
+            .call("sloth_unscored")?
+            .args_json(args)
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_unscored: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
         Ok(self.get_events(result))
     }
 
@@ -104,22 +178,21 @@ impl NearClient {
         });
 
         let result = self
-            .contract
-            .call("sloth_merged")
+            .call("sloth_merged")?
             .args_json(args)
             .transact_async()
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call sloth_merged: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
         Ok(self.get_events(result))
     }
 
     #[instrument(skip(self))]
     pub async fn send_pause(&self, organization: &str, repo: &str) -> anyhow::Result<Vec<Event>> {
         let result = self
-            .contract
-            .call("exclude_repo")
+            .call("exclude_repo")?
             .args_json(json!({
                 "organization": organization,
                 "repo": repo,}))
@@ -127,15 +200,15 @@ impl NearClient {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call sloth_paused: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
         Ok(self.get_events(result))
     }
 
     #[instrument(skip(self))]
     pub async fn send_unpause(&self, organization: &str, repo: &str) -> anyhow::Result<Vec<Event>> {
         let result = self
-            .contract
-            .call("include_repo")
+            .call("include_repo")?
             .args_json(json!({
                 "organization": organization,
                 "repo": repo,}))
@@ -143,7 +216,8 @@ impl NearClient {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call sloth_resumed: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
         Ok(self.get_events(result))
     }
 
@@ -246,20 +320,152 @@ impl NearClient {
     pub async fn send_stale(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
         let args = json!({
             "pr_id": pr.full_id,
+            "closed": pr.closed,
         });
 
         let result = self
-            .contract
-            .call("sloth_stale")
+            .call("sloth_stale")?
             .args_json(args)
             .transact_async()
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call sloth_stale: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self, pr), fields(pr = pr.full_id))]
+    pub async fn send_snooze(
+        &self,
+        pr: &PrMetadata,
+        until: near_sdk::Timestamp,
+    ) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "pr_id": pr.full_id,
+            "until": until,
+        });
+
+        let result = self
+            .call("sloth_snooze")?
+            .args_json(args)
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_snooze: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self, pr), fields(pr = pr.full_id))]
+    pub async fn send_ready(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "pr_id": pr.full_id,
+        });
+
+        let result = self
+            .call("sloth_ready")?
+            .args_json(args)
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_ready: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self, pr), fields(pr = pr.full_id))]
+    pub async fn send_draft(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "pr_id": pr.full_id,
+        });
+
+        let result = self
+            .call("sloth_draft")?
+            .args_json(args)
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_draft: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self, pr), fields(pr = pr.full_id))]
+    pub async fn send_reopen(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "pr_id": pr.full_id,
+        });
+
+        let result = self
+            .call("sloth_reopen")?
+            .args_json(args)
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_reopen: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_revert(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "pr_id": format!("{owner}/{repo}/{number}"),
+        });
+
+        let result = self
+            .call("sloth_revert")?
+            .args_json(args)
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_revert: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
         Ok(self.get_events(result))
     }
 
+    // Moves a batch of the PRs, returns the amount of the PRs left under the old name
+    #[instrument(skip(self))]
+    pub async fn send_move_repo(
+        &self,
+        owner: &str,
+        repo: &str,
+        new_owner: &str,
+        new_repo: &str,
+        repo_id: u64,
+    ) -> anyhow::Result<u32> {
+        let args = json!({
+            "organization": owner,
+            "repo": repo,
+            "new_organization": new_owner,
+            "new_repo": new_repo,
+            "repo_id": repo_id,
+        });
+
+        let result = self
+            .call("sloth_move_repo")?
+            .args_json(args)
+            .max_gas()
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_move_repo: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(result.json()?)
+    }
+
     #[instrument(skip(self, pr), fields(pr = pr.full_id))]
     pub async fn send_exclude(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
         let args = json!({
@@ -267,22 +473,21 @@ impl NearClient {
         });
 
         let result = self
-            .contract
-            .call("sloth_exclude")
+            .call("sloth_exclude")?
             .args_json(args)
             .transact_async()
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call sloth_exclude: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
         Ok(self.get_events(result))
     }
 
     #[instrument(skip(self))]
     pub async fn send_finalize(&self, pr_id: &str) -> anyhow::Result<Vec<Event>> {
         let result = self
-            .contract
-            .call("sloth_finalize")
+            .call("sloth_finalize")?
             .args_json(json!({
                 "pr_id": pr_id,
             }))
@@ -290,10 +495,82 @@ impl NearClient {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to call execute_prs: {:?}", e))?
             .await?
-            .into_result()?;
+            .into_result()
+            .map_err(contract_error)?;
         Ok(self.get_events(result))
     }
 
+    // Returns the events of every operation in the same order
+    #[instrument(skip(self, operations), fields(operations = operations.len()))]
+    pub async fn send_batch(&self, operations: &[Operation]) -> anyhow::Result<Vec<Vec<Event>>> {
+        let result = self
+            .call("sloth_batch")?
+            .args_json(json!({
+                "operations": operations,
+            }))
+            .max_gas()
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_batch: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+
+        let mut events: Vec<Vec<Event>> = vec![];
+        for event in self.get_events(result) {
+            match (event, events.last_mut()) {
+                (Event::BatchOperation { .. }, _) => events.push(vec![]),
+                (event, Some(operation_events)) => operation_events.push(event),
+                (event, None) => bail!("Event {event:?} logged outside of the batch operation"),
+            }
+        }
+        if events.len() != operations.len() {
+            bail!(
+                "Expected events for {} operations, got {}",
+                operations.len(),
+                events.len()
+            )
+        }
+        Ok(events)
+    }
+
+    // The operation replaces the queued one it supersedes, so the operations queued again
+    // while their method is paused don't pile up
+    pub fn queue(&self, operation: Operation) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.retain(|queued| !queued.is_replaced_by(&operation));
+        queue.push(operation);
+    }
+
+    // Sends the queued operations in batches, the failed batch fails all its operations
+    #[instrument(skip(self))]
+    pub async fn flush(&self) -> Vec<(Operation, anyhow::Result<Vec<Event>>)> {
+        if self.is_method_paused("sloth_batch") {
+            return vec![];
+        }
+
+        // Operations of the paused methods wait in the queue until the methods are unpaused
+        let (operations, paused): (Vec<Operation>, Vec<Operation>) =
+            std::mem::take(&mut *self.queue.lock().unwrap())
+                .into_iter()
+                .partition(|operation| !self.is_method_paused(operation.method()));
+        self.queue.lock().unwrap().extend(paused);
+
+        let mut results = vec![];
+        for chunk in operations.chunks(MAX_BATCH_SIZE) {
+            match self.send_batch(chunk).await {
+                Ok(events) => results.extend(chunk.iter().cloned().zip(events.into_iter().map(Ok))),
+                Err(e) => results.extend(
+                    chunk
+                        .iter()
+                        .cloned()
+                        .map(|operation| (operation, Err(anyhow::anyhow!("Batch failed: {e}")))),
+                ),
+            }
+        }
+        results
+    }
+
     #[instrument(skip(self))]
     pub async fn user_info(
         &self,
@@ -318,7 +595,7 @@ impl NearClient {
         page: u64,
         limit: u64,
         periods: Vec<TimePeriodString>,
-    ) -> anyhow::Result<Vec<User>> {
+    ) -> anyhow::Result<Option<Vec<User>>> {
         let res = self
             .contract
             .view("users")
@@ -338,11 +615,8 @@ impl NearClient {
         let mut page = 0;
         const LIMIT: u64 = 100;
         let mut res = vec![];
-        loop {
-            let users = self.users_paged(page, LIMIT, periods.clone()).await?;
-            if users.is_empty() {
-                break;
-            }
+        // Pages of the skipped accounts are empty, so the walk stops only past the accounts
+        while let Some(users) = self.users_paged(page, LIMIT, periods.clone()).await? {
             res.extend(users);
             page += 1;
         }
@@ -384,6 +658,381 @@ impl NearClient {
         Ok(res)
     }
 
+    // Draft PRs among the given ones
+    #[instrument(skip(self, pr_ids))]
+    pub async fn pending_prs(&self, pr_ids: &[String]) -> anyhow::Result<HashSet<String>> {
+        let mut res = HashSet::new();
+        for chunk in pr_ids.chunks(100) {
+            let prs: Vec<String> = self
+                .contract
+                .view("pending_prs")
+                .args_json(json!({
+                    "pr_ids": chunk,
+                }))
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to call pending_prs: {:?}", e))?
+                .json()?;
+            res.extend(prs);
+        }
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn reverted_prs_paged(
+        &self,
+        page: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(String, u64)>> {
+        let res = self
+            .contract
+            .view("reverted_prs")
+            .args_json(json!({
+                "page": page,
+                "limit": limit,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call reverted_prs: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    // Reverted PRs, so the off-chain copies of the executed ones are dropped
+    #[instrument(skip(self))]
+    pub async fn reverted_prs(&self) -> anyhow::Result<Vec<String>> {
+        let mut page = 0;
+        const LIMIT: u64 = 100;
+        let mut res = vec![];
+        loop {
+            let prs = self.reverted_prs_paged(page, LIMIT).await?;
+            if prs.is_empty() {
+                break;
+            }
+            res.extend(prs.into_iter().map(|(pr_id, _)| pr_id));
+            page += 1;
+        }
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn unindexed_prs_paged(
+        &self,
+        page: u64,
+        limit: u64,
+    ) -> anyhow::Result<Option<Vec<(String, Option<near_sdk::Timestamp>)>>> {
+        let res = self
+            .contract
+            .view("unindexed_prs")
+            .args_json(json!({
+                "page": page,
+                "limit": limit,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call unindexed_prs: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn unindexed_prs(
+        &self,
+    ) -> anyhow::Result<Vec<(String, Option<near_sdk::Timestamp>)>> {
+        let mut page = 0;
+        const LIMIT: u64 = 100;
+        let mut res = vec![];
+        // Pages of the indexed PRs are empty, so the walk stops only past the stored PRs
+        while let Some(prs) = self.unindexed_prs_paged(page, LIMIT).await? {
+            res.extend(prs);
+            page += 1;
+        }
+        Ok(res)
+    }
+
+    #[instrument(skip(self, pr_ids))]
+    pub async fn send_index_prs(&self, pr_ids: &[String]) -> anyhow::Result<u32> {
+        let result = self
+            .call("sloth_index_prs")?
+            .args_json(json!({
+                "pr_ids": pr_ids,
+            }))
+            .max_gas()
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_index_prs: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(result.json()?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn prs_by_author(
+        &self,
+        user: &str,
+        from_key: Option<&str>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(PRWithRating, bool)>> {
+        let res = self
+            .contract
+            .view("prs_by_author")
+            .args_json(json!({
+                "user": user,
+                "from_key": from_key,
+                "limit": limit,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call prs_by_author: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn prs_by_repo(
+        &self,
+        organization: &str,
+        repo: &str,
+        from_key: Option<&str>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(PRWithRating, bool)>> {
+        let res = self
+            .contract
+            .view("prs_by_repo")
+            .args_json(json!({
+                "organization": organization,
+                "repo": repo,
+                "from_key": from_key,
+                "limit": limit,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call prs_by_repo: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_referred_by(&self, user: &str, referrer: &str) -> anyhow::Result<Vec<Event>> {
+        let result = self
+            .call("sloth_referred_by")?
+            .args_json(json!({
+                "user": user,
+                "referrer": referrer,
+            }))
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_referred_by: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_opt_out(&self, user: &str) -> anyhow::Result<Vec<Event>> {
+        let result = self
+            .call("sloth_opt_out")?
+            .args_json(json!({
+                "user": user,
+            }))
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_opt_out: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn opted_out_users(&self) -> anyhow::Result<Vec<String>> {
+        let res = self
+            .contract
+            .view("opted_out_users")
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call opted_out_users: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn stats(&self) -> anyhow::Result<Stats> {
+        let res = self
+            .contract
+            .view("stats")
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call stats: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_link_account(
+        &self,
+        user: &str,
+        account_id: &str,
+    ) -> anyhow::Result<Vec<Event>> {
+        let result = self
+            .call("sloth_link_account")?
+            .args_json(json!({
+                "user": user,
+                "account_id": account_id,
+            }))
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_link_account: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_team_create(
+        &self,
+        team: &str,
+        organization: &str,
+    ) -> anyhow::Result<Vec<Event>> {
+        let result = self
+            .call("sloth_team_create")?
+            .args_json(json!({
+                "team": team,
+                "organization": organization,
+            }))
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_team_create: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_team_request(
+        &self,
+        team: &str,
+        organization: &str,
+        user: &str,
+    ) -> anyhow::Result<Vec<Event>> {
+        let result = self
+            .call("sloth_team_request")?
+            .args_json(json!({
+                "team": team,
+                "organization": organization,
+                "user": user,
+            }))
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_team_request: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_team_approve(
+        &self,
+        organization: &str,
+        user: &str,
+    ) -> anyhow::Result<Vec<Event>> {
+        let result = self
+            .call("sloth_team_approve")?
+            .args_json(json!({
+                "organization": organization,
+                "user": user,
+            }))
+            .transact_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_team_approve: {:?}", e))?
+            .await?
+            .into_result()
+            .map_err(contract_error)?;
+        Ok(self.get_events(result))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn team(&self, name: &str) -> anyhow::Result<Option<Team>> {
+        let res = self
+            .contract
+            .view("team")
+            .args_json(json!({
+                "name": name,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call team: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn team_request(&self, user: &str) -> anyhow::Result<Option<TeamName>> {
+        let res = self
+            .contract
+            .view("team_request")
+            .args_json(json!({
+                "user": user,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call team_request: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn teams_paged(&self, page: u64, limit: u64) -> anyhow::Result<Vec<Team>> {
+        let res = self
+            .contract
+            .view("teams")
+            .args_json(json!({
+                "page": page,
+                "limit": limit,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call teams: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn teams(&self) -> anyhow::Result<Vec<Team>> {
+        let mut page = 0;
+        const LIMIT: u64 = 100;
+        let mut res = vec![];
+        loop {
+            let teams = self.teams_paged(page, LIMIT).await?;
+            if teams.is_empty() {
+                break;
+            }
+            res.extend(teams);
+            page += 1;
+        }
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn is_paused(&self) -> anyhow::Result<bool> {
+        let res = self
+            .contract
+            .view("is_paused")
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call is_paused: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn score_timeout(&self, organization: &str) -> anyhow::Result<u64> {
+        let res = self
+            .contract
+            .view("score_timeout")
+            .args_json(json!({
+                "organization": organization,
+            }))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call score_timeout: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
+
     #[instrument(skip(self))]
     pub async fn repos(&self) -> anyhow::Result<Vec<AllowedRepos>> {
         let res = self
@@ -394,4 +1043,16 @@ impl NearClient {
         let res = res.json()?;
         Ok(res)
     }
+
+    // Tracked GitHub repo ids with the last known organization and repo names
+    #[instrument(skip(self))]
+    pub async fn repo_ids(&self) -> anyhow::Result<Vec<(u64, String, String)>> {
+        let res = self
+            .contract
+            .view("repo_ids")
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call repo_ids: {:?}", e))?;
+        let res = res.json()?;
+        Ok(res)
+    }
 }
@@ -0,0 +1,125 @@
+use near_sdk::Timestamp;
+
+use super::*;
+
+// Keeps the batch within the gas limit of a single transaction
+pub const MAX_BATCH_SIZE: usize = 20;
+
+// Sloth calls that can be applied together with the `sloth_batch`
+#[derive(Debug, Clone, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Operation {
+    Include {
+        organization: String,
+        repo: String,
+        user: GithubHandle,
+        pr_number: u64,
+        started_at: Timestamp,
+        override_exclude: bool,
+        github_id: Option<u64>,
+        #[serde(default)]
+        draft: Option<bool>,
+        #[serde(default)]
+        repo_id: Option<u64>,
+    },
+    Scored {
+        pr_id: PRId,
+        user: GithubHandle,
+        score: u32,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    Merged {
+        pr_id: PRId,
+        merged_at: Timestamp,
+    },
+    Stale {
+        pr_id: PRId,
+        #[serde(default)]
+        closed: Option<bool>,
+    },
+    Exclude {
+        pr_id: PRId,
+    },
+    Finalize {
+        pr_id: PRId,
+        timestamp: Option<Timestamp>,
+    },
+}
+
+impl Operation {
+    pub fn pr_id(&self) -> PRId {
+        match self {
+            Operation::Include {
+                organization,
+                repo,
+                pr_number,
+                ..
+            } => format!("{organization}/{repo}/{pr_number}"),
+            Operation::Scored { pr_id, .. }
+            | Operation::Merged { pr_id, .. }
+            | Operation::Stale { pr_id, .. }
+            | Operation::Exclude { pr_id }
+            | Operation::Finalize { pr_id, .. } => pr_id.clone(),
+        }
+    }
+
+    // The sloth method the operation is applied with, so its circuit breaker can be checked
+    pub fn method(&self) -> &'static str {
+        match self {
+            Operation::Include { .. } => "sloth_include",
+            Operation::Scored { .. } => "sloth_scored",
+            Operation::Merged { .. } => "sloth_merged",
+            Operation::Stale { .. } => "sloth_stale",
+            Operation::Exclude { .. } => "sloth_exclude",
+            Operation::Finalize { .. } => "sloth_finalize",
+        }
+    }
+
+    // The later operation of the same method for the same PR supersedes the queued one.
+    // Scores of the different users are kept apart
+    pub fn is_replaced_by(&self, other: &Operation) -> bool {
+        match (self, other) {
+            (
+                Operation::Scored { user, .. },
+                Operation::Scored {
+                    user: other_user, ..
+                },
+            ) if user != other_user => false,
+            _ => self.method() == other.method() && self.pr_id() == other.pr_id(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scored(user: &str, score: u32) -> Operation {
+        Operation::Scored {
+            pr_id: "org/repo/1".to_string(),
+            user: user.to_string(),
+            score,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_is_replaced_by() {
+        let finalize = Operation::Finalize {
+            pr_id: "org/repo/1".to_string(),
+            timestamp: None,
+        };
+        assert!(finalize.is_replaced_by(&finalize.clone()));
+        assert!(!finalize.is_replaced_by(&Operation::Exclude {
+            pr_id: "org/repo/1".to_string()
+        }));
+        assert!(!finalize.is_replaced_by(&Operation::Finalize {
+            pr_id: "org/repo/2".to_string(),
+            timestamp: None,
+        }));
+
+        assert!(scored("a", 5).is_replaced_by(&scored("a", 8)));
+        assert!(!scored("a", 5).is_replaced_by(&scored("b", 8)));
+    }
+}
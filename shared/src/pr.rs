@@ -1,3 +1,4 @@
+pub use near_sdk::json_types::Base58CryptoHash;
 use near_sdk::Timestamp;
 
 use super::*;
@@ -6,6 +7,11 @@ pub type PRId = String;
 
 pub const SCORE_TIMEOUT_IN_SECONDS: Timestamp = 24 * 60 * 60;
 pub const SCORE_TIMEOUT_IN_NANOSECONDS: Timestamp = SCORE_TIMEOUT_IN_SECONDS * 1_000_000_000;
+pub const SCORE_RATING_MULTIPLIER: u32 = 10;
+pub const MIN_SCORE_TIMEOUT_IN_NANOSECONDS: Timestamp = 60 * 60 * 1_000_000_000;
+pub const MAX_SCORE_TIMEOUT_IN_NANOSECONDS: Timestamp = 7 * SCORE_TIMEOUT_IN_NANOSECONDS;
+pub const MAX_SNOOZE_DAYS: u64 = 30;
+pub const MAX_SNOOZE_IN_NANOSECONDS: Timestamp = MAX_SNOOZE_DAYS * SCORE_TIMEOUT_IN_NANOSECONDS;
 
 #[derive(
     Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, PartialEq,
@@ -15,6 +21,20 @@ pub const SCORE_TIMEOUT_IN_NANOSECONDS: Timestamp = SCORE_TIMEOUT_IN_SECONDS * 1
 pub struct Score {
     pub user: GithubHandle,
     pub score: u32,
+    // sha256 of the reviewer's reason. The contract keeps it aside and fills it in the views,
+    // while the text itself is stored off-chain
+    #[borsh(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason_hash: Option<Base58CryptoHash>,
+}
+
+// Same as `env::sha256` of the contract, so the off-chain text can be checked against the score
+#[cfg(feature = "client")]
+pub fn reason_hash(reason: &str) -> Base58CryptoHash {
+    use sha2::Digest;
+
+    let hash: [u8; 32] = sha2::Sha256::digest(reason.as_bytes()).into();
+    hash.into()
 }
 
 #[derive(Serialize, Deserialize, NearSchema, Default)]
@@ -27,9 +47,33 @@ pub struct PRInfo {
     pub merged: bool,
     pub executed: bool,
     pub excluded: bool,
+    pub score_timeout: Timestamp,
+    #[serde(default)]
+    pub snoozed_until: Option<Timestamp>,
+    // Draft PR that isn't counted until it's ready for review
+    #[serde(default)]
+    pub pending: bool,
+    // Closed PR that was removed with its votes, so it can be restored on reopen
+    #[serde(default)]
+    pub closed: bool,
 }
 
 impl PRInfo {
+    // Scoring window for the messages, e.g. "1 hour" or "1 hour 30 minutes"
+    pub fn score_timeout_text(&self) -> String {
+        let minutes = self.score_timeout.div_ceil(60 * 1_000_000_000);
+        let plural = |amount: u64, unit: &str| match amount {
+            1 => format!("1 {unit}"),
+            amount => format!("{amount} {unit}s"),
+        };
+
+        match (minutes / 60, minutes % 60) {
+            (0, minutes) => plural(minutes, "minute"),
+            (hours, 0) => plural(hours, "hour"),
+            (hours, minutes) => format!("{} {}", plural(hours, "hour"), plural(minutes, "minute")),
+        }
+    }
+
     pub fn average_score(&self) -> u32 {
         if self.votes.is_empty() {
             return 0;
@@ -54,10 +98,10 @@ impl VersionedPR {
         data.merged_at.is_some()
     }
 
-    pub fn is_ready_to_move(&self, timestamp: Timestamp) -> bool {
+    pub fn is_ready_to_move(&self, timestamp: Timestamp, score_timeout: Timestamp) -> bool {
         let data: PRWithRating = self.clone().into();
 
-        data.is_ready_to_move(timestamp)
+        data.is_ready_to_move(timestamp, score_timeout)
     }
 }
 
@@ -112,25 +156,36 @@ impl PRWithRating {
         if let Some(user) = self.score.iter_mut().find(|s| s.user == user) {
             user.score = score;
         } else {
-            self.score.push(Score { user, score });
+            self.score.push(Score {
+                user,
+                score,
+                reason_hash: None,
+            });
         }
     }
 
+    pub fn remove_score(&mut self, user: &str) {
+        self.score.retain(|s| s.user != user);
+    }
+
     pub fn add_merge_info(&mut self, merged_at: Timestamp) {
         self.merged_at = Some(merged_at);
     }
 
-    pub fn ready_to_move_timestamp(&self) -> Option<Timestamp> {
-        self.merged_at.map(|t| t + SCORE_TIMEOUT_IN_NANOSECONDS)
+    pub fn ready_to_move_timestamp(&self, score_timeout: Timestamp) -> Option<Timestamp> {
+        self.merged_at.map(|t| t + score_timeout)
     }
 
-    pub fn is_ready_to_move(&self, timestamp: Timestamp) -> bool {
-        self.merged_at.is_some()
-            && (timestamp - self.merged_at.unwrap()) > SCORE_TIMEOUT_IN_NANOSECONDS
+    pub fn is_ready_to_move(&self, timestamp: Timestamp, score_timeout: Timestamp) -> bool {
+        self.merged_at.is_some() && (timestamp - self.merged_at.unwrap()) > score_timeout
     }
 
     pub fn rating(&self) -> u32 {
-        let score = self.score().unwrap_or_default() * 10 + self.streak_bonus_rating;
+        self.rating_with_multiplier(SCORE_RATING_MULTIPLIER)
+    }
+
+    pub fn rating_with_multiplier(&self, score_multiplier: u32) -> u32 {
+        let score = self.score().unwrap_or_default() * score_multiplier + self.streak_bonus_rating;
         let percentage = (self.percentage_multiplier + 100) as f64;
         ((score as f64 * percentage / 100.0).ceil()) as u32
     }
@@ -147,3 +202,36 @@ impl PRWithRating {
         format!("{}/{}/{}", self.organization, self.repo, self.number)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(score_timeout: Timestamp) -> PRInfo {
+        PRInfo {
+            votes: vec![],
+            allowed_org: true,
+            allowed_repo: true,
+            exist: true,
+            merged: true,
+            executed: false,
+            excluded: false,
+            score_timeout,
+            snoozed_until: None,
+            pending: false,
+            closed: false,
+        }
+    }
+
+    #[test]
+    fn test_score_timeout_text() {
+        let minute = 60 * 1_000_000_000;
+        assert_eq!(info(60 * minute).score_timeout_text(), "1 hour");
+        assert_eq!(info(90 * minute).score_timeout_text(), "1 hour 30 minutes");
+        assert_eq!(info(24 * 60 * minute).score_timeout_text(), "24 hours");
+        assert_eq!(
+            info(61 * minute + 1).score_timeout_text(),
+            "1 hour 2 minutes"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use near_sdk::Timestamp;
+use strum::EnumIter;
+
+use super::*;
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    EnumIter,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum Rank {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+    Rust,
+}
+
+impl Rank {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bronze => "Bronze",
+            Self::Silver => "Silver",
+            Self::Gold => "Gold",
+            Self::Platinum => "Platinum",
+            Self::Rust => "Rust",
+        }
+    }
+
+    // Minimal lifetime percentage bonus required for the rank
+    pub fn min_lifetime_percent(&self) -> u32 {
+        match self {
+            Self::Bronze => 5,
+            Self::Silver => 10,
+            Self::Gold => 15,
+            Self::Platinum => 20,
+            Self::Rust => 25,
+        }
+    }
+
+    // All the ranks that are reached with the lifetime percentage bonus
+    pub fn reached(lifetime_percent: u32) -> Vec<Self> {
+        Self::iter()
+            .filter(|rank| rank.min_lifetime_percent() <= lifetime_percent)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedRankBadge {
+    V1(RankBadge),
+}
+
+impl From<VersionedRankBadge> for RankBadge {
+    fn from(message: VersionedRankBadge) -> Self {
+        match message {
+            VersionedRankBadge::V1(x) => x,
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct RankBadge {
+    pub owner_id: AccountId,
+    pub github_handle: GithubHandle,
+    pub rank: Rank,
+    pub issued_at: Timestamp,
+}
+
+impl RankBadge {
+    pub fn token_id(user_id: UserId, rank: Rank) -> String {
+        format!("{}-{}", user_id, rank.name().to_lowercase())
+    }
+}
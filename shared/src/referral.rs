@@ -0,0 +1,77 @@
+use near_sdk::Timestamp;
+
+use super::*;
+
+// One-time flat bonus of the referrer when the first PR of the referee is finalized
+pub const REFERRAL_BONUS_RATING: u32 = 25;
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedReferral {
+    V1(Referral),
+}
+
+impl From<VersionedReferral> for Referral {
+    fn from(message: VersionedReferral) -> Self {
+        match message {
+            VersionedReferral::V1(x) => x,
+        }
+    }
+}
+
+// The bonus is added to the referrer periods, so it's kept to be audited and reverted
+#[derive(
+    Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ReferralBonusStorage {
+    // The first executed PR of the referee
+    pub pr_id: PRId,
+    pub rating: u32,
+    pub rewarded_at: Timestamp,
+}
+
+#[derive(
+    Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Referral {
+    pub referrer: UserId,
+    pub reward: u32,
+    pub rewarded: Option<ReferralBonusStorage>,
+}
+
+impl Referral {
+    pub fn new(referrer: UserId) -> Self {
+        Self {
+            referrer,
+            reward: REFERRAL_BONUS_RATING,
+            rewarded: None,
+        }
+    }
+
+    // Returns the reward only once, so the referrer can't be rewarded twice for the same referee
+    pub fn use_reward(&mut self, pr_id: PRId, timestamp: Timestamp) -> u32 {
+        if self.rewarded.is_some() {
+            return 0;
+        }
+
+        self.rewarded = Some(ReferralBonusStorage {
+            pr_id,
+            rating: self.reward,
+            rewarded_at: timestamp,
+        });
+        self.reward
+    }
+
+    // Takes back the bonus earned by the PR, so the next executed PR of the referee earns it again
+    pub fn revoke_reward(&mut self, pr_id: &PRId) -> Option<ReferralBonusStorage> {
+        if self.rewarded.as_ref()?.pr_id != *pr_id {
+            return None;
+        }
+        self.rewarded.take()
+    }
+}
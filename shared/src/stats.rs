@@ -0,0 +1,43 @@
+use super::*;
+
+#[derive(
+    Debug, Clone, Default, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PRStats {
+    pub opened: u32,
+    pub merged: u32,
+    pub executed: u32,
+    // Only the ids of the excluded PRs are stored,
+    // so the PRs excluded before the stats were introduced aren't counted
+    pub excluded: u32,
+    // Rating of the executed PRs and the referral bonuses
+    pub total_rating: u64,
+}
+
+impl PRStats {
+    pub fn add(&mut self, other: &PRStats) {
+        self.opened += other.opened;
+        self.merged += other.merged;
+        self.executed += other.executed;
+        self.excluded += other.excluded;
+        self.total_rating += other.total_rating;
+    }
+
+    pub fn sub(&mut self, other: &PRStats) {
+        self.opened = self.opened.saturating_sub(other.opened);
+        self.merged = self.merged.saturating_sub(other.merged);
+        self.executed = self.executed.saturating_sub(other.executed);
+        self.excluded = self.excluded.saturating_sub(other.excluded);
+        self.total_rating = self.total_rating.saturating_sub(other.total_rating);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Stats {
+    pub users: u32,
+    pub prs: PRStats,
+    pub organizations: Vec<(String, PRStats)>,
+}
@@ -160,3 +160,17 @@ pub struct StreakUserData {
     pub best: u32,
     pub latest_time_string: TimePeriodString,
 }
+
+impl StreakUserData {
+    // Keeps the longer streak when two accounts of the same contributor are merged
+    pub fn merge(self, other: StreakUserData) -> StreakUserData {
+        let best = self.best.max(other.best);
+        let mut result = if other.amount > self.amount {
+            other
+        } else {
+            self
+        };
+        result.best = best;
+        result
+    }
+}
@@ -0,0 +1,55 @@
+use super::*;
+
+pub type TeamName = String;
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedTeam {
+    V1(Team),
+}
+
+impl From<VersionedTeam> for Team {
+    fn from(message: VersionedTeam) -> Self {
+        match message {
+            VersionedTeam::V1(x) => x,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Team {
+    pub name: TeamName,
+    // Maintainers of this organization approve new members
+    pub organization: String,
+    pub members: Vec<GithubHandle>,
+}
+
+impl Team {
+    pub fn new(name: TeamName, organization: String) -> Self {
+        Self {
+            name,
+            organization,
+            members: vec![],
+        }
+    }
+
+    pub fn add_member(&mut self, user: GithubHandle) -> bool {
+        if self.members.contains(&user) {
+            return false;
+        }
+
+        self.members.push(user);
+        true
+    }
+
+    pub fn remove_member(&mut self, user: &str) -> bool {
+        let len = self.members.len();
+        self.members.retain(|member| member != user);
+        len != self.members.len()
+    }
+}
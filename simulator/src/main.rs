@@ -0,0 +1,159 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context};
+use shared::GithubHandle;
+
+mod replay;
+mod source;
+
+use replay::Proposal;
+
+const USAGE: &str = "Usage: race-of-sloths-simulator (--db <database-url> | --json <prs.json>) \
+    --contract <account-id> [--network mainnet|testnet] --proposal <proposal.json> \
+    [--export <prs.json>]";
+
+#[derive(Default)]
+struct Args {
+    db: Option<String>,
+    json: Option<PathBuf>,
+    contract: Option<String>,
+    network: Option<String>,
+    proposal: Option<PathBuf>,
+    export: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> anyhow::Result<Self> {
+        let mut args = Self::default();
+        let mut input = std::env::args().skip(1);
+        while let Some(arg) = input.next() {
+            let value = input.next().context(USAGE)?;
+            match arg.as_str() {
+                "--db" => args.db = Some(value),
+                "--json" => args.json = Some(value.into()),
+                "--contract" => args.contract = Some(value),
+                "--network" => args.network = Some(value),
+                "--proposal" => args.proposal = Some(value.into()),
+                "--export" => args.export = Some(value.into()),
+                _ => bail!(USAGE),
+            }
+        }
+        Ok(args)
+    }
+}
+
+struct LeaderboardDiff {
+    user: GithubHandle,
+    place_before: Option<usize>,
+    place_after: Option<usize>,
+    rating_before: u32,
+    rating_after: u32,
+}
+
+fn places(ratings: &HashMap<GithubHandle, u32>) -> HashMap<GithubHandle, usize> {
+    let mut leaderboard: Vec<_> = ratings.iter().collect();
+    leaderboard.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    leaderboard
+        .into_iter()
+        .enumerate()
+        .map(|(place, (user, _))| (user.clone(), place + 1))
+        .collect()
+}
+
+fn diff(
+    before: &HashMap<GithubHandle, u32>,
+    after: &HashMap<GithubHandle, u32>,
+) -> Vec<LeaderboardDiff> {
+    let places_before = places(before);
+    let places_after = places(after);
+
+    let mut diff: Vec<_> = before
+        .keys()
+        .chain(after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|user| LeaderboardDiff {
+            user: user.clone(),
+            place_before: places_before.get(user).copied(),
+            place_after: places_after.get(user).copied(),
+            rating_before: before.get(user).copied().unwrap_or_default(),
+            rating_after: after.get(user).copied().unwrap_or_default(),
+        })
+        .collect();
+    diff.sort_by_key(|d| d.place_after.unwrap_or(usize::MAX));
+    diff
+}
+
+fn place(place: Option<usize>) -> String {
+    place
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse()?;
+
+    let records = match (&args.db, &args.json) {
+        (Some(database_url), None) => source::load_from_db(database_url).await?,
+        (None, Some(path)) => source::load_from_json(path)?,
+        _ => bail!(USAGE),
+    };
+    if let Some(path) = &args.export {
+        source::export_to_json(path, &records)?;
+    }
+
+    let mut proposal: Proposal = match &args.proposal {
+        Some(path) => serde_json::from_reader(std::fs::File::open(path)?)?,
+        None => bail!(USAGE),
+    };
+
+    let mainnet = match args.network.as_deref() {
+        None | Some("mainnet") => true,
+        Some("testnet") => false,
+        Some(_) => bail!(USAGE),
+    };
+    let authors: Vec<GithubHandle> = records
+        .iter()
+        .map(|record| record.author.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let state = match &args.contract {
+        Some(contract) => source::load_from_contract(contract, mainnet, &authors).await?,
+        None => bail!(USAGE),
+    };
+    let current = Proposal::current(&state.streaks);
+    if proposal.streaks.is_none() {
+        proposal.streaks = current.streaks.clone();
+    }
+
+    eprintln!(
+        "Replaying {} PRs with {} referrals",
+        records.len(),
+        state.referrals.len()
+    );
+    let before = replay::replay(&records, &state.referrals, &current);
+    let after = replay::replay(&records, &state.referrals, &proposal);
+
+    println!(
+        "{:<8} {:<8} {:<40} {:>10} {:>10} {:>10}",
+        "Before", "After", "User", "Rating", "Proposed", "Diff"
+    );
+    for d in diff(&before, &after) {
+        println!(
+            "{:<8} {:<8} {:<40} {:>10} {:>10} {:>+10}",
+            place(d.place_before),
+            place(d.place_after),
+            d.user,
+            d.rating_before,
+            d.rating_after,
+            d.rating_after as i64 - d.rating_before as i64
+        );
+    }
+
+    Ok(())
+}
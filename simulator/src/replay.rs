@@ -0,0 +1,274 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId, Timestamp};
+use serde::Deserialize;
+use shared::{
+    AllowedRepos, GithubHandle, Streak, StreakReward, StreakType, TimePeriod,
+    SCORE_RATING_MULTIPLIER, SCORE_TIMEOUT_IN_NANOSECONDS,
+};
+use slothrace_storage_contract::Contract;
+
+use crate::source::PullRequestRecord;
+
+// Scores are put on behalf of a single reviewer, as the DB keeps only the average one
+const REVIEWER: &str = "race-of-sloths-simulator";
+const PAGE_SIZE: u64 = 100;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreakConfig {
+    pub name: String,
+    pub time_period: TimePeriod,
+    pub streak_criterias: Vec<StreakType>,
+    pub streak_rewards: Vec<StreakReward>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Proposal {
+    // Replaces the streaks of the contract, the current ones are kept if not set
+    pub streaks: Option<Vec<StreakConfig>>,
+    #[serde(default = "default_score_multiplier")]
+    pub score_multiplier: u32,
+}
+
+impl Proposal {
+    // Rules of the contract as they are now
+    pub fn current(streaks: &[Streak]) -> Self {
+        Self {
+            streaks: Some(
+                streaks
+                    .iter()
+                    .filter(|streak| streak.is_active)
+                    .map(|streak| StreakConfig {
+                        name: streak.name.clone(),
+                        time_period: streak.time_period,
+                        streak_criterias: streak.streak_criterias.clone(),
+                        streak_rewards: streak.streak_rewards.clone(),
+                    })
+                    .collect(),
+            ),
+            score_multiplier: SCORE_RATING_MULTIPLIER,
+        }
+    }
+}
+
+fn default_score_multiplier() -> u32 {
+    SCORE_RATING_MULTIPLIER
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Step {
+    Include(usize),
+    Refer(usize),
+    Merge(usize),
+    Finalize(usize),
+}
+
+fn timestamp(time: chrono::NaiveDateTime) -> Timestamp {
+    time.and_utc().timestamp_nanos_opt().unwrap_or_default() as Timestamp
+}
+
+fn sloth() -> AccountId {
+    "sloth.near".parse().unwrap()
+}
+
+// Replays the PRs through the contract and returns the total rating of every author.
+// Referrals (referee -> referrer) are applied on the first PR of the referee.
+pub fn replay(
+    records: &[PullRequestRecord],
+    referrals: &HashMap<GithubHandle, GithubHandle>,
+    proposal: &Proposal,
+) -> HashMap<GithubHandle, u32> {
+    // The mocked blockchain is thread local, so every replay starts with the empty storage
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| replay_in_new_env(records, referrals, proposal))
+            .join()
+            .expect("Replay panicked")
+    })
+}
+
+fn replay_in_new_env(
+    records: &[PullRequestRecord],
+    referrals: &HashMap<GithubHandle, GithubHandle>,
+    proposal: &Proposal,
+) -> HashMap<GithubHandle, u32> {
+    let mut context = VMContextBuilder::new()
+        .predecessor_account_id(sloth())
+        .build();
+    testing_env!(context.clone());
+
+    let mut repos: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for record in records {
+        repos
+            .entry(record.organization.clone())
+            .or_default()
+            .insert(record.repo.clone());
+    }
+    let allowed_repos = repos
+        .into_iter()
+        .map(|(organization, repos)| AllowedRepos {
+            organization,
+            repos: repos.into_iter().collect(),
+        })
+        .collect();
+    let mut contract = Contract::new(sloth(), allowed_repos);
+
+    if let Some(streaks) = &proposal.streaks {
+        for streak in contract.streaks() {
+            contract.deactivate_streak(streak.id);
+        }
+        for streak in streaks.clone() {
+            contract.create_streak(
+                streak.name,
+                streak.time_period,
+                streak.streak_criterias,
+                streak.streak_rewards,
+            );
+        }
+    }
+
+    let mut first_prs: HashMap<&GithubHandle, usize> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        let first = first_prs.entry(&record.author).or_insert(index);
+        if record.created_at < records[*first].created_at {
+            *first = index;
+        }
+    }
+
+    let mut steps = vec![];
+    for (index, record) in records.iter().enumerate() {
+        steps.push((timestamp(record.created_at), Step::Include(index)));
+        if first_prs[&record.author] == index && referrals.contains_key(&record.author) {
+            steps.push((timestamp(record.created_at), Step::Refer(index)));
+        }
+        if let Some(merged_at) = record.merged_at {
+            steps.push((timestamp(merged_at), Step::Merge(index)));
+            if record.executed {
+                steps.push((
+                    timestamp(merged_at) + SCORE_TIMEOUT_IN_NANOSECONDS + 1,
+                    Step::Finalize(index),
+                ));
+            }
+        }
+    }
+    steps.sort();
+
+    let mut failed = 0;
+    for (timestamp, step) in steps {
+        context.block_timestamp = timestamp;
+        testing_env!(context.clone());
+
+        // The failed step is skipped, so a single broken record doesn't stop the replay
+        let result = catch_unwind(AssertUnwindSafe(|| match step {
+            Step::Include(index) => {
+                let record = &records[index];
+                contract.sloth_include(
+                    record.organization.clone(),
+                    record.repo.clone(),
+                    record.author.clone(),
+                    record.number as u64,
+                    timestamp,
+                    true,
+                    None,
+                    None,
+                    None,
+                );
+            }
+            Step::Refer(index) => {
+                let author = &records[index].author;
+                contract.sloth_referred_by(author.clone(), referrals[author].clone());
+            }
+            Step::Merge(index) => {
+                let record = &records[index];
+                if let Some(score) = record.score {
+                    contract.sloth_scored(record.pr_id(), REVIEWER.to_string(), score as u32, None);
+                }
+                contract.sloth_merged(record.pr_id(), timestamp);
+            }
+            Step::Finalize(index) => {
+                contract.sloth_finalize(records[index].pr_id(), Some(timestamp));
+            }
+        }));
+        if result.is_err() {
+            failed += 1;
+            eprintln!("Failed to replay {step:?}");
+        }
+    }
+    if failed > 0 {
+        eprintln!("{failed} steps failed to replay and were skipped");
+    }
+
+    let mut ratings: HashMap<GithubHandle, u32> = HashMap::new();
+    let mut page = 0;
+    loop {
+        let prs = contract.prs(PAGE_SIZE, page);
+        if prs.is_empty() {
+            break;
+        }
+        for (pr, executed) in prs {
+            if executed {
+                *ratings.entry(pr.author.clone()).or_default() +=
+                    pr.rating_with_multiplier(proposal.score_multiplier);
+            }
+        }
+        page += 1;
+    }
+
+    for (referee, referrer) in referrals {
+        let bonus = contract
+            .referral(referee.clone())
+            .and_then(|referral| referral.rewarded);
+        if let Some(bonus) = bonus {
+            *ratings.entry(referrer.clone()).or_default() += bonus.rating;
+        }
+    }
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(author: &str, number: i32, created_day: i64, score: i32) -> PullRequestRecord {
+        let day = |day: i64| {
+            chrono::DateTime::from_timestamp(day * 24 * 60 * 60, 0)
+                .unwrap()
+                .naive_utc()
+        };
+        PullRequestRecord {
+            organization: "NEAR-DevHub".to_string(),
+            repo: "devbot".to_string(),
+            number,
+            author: author.to_string(),
+            created_at: day(created_day),
+            merged_at: Some(day(created_day + 1)),
+            score: Some(score),
+            executed: true,
+        }
+    }
+
+    #[test]
+    fn replay_with_doubled_multiplier() {
+        let records = vec![record("alice", 1, 1, 5), record("bob", 2, 10, 8)];
+        let referrals = [("bob".to_string(), "alice".to_string())]
+            .into_iter()
+            .collect();
+        let proposal = |score_multiplier| Proposal {
+            streaks: Some(vec![]),
+            score_multiplier,
+        };
+
+        let before = replay(&records, &referrals, &proposal(SCORE_RATING_MULTIPLIER));
+        let after = replay(&records, &referrals, &proposal(2 * SCORE_RATING_MULTIPLIER));
+
+        // The referral bonus doesn't depend on the multiplier
+        let bonus = shared::REFERRAL_BONUS_RATING;
+        assert_eq!(before["alice"], 5 * SCORE_RATING_MULTIPLIER + bonus);
+        assert_eq!(
+            after["alice"] - before["alice"],
+            5 * SCORE_RATING_MULTIPLIER
+        );
+        assert_eq!(after["bob"] - before["bob"], 8 * SCORE_RATING_MULTIPLIER);
+    }
+}
@@ -0,0 +1,109 @@
+use std::{collections::HashMap, path::Path};
+
+use near_workspaces::{network::NetworkClient, AccountId, Worker};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::{GithubHandle, Referral, Streak, User};
+use sqlx::postgres::PgPoolOptions;
+
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct PullRequestRecord {
+    pub organization: String,
+    pub repo: String,
+    pub number: i32,
+    pub author: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub merged_at: Option<chrono::NaiveDateTime>,
+    pub score: Option<i32>,
+    pub executed: bool,
+}
+
+impl PullRequestRecord {
+    pub fn pr_id(&self) -> String {
+        format!("{}/{}/{}", self.organization, self.repo, self.number)
+    }
+}
+
+pub async fn load_from_db(database_url: &str) -> anyhow::Result<Vec<PullRequestRecord>> {
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(database_url)
+        .await?;
+
+    let records = sqlx::query_as::<_, PullRequestRecord>(
+        r#"
+        SELECT o.login as organization, r.name as repo, pr.number, u.login as author,
+            pr.created_at, pr.merged_at, pr.score, pr.executed
+        FROM pull_requests pr
+        JOIN repos r ON pr.repo_id = r.id
+        JOIN organizations o ON r.organization_id = o.id
+        JOIN users u ON pr.author_id = u.id
+        ORDER BY pr.created_at
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(records)
+}
+
+pub fn load_from_json(path: &Path) -> anyhow::Result<Vec<PullRequestRecord>> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+pub fn export_to_json(path: &Path, records: &[PullRequestRecord]) -> anyhow::Result<()> {
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer_pretty(file, records)?;
+    Ok(())
+}
+
+// Rules and referrals currently stored in the contract
+pub struct ContractState {
+    pub streaks: Vec<Streak>,
+    // Referee -> referrer
+    pub referrals: HashMap<GithubHandle, GithubHandle>,
+}
+
+pub async fn load_from_contract(
+    contract: &str,
+    mainnet: bool,
+    authors: &[GithubHandle],
+) -> anyhow::Result<ContractState> {
+    let contract: AccountId = contract.parse()?;
+    if mainnet {
+        load_state(&near_workspaces::mainnet().await?, &contract, authors).await
+    } else {
+        load_state(&near_workspaces::testnet().await?, &contract, authors).await
+    }
+}
+
+async fn load_state<T: NetworkClient + ?Sized>(
+    worker: &Worker<T>,
+    contract: &AccountId,
+    authors: &[GithubHandle],
+) -> anyhow::Result<ContractState> {
+    let streaks: Vec<Streak> = worker.view(contract, "streaks").await?.json()?;
+
+    let mut referrals = HashMap::new();
+    for author in authors {
+        let referral: Option<Referral> = worker
+            .view(contract, "referral")
+            .args_json(json!({ "user": author }))
+            .await?
+            .json()?;
+        let Some(referral) = referral else {
+            continue;
+        };
+        let referrer: Option<User> = worker
+            .view(contract, "user_by_id")
+            .args_json(json!({ "user_id": referral.referrer, "periods": [] }))
+            .await?
+            .json()?;
+        if let Some(referrer) = referrer {
+            referrals.insert(author.clone(), referrer.name);
+        }
+    }
+
+    Ok(ContractState { streaks, referrals })
+}